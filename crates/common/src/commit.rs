@@ -1,6 +1,7 @@
 //! Plaintext commitment and proof of encryption.
 
 pub mod hash;
+pub mod predicate;
 
 use mpz_core::bitvec::BitVec;
 use mpz_memory_core::{binary::Binary, DecodeFutureTyped};
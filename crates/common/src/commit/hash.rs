@@ -1,24 +1,34 @@
 //! Plaintext hash commitments.
 
-use std::collections::HashMap;
+mod blake3;
+pub(crate) mod circuit;
+mod keccak;
 
+use std::{collections::HashMap, sync::Arc};
+
+use mpz_circuits::Circuit;
 use mpz_core::bitvec::BitVec;
 use mpz_hash::sha256::Sha256;
 use mpz_memory_core::{
     binary::{Binary, U8},
     DecodeFutureTyped, MemoryExt, Vector,
 };
-use mpz_vm_core::{prelude::*, Vm, VmError};
+use mpz_vm_core::{prelude::*, Call, Vm, VmError};
 use tlsn_core::{
     hash::{Blinder, Hash, HashAlgId, TypedHash},
     transcript::{
-        hash::{PlaintextHash, PlaintextHashSecret},
+        hash::{
+            plaintext_mask, MaskedPlaintextHash, MaskedPlaintextHashSecret, PlaintextHash,
+            PlaintextHashSecret,
+        },
         Direction, Idx,
     },
 };
 
 use crate::{transcript::TranscriptRefs, Role};
 
+use self::{blake3::build_blake3, circuit::build_mask, keccak::build_keccak256};
+
 /// Future which will resolve to the committed hash values.
 #[derive(Debug)]
 pub struct HashCommitFuture {
@@ -114,39 +124,213 @@ fn hash_commit_inner(
     idxs: impl IntoIterator<Item = (Direction, Idx, HashAlgId)>,
 ) -> Result<Vec<(Direction, Idx, HashAlgId, Array<U8, 32>, Vector<U8>)>, HashCommitError> {
     let mut output = Vec::new();
-    let mut hashers = HashMap::new();
+    let mut hasher = Hasher::default();
     for (direction, idx, alg) in idxs {
-        let blinder = vm.alloc_vec::<U8>(16)?;
-        match role {
-            Role::Prover => vm.mark_private(blinder)?,
-            Role::Verifier => vm.mark_blind(blinder)?,
+        let blinder = alloc_blinder(vm, role)?;
+
+        let mut inputs = refs.get(direction, &idx).expect("plaintext refs are valid");
+        inputs.push(blinder);
+
+        let hash = hasher.hash(vm, alg, &inputs)?;
+
+        output.push((direction, idx, alg, hash, blinder));
+    }
+
+    Ok(output)
+}
+
+/// Future which will resolve to the committed masked hash values.
+#[derive(Debug)]
+pub struct MaskedHashCommitFuture {
+    #[allow(clippy::type_complexity)]
+    futs: Vec<(Direction, HashAlgId, DecodeFutureTyped<BitVec, Vec<u8>>)>,
+}
+
+impl MaskedHashCommitFuture {
+    /// Tries to receive the value, returning an error if the value is not
+    /// ready.
+    pub fn try_recv(self) -> Result<Vec<MaskedPlaintextHash>, HashCommitError> {
+        let mut output = Vec::new();
+        for (direction, alg, mut fut) in self.futs {
+            let hash = fut
+                .try_recv()
+                .map_err(|_| HashCommitError::decode())?
+                .ok_or_else(HashCommitError::decode)?;
+            output.push(MaskedPlaintextHash {
+                direction,
+                hash: TypedHash {
+                    alg,
+                    value: Hash::try_from(hash).map_err(HashCommitError::convert)?,
+                },
+            });
         }
 
-        let hash = match alg {
+        Ok(output)
+    }
+}
+
+/// Prove masked plaintext hash commitments.
+///
+/// The committed ranges are private inputs of the prover, the verifier only
+/// learns the direction and the hash algorithm.
+pub fn prove_masked_hash(
+    vm: &mut dyn Vm<Binary>,
+    refs: &TranscriptRefs,
+    idxs: impl IntoIterator<Item = (Direction, Idx, HashAlgId)>,
+) -> Result<(MaskedHashCommitFuture, Vec<MaskedPlaintextHashSecret>), HashCommitError> {
+    let mut futs = Vec::new();
+    let mut secrets = Vec::new();
+    let mut hasher = Hasher::default();
+    for (direction, idx, alg) in idxs {
+        let plaintext = direction_refs(refs, direction);
+        let len = plaintext.iter().map(|slice| slice.len()).sum::<usize>();
+
+        let mask = vm.alloc_vec::<U8>(len.div_ceil(8))?;
+        vm.mark_private(mask)?;
+        vm.assign(mask, plaintext_mask(&idx, len))?;
+        vm.commit(mask)?;
+
+        let blinder_ref = alloc_blinder(vm, Role::Prover)?;
+        let blinder: Blinder = rand::random();
+        vm.assign(blinder_ref, blinder.as_bytes().to_vec())?;
+        vm.commit(blinder_ref)?;
+
+        let hash_ref = masked_hash(vm, &mut hasher, alg, &plaintext, mask, blinder_ref)?;
+        let hash_fut = vm.decode(Vector::<U8>::from(hash_ref))?;
+
+        futs.push((direction, alg, hash_fut));
+        secrets.push(MaskedPlaintextHashSecret {
+            direction,
+            idx,
+            alg,
+            blinder,
+        });
+    }
+
+    Ok((MaskedHashCommitFuture { futs }, secrets))
+}
+
+/// Verify masked plaintext hash commitments.
+pub fn verify_masked_hash(
+    vm: &mut dyn Vm<Binary>,
+    refs: &TranscriptRefs,
+    commits: impl IntoIterator<Item = (Direction, HashAlgId)>,
+) -> Result<MaskedHashCommitFuture, HashCommitError> {
+    let mut futs = Vec::new();
+    let mut hasher = Hasher::default();
+    for (direction, alg) in commits {
+        let plaintext = direction_refs(refs, direction);
+        let len = plaintext.iter().map(|slice| slice.len()).sum::<usize>();
+
+        let mask = vm.alloc_vec::<U8>(len.div_ceil(8))?;
+        vm.mark_blind(mask)?;
+        vm.commit(mask)?;
+
+        let blinder_ref = alloc_blinder(vm, Role::Verifier)?;
+        vm.commit(blinder_ref)?;
+
+        let hash_ref = masked_hash(vm, &mut hasher, alg, &plaintext, mask, blinder_ref)?;
+        let hash_fut = vm.decode(Vector::<U8>::from(hash_ref))?;
+
+        futs.push((direction, alg, hash_fut));
+    }
+
+    Ok(MaskedHashCommitFuture { futs })
+}
+
+/// Computes `H(view | mask | blinder)` where the view is the plaintext with
+/// all bytes outside of the mask set to zero.
+fn masked_hash(
+    vm: &mut dyn Vm<Binary>,
+    hasher: &mut Hasher,
+    alg: HashAlgId,
+    plaintext: &[Vector<U8>],
+    mask: Vector<U8>,
+    blinder: Vector<U8>,
+) -> Result<Array<U8, 32>, HashCommitError> {
+    let len = plaintext.iter().map(|slice| slice.len()).sum::<usize>();
+
+    let mut call = Call::builder(build_mask(len));
+    for slice in plaintext {
+        call = call.arg(*slice);
+    }
+    call = call.arg(mask);
+
+    let view: Vector<U8> = vm.call(call.build().map_err(HashCommitError::hasher)?)?;
+
+    hasher.hash(vm, alg, &[view, mask, blinder])
+}
+
+/// Returns the references to the whole transcript in the given direction.
+fn direction_refs(refs: &TranscriptRefs, direction: Direction) -> Vec<Vector<U8>> {
+    match direction {
+        Direction::Sent => refs.sent().to_vec(),
+        Direction::Received => refs.recv().to_vec(),
+    }
+}
+
+fn alloc_blinder(vm: &mut dyn Vm<Binary>, role: Role) -> Result<Vector<U8>, HashCommitError> {
+    let blinder = vm.alloc_vec::<U8>(16)?;
+    match role {
+        Role::Prover => vm.mark_private(blinder)?,
+        Role::Verifier => vm.mark_blind(blinder)?,
+    }
+
+    Ok(blinder)
+}
+
+/// Hashes the concatenation of inputs in the VM.
+#[derive(Default)]
+struct Hasher {
+    sha256: Option<Sha256>,
+    /// Circuits depend on the message length, so they are built on demand.
+    circuits: HashMap<(HashAlgId, usize), Arc<Circuit>>,
+}
+
+impl Hasher {
+    fn hash(
+        &mut self,
+        vm: &mut dyn Vm<Binary>,
+        alg: HashAlgId,
+        inputs: &[Vector<U8>],
+    ) -> Result<Array<U8, 32>, HashCommitError> {
+        match alg {
             HashAlgId::SHA256 => {
-                let mut hasher = if let Some(hasher) = hashers.get(&alg).cloned() {
+                let mut hasher = if let Some(hasher) = self.sha256.clone() {
                     hasher
                 } else {
                     let hasher = Sha256::new_with_init(vm).map_err(HashCommitError::hasher)?;
-                    hashers.insert(alg, hasher.clone());
+                    self.sha256 = Some(hasher.clone());
                     hasher
                 };
 
-                for plaintext in refs.get(direction, &idx).expect("plaintext refs are valid") {
-                    hasher.update(&plaintext);
+                for input in inputs {
+                    hasher.update(input);
                 }
-                hasher.update(&blinder);
-                hasher.finalize(vm).map_err(HashCommitError::hasher)?
-            }
-            alg => {
-                return Err(HashCommitError::unsupported_alg(alg));
+                hasher.finalize(vm).map_err(HashCommitError::hasher)
             }
-        };
+            HashAlgId::BLAKE3 | HashAlgId::KECCAK256 => {
+                let len = inputs.iter().map(|input| input.len()).sum::<usize>();
 
-        output.push((direction, idx, alg, hash, blinder));
-    }
+                let circuit = self
+                    .circuits
+                    .entry((alg, len))
+                    .or_insert_with(|| match alg {
+                        HashAlgId::BLAKE3 => build_blake3(len),
+                        _ => build_keccak256(len),
+                    })
+                    .clone();
 
-    Ok(output)
+                let mut call = Call::builder(circuit);
+                for input in inputs {
+                    call = call.arg(*input);
+                }
+
+                Ok(vm.call(call.build().map_err(HashCommitError::hasher)?)?)
+            }
+            alg => Err(HashCommitError::unsupported_alg(alg)),
+        }
+    }
 }
 
 /// Error type for hash commitments.
@@ -195,3 +379,66 @@ impl From<VmError> for HashCommitError {
         Self(ErrorRepr::Vm(value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::test_utils::mock_vm;
+    use mpz_common::context::test_st_context;
+    use mpz_memory_core::{Array, ViewExt};
+    use mpz_vm_core::Execute;
+    use rstest::rstest;
+    use tlsn_core::hash::{Blake3, HashAlgorithm, Keccak256};
+
+    #[rstest]
+    #[case::blake3(HashAlgId::BLAKE3, 20)]
+    #[case::blake3_multi_chunk(HashAlgId::BLAKE3, 1100)]
+    #[case::keccak256(HashAlgId::KECCAK256, 20)]
+    #[case::keccak256_multi_block(HashAlgId::KECCAK256, 300)]
+    #[tokio::test]
+    async fn test_hash_circuit(#[case] alg: HashAlgId, #[case] len: usize) {
+        let msg: Vec<u8> = (0..len).map(|i| i as u8).collect();
+
+        let (circuit, expected) = match alg {
+            HashAlgId::BLAKE3 => (build_blake3(len), Blake3::default().hash(&msg)),
+            _ => (build_keccak256(len), Keccak256::default().hash(&msg)),
+        };
+
+        let (mut ctx_a, mut ctx_b) = test_st_context(8);
+        let (mut gen, mut ev) = mock_vm();
+
+        let hash_gen = alloc_hash(&mut gen, circuit.clone(), &msg);
+        let hash_ev = alloc_hash(&mut ev, circuit, &msg);
+
+        let (hash_gen, hash_ev) = tokio::try_join!(
+            async {
+                let out = gen.decode(hash_gen).unwrap();
+                gen.flush(&mut ctx_a).await.unwrap();
+                gen.execute(&mut ctx_a).await.unwrap();
+                gen.flush(&mut ctx_a).await.unwrap();
+                out.await
+            },
+            async {
+                let out = ev.decode(hash_ev).unwrap();
+                ev.flush(&mut ctx_b).await.unwrap();
+                ev.execute(&mut ctx_b).await.unwrap();
+                ev.flush(&mut ctx_b).await.unwrap();
+                out.await
+            }
+        )
+        .unwrap();
+
+        assert_eq!(hash_gen, hash_ev);
+        assert_eq!(Hash::try_from(hash_gen.to_vec()).unwrap(), expected);
+    }
+
+    fn alloc_hash(vm: &mut dyn Vm<Binary>, circuit: Arc<Circuit>, msg: &[u8]) -> Array<U8, 32> {
+        let msg_ref: Vector<U8> = vm.alloc_vec(msg.len()).unwrap();
+        vm.mark_public(msg_ref).unwrap();
+        vm.assign(msg_ref, msg.to_vec()).unwrap();
+        vm.commit(msg_ref).unwrap();
+
+        vm.call(Call::builder(circuit).arg(msg_ref).build().unwrap())
+            .unwrap()
+    }
+}
@@ -0,0 +1,165 @@
+//! BLAKE3 circuit.
+//!
+//! The message length is public, so the chunk and block layout as well as the
+//! shape of the hash tree are fixed when the circuit is built.
+
+use std::sync::Arc;
+
+use mpz_circuits::Circuit;
+
+use super::circuit::{const_bits, Builder, Wire, Word};
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+const ROUNDS: usize = 7;
+
+const BLOCK_LEN: usize = 64;
+const CHUNK_LEN: usize = 1024;
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+type ChainingValue = [Word; 8];
+
+/// Builds a BLAKE3 circuit for a message of `len` bytes.
+///
+/// The circuit takes the message as input and outputs the 32 byte digest.
+pub(super) fn build_blake3(len: usize) -> Arc<Circuit> {
+    let mut builder = Builder::new();
+
+    let msg = builder.add_input_bytes(len);
+    let mut chunks: Vec<&[Wire]> = msg.chunks(8 * CHUNK_LEN).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+
+    let output = subtree(&mut builder, &chunks, 0, true);
+
+    for word in output {
+        for bit in word {
+            builder.add_output(bit);
+        }
+    }
+
+    Arc::new(builder.build())
+}
+
+/// Computes the chaining value of a subtree of chunks.
+///
+/// The left subtree holds the largest power of two number of chunks such that
+/// the right subtree is not empty.
+fn subtree(builder: &mut Builder, chunks: &[&[Wire]], start: usize, root: bool) -> ChainingValue {
+    if chunks.len() == 1 {
+        return chunk(builder, chunks[0], start as u64, root);
+    }
+
+    let mut left_len = 1;
+    while 2 * left_len < chunks.len() {
+        left_len *= 2;
+    }
+
+    let left = subtree(builder, &chunks[..left_len], start, false);
+    let right = subtree(builder, &chunks[left_len..], start + left_len, false);
+
+    let mut block = [[Wire::Const(false); 32]; 16];
+    block[..8].copy_from_slice(&left);
+    block[8..].copy_from_slice(&right);
+
+    let flags = PARENT | if root { ROOT } else { 0 };
+
+    compress(builder, iv(), block, 0, BLOCK_LEN as u32, flags)
+}
+
+/// Computes the chaining value of a chunk.
+fn chunk(builder: &mut Builder, chunk: &[Wire], counter: u64, root: bool) -> ChainingValue {
+    let mut blocks: Vec<&[Wire]> = chunk.chunks(8 * BLOCK_LEN).collect();
+    if blocks.is_empty() {
+        blocks.push(&[]);
+    }
+
+    let mut cv = iv();
+    let last = blocks.len() - 1;
+    for (i, block) in blocks.into_iter().enumerate() {
+        let mut flags = 0;
+        if i == 0 {
+            flags |= CHUNK_START;
+        }
+        if i == last {
+            flags |= CHUNK_END;
+            if root {
+                flags |= ROOT;
+            }
+        }
+
+        // Partial blocks are padded with zeroes.
+        let words = std::array::from_fn(|j| {
+            std::array::from_fn(|k| block.get(32 * j + k).copied().unwrap_or(Wire::Const(false)))
+        });
+
+        cv = compress(builder, cv, words, counter, (block.len() / 8) as u32, flags);
+    }
+
+    cv
+}
+
+/// The BLAKE3 compression function, truncated to the chaining value.
+fn compress(
+    builder: &mut Builder,
+    cv: ChainingValue,
+    mut block: [Word; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> ChainingValue {
+    let mut state = [[Wire::Const(false); 32]; 16];
+    state[..8].copy_from_slice(&cv);
+    state[8..12].copy_from_slice(&iv()[..4]);
+    state[12] = const_bits(counter);
+    state[13] = const_bits(counter >> 32);
+    state[14] = const_bits(block_len as u64);
+    state[15] = const_bits(flags as u64);
+
+    for round in 0..ROUNDS {
+        g(builder, &mut state, [0, 4, 8, 12], block[0], block[1]);
+        g(builder, &mut state, [1, 5, 9, 13], block[2], block[3]);
+        g(builder, &mut state, [2, 6, 10, 14], block[4], block[5]);
+        g(builder, &mut state, [3, 7, 11, 15], block[6], block[7]);
+        g(builder, &mut state, [0, 5, 10, 15], block[8], block[9]);
+        g(builder, &mut state, [1, 6, 11, 12], block[10], block[11]);
+        g(builder, &mut state, [2, 7, 8, 13], block[12], block[13]);
+        g(builder, &mut state, [3, 4, 9, 14], block[14], block[15]);
+
+        if round < ROUNDS - 1 {
+            block = std::array::from_fn(|i| block[MSG_PERMUTATION[i]]);
+        }
+    }
+
+    std::array::from_fn(|i| builder.xor_word(state[i], state[i + 8]))
+}
+
+/// The quarter-round function.
+fn g(builder: &mut Builder, state: &mut [Word; 16], [a, b, c, d]: [usize; 4], mx: Word, my: Word) {
+    state[a] = builder.add_word(state[a], state[b]);
+    state[a] = builder.add_word(state[a], mx);
+    state[d] = rotate_right(builder.xor_word(state[d], state[a]), 16);
+    state[c] = builder.add_word(state[c], state[d]);
+    state[b] = rotate_right(builder.xor_word(state[b], state[c]), 12);
+
+    state[a] = builder.add_word(state[a], state[b]);
+    state[a] = builder.add_word(state[a], my);
+    state[d] = rotate_right(builder.xor_word(state[d], state[a]), 8);
+    state[c] = builder.add_word(state[c], state[d]);
+    state[b] = rotate_right(builder.xor_word(state[b], state[c]), 7);
+}
+
+fn rotate_right(word: Word, n: usize) -> Word {
+    std::array::from_fn(|i| word[(i + n) % 32])
+}
+
+fn iv() -> ChainingValue {
+    IV.map(|word| const_bits(word as u64))
+}
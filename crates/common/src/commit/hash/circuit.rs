@@ -0,0 +1,150 @@
+//! Helpers for building hash circuits.
+//!
+//! Wires which carry a public constant, e.g. message padding or an initial
+//! state, are folded while building so that they do not produce gates.
+
+use std::sync::Arc;
+
+use mpz_circuits::{Circuit, CircuitBuilder, Feed, Node};
+
+/// A wire in a circuit under construction.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Wire {
+    /// A public constant.
+    Const(bool),
+    /// A wire computed in the circuit.
+    Node(Node<Feed>),
+}
+
+/// A 32-bit word, least significant bit first.
+pub(crate) type Word = [Wire; 32];
+
+/// Circuit builder with constant folding.
+pub(crate) struct Builder {
+    inner: CircuitBuilder,
+    /// Any input of the circuit, used to express constant outputs.
+    source: Option<Node<Feed>>,
+}
+
+impl Builder {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: CircuitBuilder::new(),
+            source: None,
+        }
+    }
+
+    /// Adds `len` input bytes, each encoded least significant bit first.
+    pub(crate) fn add_input_bytes(&mut self, len: usize) -> Vec<Wire> {
+        (0..8 * len)
+            .map(|_| {
+                let node = self.inner.add_input();
+                self.source.get_or_insert(node);
+                Wire::Node(node)
+            })
+            .collect()
+    }
+
+    pub(crate) fn add_output(&mut self, wire: Wire) {
+        let node = match wire {
+            Wire::Node(node) => self.inner.add_id_gate(node),
+            Wire::Const(value) => {
+                let source = self.source.expect("circuit has at least one input");
+                let zero = self.inner.add_xor_gate(source, source);
+                if value {
+                    self.inner.add_inv_gate(zero)
+                } else {
+                    zero
+                }
+            }
+        };
+        self.inner.add_output(node);
+    }
+
+    pub(crate) fn build(self) -> Circuit {
+        self.inner.build().expect("hash circuit should be valid")
+    }
+
+    pub(crate) fn xor(&mut self, a: Wire, b: Wire) -> Wire {
+        match (a, b) {
+            (Wire::Const(a), Wire::Const(b)) => Wire::Const(a ^ b),
+            (Wire::Node(a), Wire::Const(c)) | (Wire::Const(c), Wire::Node(a)) => {
+                if c {
+                    Wire::Node(self.inner.add_inv_gate(a))
+                } else {
+                    Wire::Node(a)
+                }
+            }
+            (Wire::Node(a), Wire::Node(b)) => Wire::Node(self.inner.add_xor_gate(a, b)),
+        }
+    }
+
+    pub(crate) fn and(&mut self, a: Wire, b: Wire) -> Wire {
+        match (a, b) {
+            (Wire::Const(a), Wire::Const(b)) => Wire::Const(a & b),
+            (Wire::Node(a), Wire::Const(c)) | (Wire::Const(c), Wire::Node(a)) => {
+                if c {
+                    Wire::Node(a)
+                } else {
+                    Wire::Const(false)
+                }
+            }
+            (Wire::Node(a), Wire::Node(b)) => Wire::Node(self.inner.add_and_gate(a, b)),
+        }
+    }
+
+    pub(crate) fn not(&mut self, a: Wire) -> Wire {
+        self.xor(a, Wire::Const(true))
+    }
+
+    pub(crate) fn xor_word(&mut self, a: Word, b: Word) -> Word {
+        std::array::from_fn(|i| self.xor(a[i], b[i]))
+    }
+
+    /// Addition modulo 2^32 with a ripple-carry adder.
+    pub(crate) fn add_word(&mut self, a: Word, b: Word) -> Word {
+        let mut carry = Wire::Const(false);
+        std::array::from_fn(|i| {
+            let sum = self.xor(a[i], b[i]);
+            let out = self.xor(sum, carry);
+            if i < 31 {
+                // carry' = ((a ^ c) & (b ^ c)) ^ c
+                let ac = self.xor(a[i], carry);
+                let bc = self.xor(b[i], carry);
+                let and = self.and(ac, bc);
+                carry = self.xor(and, carry);
+            }
+            out
+        })
+    }
+}
+
+/// Builds a circuit which masks `len` bytes of plaintext.
+///
+/// The circuit takes the plaintext followed by the mask, one bit per byte, and
+/// outputs the plaintext with every byte whose mask bit is unset zeroed.
+pub(crate) fn build_mask(len: usize) -> Arc<Circuit> {
+    let mut builder = Builder::new();
+
+    let plaintext = builder.add_input_bytes(len);
+    let mask = builder.add_input_bytes(len.div_ceil(8));
+
+    for (i, byte) in plaintext.chunks_exact(8).enumerate() {
+        for bit in byte {
+            let bit = builder.and(*bit, mask[i]);
+            builder.add_output(bit);
+        }
+    }
+
+    Arc::new(builder.build())
+}
+
+/// Returns the first `N` bits of `value` as constant wires.
+pub(crate) fn const_bits<const N: usize>(value: u64) -> [Wire; N] {
+    std::array::from_fn(|i| Wire::Const((value >> i) & 1 == 1))
+}
+
+/// Rotates the bits of a little-endian integer left by `n`.
+pub(crate) fn rotate_left<const N: usize>(bits: [Wire; N], n: usize) -> [Wire; N] {
+    std::array::from_fn(|i| bits[(i + N - n) % N])
+}
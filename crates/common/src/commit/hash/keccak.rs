@@ -0,0 +1,128 @@
+//! Keccak-256 circuit.
+//!
+//! This is the original Keccak submission as used by Ethereum, which differs
+//! from SHA3-256 only in the padding.
+
+use std::sync::Arc;
+
+use mpz_circuits::Circuit;
+
+use super::circuit::{const_bits, rotate_left, Builder, Wire};
+
+/// Rate in bytes.
+const RATE: usize = 136;
+const ROUNDS: usize = 24;
+
+/// Rotation offsets, indexed by `[x][y]`.
+const RHO: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+const RC: [u64; ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// A 64-bit lane, least significant bit first.
+type Lane = [Wire; 64];
+
+/// Builds a Keccak-256 circuit for a message of `len` bytes.
+///
+/// The circuit takes the message as input and outputs the 32 byte digest.
+pub(super) fn build_keccak256(len: usize) -> Arc<Circuit> {
+    let mut builder = Builder::new();
+
+    let mut msg = builder.add_input_bytes(len);
+
+    // Pad with 0x01 || 0x00..0x00 || 0x80.
+    let padded_len = RATE * (len / RATE + 1);
+    msg.resize(8 * padded_len, Wire::Const(false));
+    msg[8 * len] = Wire::Const(true);
+    msg[8 * padded_len - 1] = Wire::Const(true);
+
+    // Lanes are indexed by `x + 5 * y`.
+    let mut state: [Lane; 25] = [[Wire::Const(false); 64]; 25];
+    for block in msg.chunks_exact(8 * RATE) {
+        for (lane, bits) in state.iter_mut().zip(block.chunks_exact(64)) {
+            *lane = std::array::from_fn(|i| builder.xor(lane[i], bits[i]));
+        }
+        keccak_f(&mut builder, &mut state);
+    }
+
+    for lane in &state[..4] {
+        for bit in lane {
+            builder.add_output(*bit);
+        }
+    }
+
+    Arc::new(builder.build())
+}
+
+/// The Keccak-f[1600] permutation.
+fn keccak_f(builder: &mut Builder, state: &mut [Lane; 25]) {
+    for rc in RC {
+        // θ
+        let c: [Lane; 5] = std::array::from_fn(|x| {
+            std::array::from_fn(|i| {
+                (1..5).fold(state[x][i], |acc, y| builder.xor(acc, state[x + 5 * y][i]))
+            })
+        });
+        for x in 0..5 {
+            let rot = rotate_left(c[(x + 1) % 5], 1);
+            let d: Lane = std::array::from_fn(|i| builder.xor(c[(x + 4) % 5][i], rot[i]));
+            for y in 0..5 {
+                let lane = &mut state[x + 5 * y];
+                *lane = std::array::from_fn(|i| builder.xor(lane[i], d[i]));
+            }
+        }
+
+        // ρ and π
+        let mut b = [[Wire::Const(false); 64]; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = rotate_left(state[x + 5 * y], RHO[x][y]);
+            }
+        }
+
+        // χ
+        for x in 0..5 {
+            for y in 0..5 {
+                state[x + 5 * y] = std::array::from_fn(|i| {
+                    let not = builder.not(b[(x + 1) % 5 + 5 * y][i]);
+                    let and = builder.and(not, b[(x + 2) % 5 + 5 * y][i]);
+                    builder.xor(b[x + 5 * y][i], and)
+                });
+            }
+        }
+
+        // ι
+        let rc: Lane = const_bits(rc);
+        state[0] = std::array::from_fn(|i| builder.xor(state[0][i], rc[i]));
+    }
+}
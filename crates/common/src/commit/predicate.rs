@@ -0,0 +1,470 @@
+//! Predicate proofs over committed plaintext.
+//!
+//! Each predicate is evaluated by a circuit over the plaintext references of
+//! the transcript, and only the single output bit is decoded. Hence the
+//! verifier learns whether the predicate holds, but nothing else about the
+//! plaintext.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use mpz_circuits::Circuit;
+use mpz_core::bitvec::BitVec;
+use mpz_memory_core::{
+    binary::{Binary, U8},
+    Array, DecodeFutureTyped, MemoryExt, Vector,
+};
+use mpz_vm_core::{prelude::*, Call, Vm, VmError};
+use tlsn_core::transcript::predicate::{CmpOp, Dfa, Predicate, PredicateKind};
+
+use crate::{
+    commit::hash::circuit::{Builder, Wire},
+    transcript::TranscriptRefs,
+};
+
+/// Future which will resolve to the proven predicates.
+#[derive(Debug)]
+pub struct PredicateFuture {
+    futs: Vec<(Predicate, DecodeFutureTyped<BitVec, Vec<u8>>)>,
+}
+
+impl PredicateFuture {
+    /// Tries to receive the value, returning an error if the value is not
+    /// ready or if any predicate does not hold.
+    pub fn try_recv(self) -> Result<Vec<Predicate>, PredicateError> {
+        let mut output = Vec::new();
+        for (predicate, mut fut) in self.futs {
+            let result = fut
+                .try_recv()
+                .map_err(|_| PredicateError(ErrorRepr::Decode))?
+                .ok_or(PredicateError(ErrorRepr::Decode))?;
+
+            if result.first().copied().unwrap_or_default() & 1 != 1 {
+                return Err(PredicateError(ErrorRepr::NotSatisfied { predicate }));
+            }
+
+            output.push(predicate);
+        }
+
+        Ok(output)
+    }
+}
+
+/// Evaluates predicates over the plaintext of the transcript.
+///
+/// This is the same for the prover and the verifier, the plaintext is already
+/// assigned when the records are committed.
+pub fn eval_predicates(
+    vm: &mut dyn Vm<Binary>,
+    refs: &TranscriptRefs,
+    predicates: impl IntoIterator<Item = Predicate>,
+) -> Result<PredicateFuture, PredicateError> {
+    let mut futs = Vec::new();
+    for predicate in predicates {
+        if predicate.idx.is_empty() {
+            return Err(ErrorRepr::Index { predicate }.into());
+        }
+
+        let plaintext = refs
+            .get(predicate.direction, &predicate.idx)
+            .ok_or_else(|| ErrorRepr::Index {
+                predicate: predicate.clone(),
+            })?;
+
+        let circuit = build_predicate(&predicate.kind, predicate.idx.len()).ok_or_else(|| {
+            ErrorRepr::Unsupported {
+                predicate: predicate.clone(),
+            }
+        })?;
+
+        let mut call = Call::builder(circuit);
+        for slice in plaintext {
+            call = call.arg(slice);
+        }
+
+        let output: Array<U8, 1> = vm.call(call.build().map_err(PredicateError::circuit)?)?;
+        let fut = vm.decode(Vector::<U8>::from(output))?;
+
+        futs.push((predicate, fut));
+    }
+
+    Ok(PredicateFuture { futs })
+}
+
+/// Builds a circuit which evaluates a predicate over `len` bytes.
+///
+/// The output is a single byte, with the least significant bit set if the
+/// predicate holds.
+fn build_predicate(kind: &PredicateKind, len: usize) -> Option<Arc<Circuit>> {
+    let mut builder = Builder::new();
+
+    let input = builder.add_input_bytes(len);
+
+    let result = match kind {
+        PredicateKind::Equal { value } => {
+            if value.len() == len {
+                eq_const(&mut builder, &input, value)
+            } else {
+                Wire::Const(false)
+            }
+        }
+        PredicateKind::Compare { op, value } => compare(&mut builder, &input, *op, *value),
+        PredicateKind::Contains { needle } => contains(&mut builder, &input, needle),
+        PredicateKind::Regex { pattern } => {
+            let dfa = Dfa::new(pattern).ok()?;
+            regex(&mut builder, &input, &dfa)
+        }
+        _ => return None,
+    };
+
+    builder.add_output(result);
+    for _ in 1..8 {
+        builder.add_output(Wire::Const(false));
+    }
+
+    Some(Arc::new(builder.build()))
+}
+
+fn or(builder: &mut Builder, a: Wire, b: Wire) -> Wire {
+    let (a, b) = (builder.not(a), builder.not(b));
+    let and = builder.and(a, b);
+    builder.not(and)
+}
+
+fn and_all(builder: &mut Builder, wires: impl IntoIterator<Item = Wire>) -> Wire {
+    wires
+        .into_iter()
+        .fold(Wire::Const(true), |acc, wire| builder.and(acc, wire))
+}
+
+/// Returns the bit `i` of `bytes`, least significant bit first.
+fn const_bit(bytes: &[u8], i: usize) -> Wire {
+    Wire::Const((bytes[i / 8] >> (i % 8)) & 1 == 1)
+}
+
+/// Returns whether the bits are equal to the constant bytes.
+fn eq_const(builder: &mut Builder, bits: &[Wire], value: &[u8]) -> Wire {
+    let eq: Vec<Wire> = bits
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| {
+            let diff = builder.xor(*bit, const_bit(value, i));
+            builder.not(diff)
+        })
+        .collect();
+
+    and_all(builder, eq)
+}
+
+/// Returns whether a byte is an ASCII digit, i.e. in `0x30..=0x39`.
+fn is_digit(builder: &mut Builder, byte: &[Wire]) -> Wire {
+    let (b6, b7) = (builder.not(byte[6]), builder.not(byte[7]));
+    let high = and_all(builder, [byte[4], byte[5], b6, b7]);
+
+    // The low nibble is at most 9 unless bit 3 and either bit 1 or 2 are set.
+    let b12 = or(builder, byte[1], byte[2]);
+    let over = builder.and(byte[3], b12);
+    let low = builder.not(over);
+
+    builder.and(high, low)
+}
+
+/// Compares a fixed-length ASCII decimal number with a constant.
+///
+/// Both numbers are compared as big-endian strings of the same number of
+/// digits, which is equivalent to comparing their values.
+fn compare(builder: &mut Builder, bits: &[Wire], op: CmpOp, value: u64) -> Wire {
+    let len = bits.len() / 8;
+
+    let digits: Vec<Wire> = bits
+        .chunks_exact(8)
+        .map(|byte| is_digit(builder, byte))
+        .collect();
+    let digits = and_all(builder, digits);
+
+    let constant = format!("{value:0len$}");
+    let (lt, eq) = if constant.len() > len {
+        // The constant has more digits than the plaintext.
+        (Wire::Const(true), Wire::Const(false))
+    } else {
+        let constant = constant.as_bytes();
+
+        // lt' = (!x & c) | ((x == c) & lt), from the least significant bit.
+        let mut lt = Wire::Const(false);
+        for byte in (0..len).rev() {
+            for bit in 0..8 {
+                let i = 8 * byte + bit;
+                let c = const_bit(constant, i);
+
+                let not_x = builder.not(bits[i]);
+                let less = builder.and(not_x, c);
+                let diff = builder.xor(bits[i], c);
+                let same = builder.not(diff);
+                let carry = builder.and(same, lt);
+                // The terms are mutually exclusive.
+                lt = builder.xor(less, carry);
+            }
+        }
+
+        (lt, eq_const(builder, bits, constant))
+    };
+
+    let result = match op {
+        CmpOp::Lt => lt,
+        CmpOp::Lte => builder.xor(lt, eq),
+        CmpOp::Gt => {
+            let lte = builder.xor(lt, eq);
+            builder.not(lte)
+        }
+        CmpOp::Gte => builder.not(lt),
+    };
+
+    builder.and(digits, result)
+}
+
+/// Returns whether the bits contain the needle at any byte offset.
+fn contains(builder: &mut Builder, bits: &[Wire], needle: &[u8]) -> Wire {
+    let len = bits.len() / 8;
+
+    if needle.is_empty() {
+        return Wire::Const(true);
+    } else if needle.len() > len {
+        return Wire::Const(false);
+    }
+
+    let mut result = Wire::Const(false);
+    for offset in 0..=len - needle.len() {
+        let window = &bits[8 * offset..8 * (offset + needle.len())];
+        let eq = eq_const(builder, window, needle);
+        result = or(builder, result, eq);
+    }
+
+    result
+}
+
+/// Returns whether the bits match the automaton.
+///
+/// The state is one-hot encoded, so the transitions into a state are
+/// mutually exclusive and can be combined with XOR. Each transition costs an
+/// AND gate, plus the gates to test the input byte.
+fn regex(builder: &mut Builder, bits: &[Wire], dfa: &Dfa) -> Wire {
+    // Transitions of each state, grouped by the target state.
+    let transitions: Vec<BTreeMap<usize, Vec<u8>>> = (0..dfa.num_states())
+        .map(|state| {
+            let mut targets = BTreeMap::<usize, Vec<u8>>::new();
+            for byte in 0..=255u8 {
+                if let Some(next) = dfa.next(state, byte) {
+                    targets.entry(next).or_default().push(byte);
+                }
+            }
+            targets
+        })
+        .collect();
+
+    let mut state: Vec<Wire> = (0..dfa.num_states())
+        .map(|state| Wire::Const(state == dfa.start()))
+        .collect();
+
+    for byte in bits.chunks_exact(8) {
+        let mut decoder = ByteDecoder::new(byte);
+        let mut next = vec![Wire::Const(false); dfa.num_states()];
+        for (current, targets) in state.iter().zip(&transitions) {
+            if let Wire::Const(false) = current {
+                continue;
+            }
+
+            for (target, bytes) in targets {
+                let is_member = decoder.is_member(builder, bytes);
+                let transition = builder.and(*current, is_member);
+                next[*target] = builder.xor(next[*target], transition);
+            }
+        }
+        state = next;
+    }
+
+    state
+        .into_iter()
+        .enumerate()
+        .filter(|(state, _)| dfa.is_accepting(*state))
+        .fold(Wire::Const(false), |acc, (_, wire)| builder.xor(acc, wire))
+}
+
+/// Tests whether a byte is a member of a public set.
+///
+/// The byte is decoded into one wire per value, each computed from the
+/// values of both nibbles and only when it is first needed. The values are
+/// mutually exclusive, so a set is the XOR of its members.
+struct ByteDecoder<'a> {
+    byte: &'a [Wire],
+    /// Wires of the values of the low and high nibble.
+    nibbles: Option<[[Wire; 16]; 2]>,
+    values: [Option<Wire>; 256],
+}
+
+impl<'a> ByteDecoder<'a> {
+    fn new(byte: &'a [Wire]) -> Self {
+        Self {
+            byte,
+            nibbles: None,
+            values: [None; 256],
+        }
+    }
+
+    fn is_member(&mut self, builder: &mut Builder, bytes: &[u8]) -> Wire {
+        if bytes.len() == 256 {
+            return Wire::Const(true);
+        }
+
+        // Test the complement if it is smaller.
+        if bytes.len() > 128 {
+            let complement: Vec<u8> = (0..=255u8).filter(|b| !bytes.contains(b)).collect();
+            let is_member = self.is_member(builder, &complement);
+            return builder.not(is_member);
+        }
+
+        bytes.iter().fold(Wire::Const(false), |acc, value| {
+            let wire = self.value(builder, *value);
+            builder.xor(acc, wire)
+        })
+    }
+
+    fn value(&mut self, builder: &mut Builder, value: u8) -> Wire {
+        if let Some(wire) = self.values[value as usize] {
+            return wire;
+        }
+
+        let byte = self.byte;
+        let [low, high] = *self.nibbles.get_or_insert_with(|| {
+            [
+                nibble_values(builder, &byte[..4]),
+                nibble_values(builder, &byte[4..]),
+            ]
+        });
+
+        let wire = builder.and(low[(value & 0xf) as usize], high[(value >> 4) as usize]);
+        self.values[value as usize] = Some(wire);
+        wire
+    }
+}
+
+/// Returns a wire for each value of a nibble, which is set if the nibble has
+/// that value.
+fn nibble_values(builder: &mut Builder, nibble: &[Wire]) -> [Wire; 16] {
+    let mut pairs = [[Wire::Const(false); 4]; 2];
+    for (pair, bits) in pairs.iter_mut().zip(nibble.chunks_exact(2)) {
+        let not = [builder.not(bits[0]), builder.not(bits[1])];
+        for (value, wire) in pair.iter_mut().enumerate() {
+            let low = if value & 1 == 1 { bits[0] } else { not[0] };
+            let high = if value & 2 == 2 { bits[1] } else { not[1] };
+            *wire = builder.and(low, high);
+        }
+    }
+
+    std::array::from_fn(|value| builder.and(pairs[0][value & 3], pairs[1][value >> 2]))
+}
+
+/// Error for predicate proofs.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct PredicateError(#[from] ErrorRepr);
+
+impl PredicateError {
+    fn circuit<E>(e: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self(ErrorRepr::Circuit(e.into()))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("predicate error: {0}")]
+enum ErrorRepr {
+    #[error("VM error: {0}")]
+    Vm(VmError),
+    #[error("circuit error: {0}")]
+    Circuit(Box<dyn std::error::Error + Send + Sync>),
+    #[error("failed to decode predicate result")]
+    Decode,
+    #[error("predicate index is empty or out of bounds: {predicate}")]
+    Index { predicate: Predicate },
+    #[error("unsupported predicate: {predicate}")]
+    Unsupported { predicate: Predicate },
+    #[error("predicate does not hold: {predicate}")]
+    NotSatisfied { predicate: Predicate },
+}
+
+impl From<VmError> for PredicateError {
+    fn from(value: VmError) -> Self {
+        Self(ErrorRepr::Vm(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::test_utils::mock_vm;
+    use mpz_common::context::test_st_context;
+    use mpz_memory_core::ViewExt;
+    use mpz_vm_core::Execute;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::gt(b"1523", PredicateKind::Compare { op: CmpOp::Gt, value: 1000 })]
+    #[case::gt_equal(b"1000", PredicateKind::Compare { op: CmpOp::Gt, value: 1000 })]
+    #[case::lte_equal(b"1000", PredicateKind::Compare { op: CmpOp::Lte, value: 1000 })]
+    #[case::lt_short(b"099", PredicateKind::Compare { op: CmpOp::Lt, value: 1000 })]
+    #[case::gte_not_a_number(b"1:00", PredicateKind::Compare { op: CmpOp::Gte, value: 1 })]
+    #[case::equal(b"ok", PredicateKind::Equal { value: b"ok".to_vec() })]
+    #[case::not_equal(b"no", PredicateKind::Equal { value: b"ok".to_vec() })]
+    #[case::contains(b"status: ok", PredicateKind::Contains { needle: b"ok".to_vec() })]
+    #[case::not_contains(b"status: error", PredicateKind::Contains { needle: b"ok".to_vec() })]
+    #[case::regex(b"id=a7f3", PredicateKind::Regex { pattern: r"id=[0-9a-f]+".to_string() })]
+    #[case::regex_negated(b"\"a b\"", PredicateKind::Regex { pattern: r#""[^"]*""#.to_string() })]
+    #[case::not_regex(b"id=a7g3", PredicateKind::Regex { pattern: r"id=[0-9a-f]+".to_string() })]
+    #[case::not_regex_prefix(b"id=a7f3;", PredicateKind::Regex { pattern: r"id=[0-9a-f]+".to_string() })]
+    #[tokio::test]
+    async fn test_predicate_circuit(#[case] data: &[u8], #[case] kind: PredicateKind) {
+        let circuit = build_predicate(&kind, data.len()).unwrap();
+
+        let (mut ctx_a, mut ctx_b) = test_st_context(8);
+        let (mut gen, mut ev) = mock_vm();
+
+        let out_gen = alloc_predicate(&mut gen, circuit.clone(), data);
+        let out_ev = alloc_predicate(&mut ev, circuit, data);
+
+        let (out_gen, out_ev) = tokio::try_join!(
+            async {
+                let out = gen.decode(out_gen).unwrap();
+                gen.flush(&mut ctx_a).await.unwrap();
+                gen.execute(&mut ctx_a).await.unwrap();
+                gen.flush(&mut ctx_a).await.unwrap();
+                out.await
+            },
+            async {
+                let out = ev.decode(out_ev).unwrap();
+                ev.flush(&mut ctx_b).await.unwrap();
+                ev.execute(&mut ctx_b).await.unwrap();
+                ev.flush(&mut ctx_b).await.unwrap();
+                out.await
+            }
+        )
+        .unwrap();
+
+        assert_eq!(out_gen, out_ev);
+        assert_eq!(out_gen, [kind.eval(data) as u8]);
+    }
+
+    fn alloc_predicate(
+        vm: &mut dyn Vm<Binary>,
+        circuit: Arc<Circuit>,
+        data: &[u8],
+    ) -> Array<U8, 1> {
+        let data_ref: Vector<U8> = vm.alloc_vec(data.len()).unwrap();
+        vm.mark_public(data_ref).unwrap();
+        vm.assign(data_ref, data.to_vec()).unwrap();
+        vm.commit(data_ref).unwrap();
+
+        vm.call(Call::builder(circuit).arg(data_ref).build().unwrap())
+            .unwrap()
+    }
+}
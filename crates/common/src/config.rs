@@ -114,11 +114,22 @@ pub struct ProtocolConfigValidator {
     /// Maximum number of application data records that can be sent.
     #[builder(default = "DEFAULT_RECORDS_LIMIT")]
     max_sent_records: usize,
+    /// Maximum number of bytes that can be decrypted online, i.e. while the
+    /// MPC-TLS connection is active.
+    #[builder(setter(strip_option), default)]
+    max_recv_data_online: Option<usize>,
     /// Maximum number of bytes that can be received.
     max_recv_data: usize,
     /// Maximum number of application data records that can be received.
     #[builder(default = "DEFAULT_RECORDS_LIMIT")]
     max_recv_records: usize,
+    /// Required setting of the `deferred decryption` feature from the start
+    /// of the MPC-TLS connection, any setting is accepted if not set.
+    #[builder(setter(strip_option), default)]
+    defer_decryption_from_start: Option<bool>,
+    /// Required network settings, any setting is accepted if not set.
+    #[builder(setter(strip_option), default)]
+    network: Option<NetworkSetting>,
     /// Version that is being run by checker.
     #[builder(setter(skip), default = "VERSION.clone()")]
     version: Version,
@@ -141,6 +152,12 @@ impl ProtocolConfigValidator {
         self.max_sent_records
     }
 
+    /// Returns the maximum number of bytes that can be decrypted online, if
+    /// limited.
+    pub fn max_recv_data_online(&self) -> Option<usize> {
+        self.max_recv_data_online
+    }
+
     /// Returns the maximum number of bytes that can be received.
     pub fn max_recv_data(&self) -> usize {
         self.max_recv_data
@@ -152,11 +169,27 @@ impl ProtocolConfigValidator {
         self.max_recv_records
     }
 
+    /// Returns the required setting of the `deferred decryption` feature from
+    /// the start of the MPC-TLS connection, if any.
+    pub fn defer_decryption_from_start(&self) -> Option<bool> {
+        self.defer_decryption_from_start
+    }
+
+    /// Returns the required network settings, if any.
+    pub fn network(&self) -> Option<NetworkSetting> {
+        self.network
+    }
+
     /// Performs compatibility check of the protocol configuration between
     /// prover and verifier.
     pub fn validate(&self, config: &ProtocolConfig) -> Result<(), ProtocolConfigError> {
-        self.check_max_transcript_size(config.max_sent_data, config.max_recv_data)?;
+        self.check_max_transcript_size(
+            config.max_sent_data,
+            config.max_recv_data_online,
+            config.max_recv_data,
+        )?;
         self.check_max_records(config.max_sent_records, config.max_recv_records)?;
+        self.check_settings(config.defer_decryption_from_start, config.network)?;
         self.check_version(&config.version)?;
         Ok(())
     }
@@ -165,23 +198,28 @@ impl ProtocolConfigValidator {
     fn check_max_transcript_size(
         &self,
         max_sent_data: usize,
+        max_recv_data_online: usize,
         max_recv_data: usize,
     ) -> Result<(), ProtocolConfigError> {
-        if max_sent_data > self.max_sent_data {
-            return Err(ProtocolConfigError::max_transcript_size(format!(
-                "max_sent_data {:?} is greater than the configured limit {:?}",
-                max_sent_data, self.max_sent_data,
-            )));
-        }
-
-        if max_recv_data > self.max_recv_data {
-            return Err(ProtocolConfigError::max_transcript_size(format!(
-                "max_recv_data {:?} is greater than the configured limit {:?}",
-                max_recv_data, self.max_recv_data,
-            )));
+        check_limit(
+            ProtocolConfigLimit::MaxSentData,
+            max_sent_data,
+            self.max_sent_data,
+        )?;
+
+        if let Some(maximum) = self.max_recv_data_online {
+            check_limit(
+                ProtocolConfigLimit::MaxRecvDataOnline,
+                max_recv_data_online,
+                maximum,
+            )?;
         }
 
-        Ok(())
+        check_limit(
+            ProtocolConfigLimit::MaxRecvData,
+            max_recv_data,
+            self.max_recv_data,
+        )
     }
 
     fn check_max_records(
@@ -190,19 +228,42 @@ impl ProtocolConfigValidator {
         max_recv_records: Option<usize>,
     ) -> Result<(), ProtocolConfigError> {
         if let Some(max_sent_records) = max_sent_records {
-            if max_sent_records > self.max_sent_records {
-                return Err(ProtocolConfigError::max_record_count(format!(
-                    "max_sent_records {} is greater than the configured limit {}",
-                    max_sent_records, self.max_sent_records,
+            check_limit(
+                ProtocolConfigLimit::MaxSentRecords,
+                max_sent_records,
+                self.max_sent_records,
+            )?;
+        }
+
+        if let Some(max_recv_records) = max_recv_records {
+            check_limit(
+                ProtocolConfigLimit::MaxRecvRecords,
+                max_recv_records,
+                self.max_recv_records,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Checks if the settings are the required ones, if any.
+    fn check_settings(
+        &self,
+        defer_decryption_from_start: bool,
+        network: NetworkSetting,
+    ) -> Result<(), ProtocolConfigError> {
+        if let Some(required) = self.defer_decryption_from_start {
+            if defer_decryption_from_start != required {
+                return Err(ProtocolConfigError::setting(format!(
+                    "defer_decryption_from_start must be {required}"
                 )));
             }
         }
 
-        if let Some(max_recv_records) = max_recv_records {
-            if max_recv_records > self.max_recv_records {
-                return Err(ProtocolConfigError::max_record_count(format!(
-                    "max_recv_records {} is greater than the configured limit {}",
-                    max_recv_records, self.max_recv_records,
+        if let Some(required) = self.network {
+            if network != required {
+                return Err(ProtocolConfigError::setting(format!(
+                    "network must be {required:?}"
                 )));
             }
         }
@@ -224,11 +285,76 @@ impl ProtocolConfigValidator {
     }
 }
 
+fn check_limit(
+    limit: ProtocolConfigLimit,
+    requested: usize,
+    maximum: usize,
+) -> Result<(), ProtocolConfigError> {
+    if requested > maximum {
+        return Err(ProtocolConfigError::limit_exceeded(LimitExceeded {
+            limit,
+            requested,
+            maximum,
+        }));
+    }
+
+    Ok(())
+}
+
+/// A limit enforced by [`ProtocolConfigValidator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolConfigLimit {
+    /// Maximum number of bytes that can be sent.
+    MaxSentData,
+    /// Maximum number of application data records that can be sent.
+    MaxSentRecords,
+    /// Maximum number of bytes that can be decrypted online.
+    MaxRecvDataOnline,
+    /// Maximum number of bytes that can be received.
+    MaxRecvData,
+    /// Maximum number of application data records that can be received.
+    MaxRecvRecords,
+}
+
+impl fmt::Display for ProtocolConfigLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolConfigLimit::MaxSentData => write!(f, "max_sent_data"),
+            ProtocolConfigLimit::MaxSentRecords => write!(f, "max_sent_records"),
+            ProtocolConfigLimit::MaxRecvDataOnline => write!(f, "max_recv_data_online"),
+            ProtocolConfigLimit::MaxRecvData => write!(f, "max_recv_data"),
+            ProtocolConfigLimit::MaxRecvRecords => write!(f, "max_recv_records"),
+        }
+    }
+}
+
+/// A protocol configuration value which exceeds the limit of the checker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitExceeded {
+    /// The exceeded limit.
+    pub limit: ProtocolConfigLimit,
+    /// The value requested by the peer.
+    pub requested: usize,
+    /// The maximum value allowed by the checker.
+    pub maximum: usize,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} is greater than the configured limit {}",
+            self.limit, self.requested, self.maximum
+        )
+    }
+}
+
 /// Settings for the network environment.
 ///
 /// Provides optimization options to adapt the protocol to different network
 /// situations.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NetworkSetting {
     /// Prefers a bandwidth-heavy protocol.
     Bandwidth,
@@ -246,6 +372,7 @@ impl Default for NetworkSetting {
 #[derive(thiserror::Error, Debug)]
 pub struct ProtocolConfigError {
     kind: ErrorKind,
+    exceeded: Option<LimitExceeded>,
     #[source]
     source: Option<Box<dyn Error + Send + Sync>>,
 }
@@ -257,29 +384,40 @@ impl ProtocolConfigError {
     {
         Self {
             kind,
+            exceeded: None,
             source: Some(source.into()),
         }
     }
 
-    fn max_transcript_size(msg: impl Into<String>) -> Self {
+    fn limit_exceeded(exceeded: LimitExceeded) -> Self {
+        let kind = match exceeded.limit {
+            ProtocolConfigLimit::MaxSentData
+            | ProtocolConfigLimit::MaxRecvDataOnline
+            | ProtocolConfigLimit::MaxRecvData => ErrorKind::MaxTranscriptSize,
+            ProtocolConfigLimit::MaxSentRecords | ProtocolConfigLimit::MaxRecvRecords => {
+                ErrorKind::MaxRecordCount
+            }
+        };
+
         Self {
-            kind: ErrorKind::MaxTranscriptSize,
-            source: Some(msg.into().into()),
+            kind,
+            exceeded: Some(exceeded),
+            source: Some(exceeded.to_string().into()),
         }
     }
 
-    fn max_record_count(msg: impl Into<String>) -> Self {
-        Self {
-            kind: ErrorKind::MaxRecordCount,
-            source: Some(msg.into().into()),
-        }
+    fn setting(msg: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Setting, msg.into())
     }
 
     fn version(msg: impl Into<String>) -> Self {
-        Self {
-            kind: ErrorKind::Version,
-            source: Some(msg.into().into()),
-        }
+        Self::new(ErrorKind::Version, msg.into())
+    }
+
+    /// Returns the exceeded limit, if the configuration was rejected because
+    /// of one.
+    pub fn limit(&self) -> Option<&LimitExceeded> {
+        self.exceeded.as_ref()
     }
 }
 
@@ -288,6 +426,7 @@ impl fmt::Display for ProtocolConfigError {
         match self.kind {
             ErrorKind::MaxTranscriptSize => write!(f, "max transcript size exceeded")?,
             ErrorKind::MaxRecordCount => write!(f, "max record count exceeded")?,
+            ErrorKind::Setting => write!(f, "setting error")?,
             ErrorKind::Version => write!(f, "version error")?,
         }
 
@@ -303,6 +442,7 @@ impl fmt::Display for ProtocolConfigError {
 enum ErrorKind {
     MaxTranscriptSize,
     MaxRecordCount,
+    Setting,
     Version,
 }
 
@@ -360,4 +500,54 @@ mod test {
 
         assert!(config_validator.validate(&peer_config).is_err())
     }
+
+    #[rstest]
+    fn test_check_fail_limit(config_validator: &ProtocolConfigValidator) {
+        let peer_config = ProtocolConfig::builder()
+            .max_sent_data(TEST_MAX_SENT_LIMIT)
+            .max_recv_data(TEST_MAX_RECV_LIMIT)
+            .max_recv_records(DEFAULT_RECORDS_LIMIT + 1)
+            .build()
+            .unwrap();
+
+        let err = config_validator.validate(&peer_config).unwrap_err();
+        assert_eq!(
+            err.limit(),
+            Some(&LimitExceeded {
+                limit: ProtocolConfigLimit::MaxRecvRecords,
+                requested: DEFAULT_RECORDS_LIMIT + 1,
+                maximum: DEFAULT_RECORDS_LIMIT,
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_fail_setting() {
+        let config_validator = ProtocolConfigValidator::builder()
+            .max_sent_data(TEST_MAX_SENT_LIMIT)
+            .max_recv_data(TEST_MAX_RECV_LIMIT)
+            .defer_decryption_from_start(true)
+            .network(NetworkSetting::Bandwidth)
+            .build()
+            .unwrap();
+
+        let mut builder = ProtocolConfig::builder();
+        builder
+            .max_sent_data(TEST_MAX_SENT_LIMIT)
+            .max_recv_data(TEST_MAX_RECV_LIMIT);
+        assert!(config_validator.validate(&builder.build().unwrap()).is_ok());
+
+        let peer_config = builder
+            .clone()
+            .defer_decryption_from_start(false)
+            .build()
+            .unwrap();
+        let err = config_validator.validate(&peer_config).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Setting));
+        assert_eq!(err.limit(), None);
+
+        let peer_config = builder.network(NetworkSetting::Latency).build().unwrap();
+        let err = config_validator.validate(&peer_config).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Setting));
+    }
 }
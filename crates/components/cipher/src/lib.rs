@@ -10,6 +10,8 @@
 #![forbid(unsafe_code)]
 
 pub mod aes;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 
 use async_trait::async_trait;
 use mpz_circuits::circuits::xor;
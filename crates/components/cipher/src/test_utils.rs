@@ -0,0 +1,19 @@
+//! Test utilities.
+
+use mpz_garble::protocol::semihonest::{Evaluator, Garbler};
+use mpz_memory_core::correlated::Delta;
+use mpz_ot::ideal::cot::{ideal_cot, IdealCOTReceiver, IdealCOTSender};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Returns a garbler and an evaluator VM backed by an ideal COT.
+pub fn mock_vm() -> (Garbler<IdealCOTSender>, Evaluator<IdealCOTReceiver>) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let delta = Delta::random(&mut rng);
+
+    let (cot_send, cot_recv) = ideal_cot(delta.into_inner());
+
+    let gen = Garbler::new(cot_send, [0u8; 16], delta);
+    let ev = Evaluator::new(cot_recv);
+
+    (gen, ev)
+}
@@ -29,11 +29,33 @@
 //! Additionally, a Notary may
 //! [include](crate::attestation::AttestationBuilder::extension)
 //! their own extensions.
+//!
+//! # Holder key
+//!
+//! A Prover may [commit](crate::request::RequestConfigBuilder::holder_key) to
+//! a key of their own in the attestation. Presentations of the attestation can
+//! then be [bound](crate::presentation::PresentationBuilder::bind) to a
+//! challenge of a Verifier, so that they can not be replayed by someone else.
+//!
+//! # Expiry and revocation
+//!
+//! A Notary may [limit](crate::attestation::AttestationConfigBuilder::validity)
+//! the validity of attestations, and revoke attestations by their [`Uid`] in a
+//! [`RevocationList`] signed with their key.
+//!
+//! # Encoding
+//!
+//! By default the header is signed in its canonical BCS serialization and the
+//! body fields are merkelized with the requested hash algorithm. A Prover may
+//! instead request the [`AttestationEncoding::Evm`] encoding, which can be
+//! verified on-chain. See the [`evm`] module for details.
 
 mod builder;
 mod config;
+pub mod evm;
 mod extension;
 mod proof;
+mod revocation;
 
 use std::fmt;
 
@@ -42,21 +64,27 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     connection::{ConnectionInfo, ServerCertCommitment, ServerEphemKey},
-    hash::{impl_domain_separator, Hash, HashAlgorithm, HashAlgorithmExt, TypedHash},
+    hash::{impl_domain_separator, Hash, HashAlgId, HashAlgorithm, HashAlgorithmExt, TypedHash},
     merkle::MerkleTree,
     presentation::PresentationBuilder,
-    signing::{Signature, VerifyingKey},
+    signing::{Signature, SignatureAlgId, VerifyingKey},
     transcript::TranscriptCommitment,
     CryptoProvider,
 };
 
-pub use builder::{AttestationBuilder, AttestationBuilderError};
+pub use builder::{AttestationBuilder, AttestationBuilderError, UnsignedAttestation};
 pub use config::{AttestationConfig, AttestationConfigBuilder, AttestationConfigError};
 pub use extension::{Extension, InvalidExtension};
 pub use proof::{AttestationError, AttestationProof};
+pub use revocation::{RevocationError, RevocationList, SignedRevocationList};
+
+use proof::ErrorKind;
 
 /// Current version of attestations.
-pub const VERSION: Version = Version(0);
+///
+/// Attestations of any other version are rejected, as their serialization is
+/// not compatible.
+pub const VERSION: Version = Version(1);
 
 /// Unique identifier for an attestation.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -68,6 +96,15 @@ impl From<[u8; 16]> for Uid {
     }
 }
 
+impl fmt::Display for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
 impl Distribution<Uid> for StandardUniform {
     fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Uid {
         Uid(self.sample(rng))
@@ -76,7 +113,7 @@ impl Distribution<Uid> for StandardUniform {
 
 /// Version of an attestation.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
-pub struct Version(u32);
+pub struct Version(pub(crate) u32);
 
 impl_domain_separator!(Version);
 
@@ -124,6 +161,57 @@ pub enum FieldKind {
     EncodingCommitment = 0x04,
     /// Plaintext hash commitment.
     PlaintextHash = 0x05,
+    /// Masked plaintext hash commitment.
+    MaskedPlaintextHash = 0x06,
+    /// Predicate over committed plaintext.
+    Predicate = 0x07,
+    /// Notary verifying key.
+    VerifyingKey = 0x08,
+    /// Extension.
+    Extension = 0x09,
+    /// Holder verifying key.
+    HolderKey = 0x0a,
+    /// Expiry of the attestation.
+    NotAfter = 0x0b,
+}
+
+/// Encoding of an attestation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttestationEncoding {
+    /// Canonical BCS serialization, merkelized with the requested hash
+    /// algorithm.
+    #[default]
+    Bcs,
+    /// ABI-encoded fields with a Keccak-256 Merkle root, and an EIP-712
+    /// signature of the header in the given domain.
+    Evm(evm::Eip712Domain),
+}
+
+impl AttestationEncoding {
+    /// Checks that the algorithms are compatible with the encoding.
+    pub(crate) fn check_algs(
+        &self,
+        signature_alg: &SignatureAlgId,
+        hash_alg: &HashAlgId,
+    ) -> Result<(), String> {
+        match self {
+            AttestationEncoding::Bcs => Ok(()),
+            AttestationEncoding::Evm(_) => {
+                if *hash_alg != HashAlgId::KECCAK256 {
+                    Err(format!(
+                        "evm encoding requires the keccak256 hash algorithm, got {hash_alg}"
+                    ))
+                } else if *signature_alg != SignatureAlgId::SECP256K1ETH {
+                    Err(format!(
+                        "evm encoding requires the secp256k1eth signature algorithm, got {signature_alg}"
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
 }
 
 /// Attestation header.
@@ -141,6 +229,38 @@ pub struct Header {
 
 impl_domain_separator!(Header);
 
+impl Header {
+    /// Checks that the header is of the current [`VERSION`].
+    pub(crate) fn check_version(&self) -> Result<(), AttestationError> {
+        if self.version != VERSION {
+            return Err(AttestationError::new(
+                ErrorKind::Version,
+                format!(
+                    "unsupported version {}, expected {}",
+                    self.version.0, VERSION.0
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Verifying key of the holder of an attestation.
+///
+/// Wraps the key so that it is hashed with a different domain separator than
+/// the Notary [`VerifyingKey`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct HolderKey(pub(crate) VerifyingKey);
+
+impl_domain_separator!(HolderKey);
+
+/// Expiry of an attestation, in seconds since the UNIX epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct NotAfter(pub(crate) u64);
+
+impl_domain_separator!(NotAfter);
+
 /// Attestation body.
 ///
 /// See [module level documentation](crate::attestation) for more information.
@@ -152,6 +272,8 @@ pub struct Body {
     cert_commitment: Field<ServerCertCommitment>,
     extensions: Vec<Field<Extension>>,
     transcript_commitments: Vec<Field<TranscriptCommitment>>,
+    holder_key: Option<Field<HolderKey>>,
+    not_after: Option<Field<NotAfter>>,
 }
 
 impl Body {
@@ -165,6 +287,18 @@ impl Body {
         &self.verifying_key.data
     }
 
+    /// Returns the verifying key of the holder, if the Prover committed to
+    /// one.
+    pub fn holder_key(&self) -> Option<&VerifyingKey> {
+        self.holder_key.as_ref().map(|field| &field.data.0)
+    }
+
+    /// Returns the time after which the attestation is expired, in seconds
+    /// since the UNIX epoch, if the Notary set one.
+    pub fn not_after(&self) -> Option<u64> {
+        self.not_after.as_ref().map(|field| field.data.0)
+    }
+
     /// Computes the Merkle root of the attestation fields.
     ///
     /// This is only used when building an attestation.
@@ -197,6 +331,8 @@ impl Body {
             cert_commitment,
             extensions,
             transcript_commitments,
+            holder_key,
+            not_after,
         } = self;
 
         let mut fields: Vec<(FieldId, Hash)> = vec![
@@ -212,6 +348,14 @@ impl Body {
             ),
         ];
 
+        if let Some(field) = holder_key {
+            fields.push((field.id, hasher.hash_separated(&field.data)));
+        }
+
+        if let Some(field) = not_after {
+            fields.push((field.id, hasher.hash_separated(&field.data)));
+        }
+
         for field in extensions.iter() {
             fields.push((field.id, hasher.hash_separated(&field.data)));
         }
@@ -225,7 +369,7 @@ impl Body {
     }
 
     /// Returns the connection information.
-    pub(crate) fn connection_info(&self) -> &ConnectionInfo {
+    pub fn connection_info(&self) -> &ConnectionInfo {
         &self.connection_info.data
     }
 
@@ -256,6 +400,8 @@ pub struct Attestation {
     pub header: Header,
     /// The attestation body.
     pub body: Body,
+    /// The encoding of the attestation.
+    pub encoding: AttestationEncoding,
 }
 
 impl Attestation {
@@ -271,4 +417,36 @@ impl Attestation {
     ) -> PresentationBuilder<'a> {
         PresentationBuilder::new(provider, self)
     }
+
+    /// Verifies an [`AttestationEncoding::Evm`] attestation the way a smart
+    /// contract would, returning the Ethereum address of the Notary.
+    ///
+    /// This is a reference implementation for on-chain verifiers: the body
+    /// root is recomputed from the ABI-encoded fields and the signer is
+    /// recovered from the EIP-712 digest of the header.
+    pub fn verify_evm(&self) -> Result<[u8; 20], AttestationError> {
+        self.header.check_version()?;
+
+        let AttestationEncoding::Evm(domain) = self.encoding else {
+            return Err(AttestationError::new(
+                ErrorKind::Encoding,
+                "attestation is not evm encoded",
+            ));
+        };
+
+        if evm::body_root(&self.body) != self.header.root {
+            return Err(AttestationError::new(
+                ErrorKind::Body,
+                "body root does not match the header",
+            ));
+        }
+
+        evm::recover(
+            &self.header,
+            &domain,
+            &self.signature,
+            self.body.verifying_key(),
+        )
+        .map_err(|e| AttestationError::new(ErrorKind::Signature, e))
+    }
 }
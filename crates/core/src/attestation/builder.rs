@@ -4,14 +4,14 @@ use rand::{rng, Rng};
 
 use crate::{
     attestation::{
-        Attestation, AttestationConfig, Body, Extension, FieldId, Header, ServerCertCommitment,
-        VERSION,
+        evm, Attestation, AttestationConfig, AttestationEncoding, Body, Extension, FieldId, Header,
+        HolderKey, NotAfter, ServerCertCommitment, VERSION,
     },
     connection::{ConnectionInfo, ServerEphemKey},
     hash::HashAlgId,
     request::Request,
     serialize::CanonicalSerialize,
-    signing::SignatureAlgId,
+    signing::{SignatureAlgId, VerifyingKey},
     transcript::TranscriptCommitment,
     CryptoProvider,
 };
@@ -24,11 +24,13 @@ pub struct Accept {}
 pub struct Sign {
     signature_alg: SignatureAlgId,
     hash_alg: HashAlgId,
+    encoding: AttestationEncoding,
     connection_info: Option<ConnectionInfo>,
     server_ephemeral_key: Option<ServerEphemKey>,
     cert_commitment: ServerCertCommitment,
     extensions: Vec<Extension>,
     transcript_commitments: Vec<TranscriptCommitment>,
+    holder_key: Option<VerifyingKey>,
 }
 
 /// An attestation builder.
@@ -57,8 +59,10 @@ impl<'a> AttestationBuilder<'a, Accept> {
         let Request {
             signature_alg,
             hash_alg,
+            encoding,
             server_cert_commitment: cert_commitment,
             extensions,
+            holder_key,
         } = request;
 
         if !config.supported_signature_algs().contains(&signature_alg) {
@@ -75,6 +79,10 @@ impl<'a> AttestationBuilder<'a, Accept> {
             ));
         }
 
+        encoding
+            .check_algs(&signature_alg, &hash_alg)
+            .map_err(|err| AttestationBuilderError::new(ErrorKind::Request, err))?;
+
         if let Some(validator) = config.extension_validator() {
             validator(&extensions)
                 .map_err(|err| AttestationBuilderError::new(ErrorKind::Extension, err))?;
@@ -85,11 +93,13 @@ impl<'a> AttestationBuilder<'a, Accept> {
             state: Sign {
                 signature_alg,
                 hash_alg,
+                encoding,
                 connection_info: None,
                 server_ephemeral_key: None,
                 cert_commitment,
                 transcript_commitments: Vec::new(),
                 extensions,
+                holder_key,
             },
         })
     }
@@ -125,14 +135,27 @@ impl AttestationBuilder<'_, Sign> {
 
     /// Builds the attestation.
     pub fn build(self, provider: &CryptoProvider) -> Result<Attestation, AttestationBuilderError> {
+        self.build_unsigned(provider)?.sign(provider)
+    }
+
+    /// Builds the attestation without signing it.
+    ///
+    /// This allows the signature to be created separately, e.g. if the signer
+    /// blocks, see [`UnsignedAttestation::sign`].
+    pub fn build_unsigned(
+        self,
+        provider: &CryptoProvider,
+    ) -> Result<UnsignedAttestation, AttestationBuilderError> {
         let Sign {
             signature_alg,
             hash_alg,
+            encoding,
             connection_info,
             server_ephemeral_key,
             cert_commitment,
             extensions,
             transcript_commitments,
+            holder_key,
         } = self.state;
 
         let hasher = provider.hash.get(&hash_alg).map_err(|_| {
@@ -150,17 +173,25 @@ impl AttestationBuilder<'_, Sign> {
             )
         })?;
 
+        let connection_info = connection_info.ok_or_else(|| {
+            AttestationBuilderError::new(ErrorKind::Field, "connection info was not set")
+        })?;
+        let not_after = self
+            .config
+            .validity()
+            .map(|validity| NotAfter(connection_info.time.saturating_add(validity.as_secs())));
+
         let mut field_id = FieldId::default();
 
         let body = Body {
             verifying_key: field_id.next(signer.verifying_key()),
-            connection_info: field_id.next(connection_info.ok_or_else(|| {
-                AttestationBuilderError::new(ErrorKind::Field, "connection info was not set")
-            })?),
+            connection_info: field_id.next(connection_info),
             server_ephemeral_key: field_id.next(server_ephemeral_key.ok_or_else(|| {
                 AttestationBuilderError::new(ErrorKind::Field, "handshake data was not set")
             })?),
             cert_commitment: field_id.next(cert_commitment),
+            holder_key: holder_key.map(|key| field_id.next(HolderKey(key))),
+            not_after: not_after.map(|not_after| field_id.next(not_after)),
             extensions: extensions
                 .into_iter()
                 .map(|extension| field_id.next(extension))
@@ -171,20 +202,83 @@ impl AttestationBuilder<'_, Sign> {
                 .collect(),
         };
 
+        let root = match encoding {
+            AttestationEncoding::Bcs => body.root(hasher),
+            AttestationEncoding::Evm(_) => evm::body_root(&body),
+        };
+
         let header = Header {
             id: rng().random(),
             version: VERSION,
-            root: body.root(hasher),
+            root,
+        };
+
+        Ok(UnsignedAttestation {
+            signature_alg,
+            header,
+            body,
+            encoding,
+        })
+    }
+}
+
+/// An attestation which is yet to be signed.
+#[derive(Debug)]
+pub struct UnsignedAttestation {
+    signature_alg: SignatureAlgId,
+    header: Header,
+    body: Body,
+    encoding: AttestationEncoding,
+}
+
+impl UnsignedAttestation {
+    /// Returns the header of the attestation.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Signs the attestation.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider, its signer must have the
+    ///   verifying key the attestation was built with.
+    pub fn sign(self, provider: &CryptoProvider) -> Result<Attestation, AttestationBuilderError> {
+        let Self {
+            signature_alg,
+            header,
+            body,
+            encoding,
+        } = self;
+
+        let signer = provider.signer.get(&signature_alg).map_err(|_| {
+            AttestationBuilderError::new(
+                ErrorKind::Config,
+                format!("signature algorithm {signature_alg} is missing in the provider"),
+            )
+        })?;
+
+        if signer.verifying_key() != body.verifying_key.data {
+            return Err(AttestationBuilderError::new(
+                ErrorKind::Config,
+                "signer does not match the verifying key of the attestation",
+            ));
+        }
+
+        let msg = match encoding {
+            AttestationEncoding::Bcs => CanonicalSerialize::serialize(&header),
+            AttestationEncoding::Evm(domain) => evm::signing_message(&header, &domain),
         };
 
         let signature = signer
-            .sign(&CanonicalSerialize::serialize(&header))
+            .sign(&msg)
             .map_err(|err| AttestationBuilderError::new(ErrorKind::Signature, err))?;
 
         Ok(Attestation {
             signature,
             header,
             body,
+            encoding,
         })
     }
 }
@@ -242,6 +336,8 @@ impl std::fmt::Display for AttestationBuilderError {
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
     use rstest::{fixture, rstest};
     use tlsn_data_fixtures::http::{request::GET_WITH_HEADER, response::OK_JSON};
 
@@ -481,4 +577,101 @@ mod test {
 
         assert_eq!(attestation.body.extensions().count(), 1);
     }
+
+    #[rstest]
+    fn test_attestation_builder_sign_mismatched_signer(
+        attestation_config: &AttestationConfig,
+        crypto_provider: &CryptoProvider,
+    ) {
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        let connection = ConnectionFixture::tlsnotary(transcript.length());
+
+        let RequestFixture { request, .. } = request_fixture(
+            transcript,
+            encoding_provider(GET_WITH_HEADER, OK_JSON),
+            connection.clone(),
+            Blake3::default(),
+            Vec::new(),
+        );
+
+        let mut attestation_builder = Attestation::builder(attestation_config)
+            .accept_request(request)
+            .unwrap();
+
+        let ConnectionFixture {
+            server_cert_data,
+            connection_info,
+            ..
+        } = connection;
+
+        let HandshakeData::V1_2(HandshakeDataV1_2 {
+            server_ephemeral_key,
+            ..
+        }) = server_cert_data.handshake;
+
+        attestation_builder
+            .connection_info(connection_info)
+            .server_ephemeral_key(server_ephemeral_key);
+
+        let unsigned = attestation_builder.build_unsigned(crypto_provider).unwrap();
+
+        // The signer must match the verifying key in the body.
+        let mut other_provider = CryptoProvider::default();
+        other_provider.signer.set_secp256k1(&[7u8; 32]).unwrap();
+        let err = unsigned.sign(&other_provider).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Config));
+    }
+
+    #[rstest]
+    fn test_attestation_builder_validity(crypto_provider: &CryptoProvider) {
+        let attestation_config = AttestationConfig::builder()
+            .supported_signature_algs([SignatureAlgId::SECP256K1])
+            .validity(Duration::from_secs(3600))
+            .build()
+            .unwrap();
+
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        let connection = ConnectionFixture::tlsnotary(transcript.length());
+
+        let RequestFixture { request, .. } = request_fixture(
+            transcript,
+            encoding_provider(GET_WITH_HEADER, OK_JSON),
+            connection.clone(),
+            Blake3::default(),
+            Vec::new(),
+        );
+
+        let mut attestation_builder = Attestation::builder(&attestation_config)
+            .accept_request(request)
+            .unwrap();
+
+        let ConnectionFixture {
+            server_cert_data,
+            connection_info,
+            ..
+        } = connection;
+
+        let HandshakeData::V1_2(HandshakeDataV1_2 {
+            server_ephemeral_key,
+            ..
+        }) = server_cert_data.handshake;
+
+        let time = connection_info.time;
+        attestation_builder
+            .connection_info(connection_info)
+            .server_ephemeral_key(server_ephemeral_key);
+
+        let attestation = attestation_builder.build(crypto_provider).unwrap();
+        assert_eq!(attestation.body.not_after(), Some(time + 3600));
+
+        // The fixture connection is long past, so the attestation is expired.
+        let provider = CryptoProvider::default();
+        let err = attestation
+            .presentation_builder(&provider)
+            .build()
+            .unwrap()
+            .verify(&provider)
+            .unwrap_err();
+        assert!(err.to_string().contains("attestation expired"));
+    }
 }
@@ -1,4 +1,4 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, sync::Arc, time::Duration};
 
 use crate::{
     attestation::{Extension, InvalidExtension},
@@ -46,6 +46,7 @@ pub struct AttestationConfig {
     supported_signature_algs: Vec<SignatureAlgId>,
     supported_hash_algs: Vec<HashAlgId>,
     extension_validator: Option<ExtensionValidator>,
+    validity: Option<Duration>,
 }
 
 impl AttestationConfig {
@@ -65,6 +66,10 @@ impl AttestationConfig {
     pub(crate) fn extension_validator(&self) -> Option<&ExtensionValidator> {
         self.extension_validator.as_ref()
     }
+
+    pub(crate) fn validity(&self) -> Option<Duration> {
+        self.validity
+    }
 }
 
 impl Debug for AttestationConfig {
@@ -72,6 +77,7 @@ impl Debug for AttestationConfig {
         f.debug_struct("AttestationConfig")
             .field("supported_signature_algs", &self.supported_signature_algs)
             .field("supported_hash_algs", &self.supported_hash_algs)
+            .field("validity", &self.validity)
             .finish_non_exhaustive()
     }
 }
@@ -81,6 +87,7 @@ pub struct AttestationConfigBuilder {
     supported_signature_algs: Vec<SignatureAlgId>,
     supported_hash_algs: Vec<HashAlgId>,
     extension_validator: Option<ExtensionValidator>,
+    validity: Option<Duration>,
 }
 
 impl Default for AttestationConfigBuilder {
@@ -97,6 +104,7 @@ impl Default for AttestationConfigBuilder {
                     Ok(())
                 }
             })),
+            validity: None,
         }
     }
 }
@@ -143,12 +151,22 @@ impl AttestationConfigBuilder {
         self
     }
 
+    /// Sets the validity period of attestations.
+    ///
+    /// The attestations expire this long after the time of the connection.
+    /// By default attestations do not expire.
+    pub fn validity(&mut self, validity: Duration) -> &mut Self {
+        self.validity = Some(validity);
+        self
+    }
+
     /// Builds the configuration.
     pub fn build(&self) -> Result<AttestationConfig, AttestationConfigError> {
         Ok(AttestationConfig {
             supported_signature_algs: self.supported_signature_algs.clone(),
             supported_hash_algs: self.supported_hash_algs.clone(),
             extension_validator: self.extension_validator.clone(),
+            validity: self.validity,
         })
     }
 }
@@ -158,6 +176,7 @@ impl Debug for AttestationConfigBuilder {
         f.debug_struct("AttestationConfigBuilder")
             .field("supported_signature_algs", &self.supported_signature_algs)
             .field("supported_hash_algs", &self.supported_hash_algs)
+            .field("validity", &self.validity)
             .finish_non_exhaustive()
     }
 }
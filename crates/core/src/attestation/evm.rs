@@ -0,0 +1,691 @@
+//! Ethereum-compatible attestation encoding.
+//!
+//! With [`AttestationEncoding::Evm`](crate::attestation::AttestationEncoding::Evm)
+//! an attestation can be verified by a smart contract using only `keccak256`,
+//! `abi.encode` and `ecrecover`.
+//!
+//! # Body
+//!
+//! Each field of the body is ABI-encoded, see [`AbiEncode`], and hashed into a
+//! leaf:
+//!
+//! ```text
+//! leaf = keccak256(0x00 || abi.encode(uint32 id, uint8 kind, bytes data))
+//! ```
+//!
+//! where `kind` is the [`FieldKind`]. The leaves, ordered by field id, form a
+//! binary Merkle tree where each parent is:
+//!
+//! ```text
+//! node = keccak256(0x01 || left || right)
+//! ```
+//!
+//! A level with an odd number of nodes is padded with a zero node, i.e.
+//! `bytes32(0)`. The root of the tree is the root of the header.
+//!
+//! # Header
+//!
+//! The header is signed as EIP-712 typed data:
+//!
+//! ```text
+//! EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)
+//! Attestation(bytes16 id,uint32 version,bytes32 root)
+//! ```
+//!
+//! with the domain name `"TLSNotary"`, the attestation version as the domain
+//! version, and the chain id and verifying contract of the [`Eip712Domain`]
+//! requested by the Prover. The signature is a
+//! [`SignatureAlgId::SECP256K1ETH`] signature of the EIP-712 digest.
+
+use k256::ecdsa::{RecoveryId, Signature as Secp256K1Signature, VerifyingKey as K256VerifyingKey};
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::{
+    attestation::{Body, Extension, FieldKind, Header},
+    connection::{ConnectionInfo, ServerCertCommitment, ServerEphemKey, TlsVersion},
+    hash::{Hash, HashAlgId, TypedHash},
+    signing::{KeyAlgId, Signature, SignatureAlgId, VerifyingKey},
+    transcript::{
+        predicate::{CmpOp, PredicateKind},
+        Direction, Idx, TranscriptCommitment,
+    },
+};
+
+/// EIP-712 domain name.
+pub const DOMAIN_NAME: &str = "TLSNotary";
+const DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const ATTESTATION_TYPE: &str = "Attestation(bytes16 id,uint32 version,bytes32 root)";
+
+/// Prefix of a leaf of the body tree.
+const LEAF_PREFIX: u8 = 0x00;
+/// Prefix of an interior node of the body tree.
+const NODE_PREFIX: u8 = 0x01;
+
+/// Chain-specific part of the EIP-712 domain.
+///
+/// Binds the signature to the contract which verifies attestations, so that it
+/// can not be replayed to another contract or chain.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Eip712Domain {
+    /// EIP-155 chain id.
+    pub chain_id: u64,
+    /// Address of the verifying contract.
+    pub verifying_contract: [u8; 20],
+}
+
+/// ABI-encoding of an attestation field.
+pub trait AbiEncode {
+    /// Returns `abi.encode` of the field.
+    fn abi_encode(&self) -> Vec<u8>;
+}
+
+impl AbiEncode for VerifyingKey {
+    /// `(uint8 alg, bytes data)`
+    fn abi_encode(&self) -> Vec<u8> {
+        encode(&[
+            Token::Uint(self.alg.as_u8() as u64),
+            Token::Bytes(self.data.clone()),
+        ])
+    }
+}
+
+impl AbiEncode for ConnectionInfo {
+    /// `(uint64 time, uint16 version, uint32 sent, uint32 received)`
+    fn abi_encode(&self) -> Vec<u8> {
+        let version = match self.version {
+            TlsVersion::V1_2 => 0x0303,
+            TlsVersion::V1_3 => 0x0304,
+        };
+
+        encode(&[
+            Token::Uint(self.time),
+            Token::Uint(version),
+            Token::Uint(self.transcript_length.sent as u64),
+            Token::Uint(self.transcript_length.received as u64),
+        ])
+    }
+}
+
+impl AbiEncode for ServerEphemKey {
+    /// `(uint16 type, bytes key)`
+    fn abi_encode(&self) -> Vec<u8> {
+        encode(&[Token::Uint(self.typ as u64), Token::Bytes(self.key.clone())])
+    }
+}
+
+impl AbiEncode for ServerCertCommitment {
+    /// `(uint8 alg, bytes32 hash)`
+    fn abi_encode(&self) -> Vec<u8> {
+        encode(&typed_hash(&self.0))
+    }
+}
+
+impl AbiEncode for Extension {
+    /// `(bytes id, bytes value)`
+    fn abi_encode(&self) -> Vec<u8> {
+        encode(&[
+            Token::Bytes(self.id.clone()),
+            Token::Bytes(self.value.clone()),
+        ])
+    }
+}
+
+impl AbiEncode for TranscriptCommitment {
+    /// - Encoding: `(uint8 alg, bytes32 root, bytes32 seed, bytes16 delta)`
+    /// - Hash: `(uint8 direction, uint256[] ranges, uint8 alg, bytes32 hash)`
+    /// - Masked hash: `(uint8 direction, uint8 alg, bytes32 hash)`
+    /// - Predicate: `(uint8 direction, uint256[] ranges, uint8 kind, bytes
+    ///   data)`
+    ///
+    /// Ranges are flattened into `[start, end, ..]` pairs. For predicates the
+    /// kind and data are:
+    ///
+    /// - Equal: `0`, the constant.
+    /// - Compare: `1`, `abi.encode(uint8 op, uint64 value)` where `op` is `<`,
+    ///   `<=`, `>`, `>=` in this order.
+    /// - Contains: `2`, the substring.
+    /// - Regex: `3`, the UTF-8 encoded pattern.
+    fn abi_encode(&self) -> Vec<u8> {
+        match self {
+            TranscriptCommitment::Encoding(commitment) => {
+                let mut tokens = typed_hash(&commitment.root).to_vec();
+                tokens.push(Token::FixedBytes(commitment.secret.seed().to_vec()));
+                tokens.push(Token::FixedBytes(commitment.secret.delta().to_vec()));
+                encode(&tokens)
+            }
+            TranscriptCommitment::Hash(hash) => {
+                let mut tokens = vec![direction(hash.direction), ranges(&hash.idx)];
+                tokens.extend(typed_hash(&hash.hash));
+                encode(&tokens)
+            }
+            TranscriptCommitment::MaskedHash(hash) => {
+                let mut tokens = vec![direction(hash.direction)];
+                tokens.extend(typed_hash(&hash.hash));
+                encode(&tokens)
+            }
+            TranscriptCommitment::Predicate(predicate) => {
+                let (kind, data) = match &predicate.kind {
+                    PredicateKind::Equal { value } => (0, value.clone()),
+                    PredicateKind::Compare { op, value } => {
+                        let op = match op {
+                            CmpOp::Lt => 0,
+                            CmpOp::Lte => 1,
+                            CmpOp::Gt => 2,
+                            CmpOp::Gte => 3,
+                        };
+                        (1, encode(&[Token::Uint(op), Token::Uint(*value)]))
+                    }
+                    PredicateKind::Contains { needle } => (2, needle.clone()),
+                    PredicateKind::Regex { pattern } => (3, pattern.as_bytes().to_vec()),
+                };
+
+                encode(&[
+                    direction(predicate.direction),
+                    ranges(&predicate.idx),
+                    Token::Uint(kind),
+                    Token::Bytes(data),
+                ])
+            }
+        }
+    }
+}
+
+fn direction(direction: Direction) -> Token {
+    Token::Uint(match direction {
+        Direction::Sent => 0,
+        Direction::Received => 1,
+    })
+}
+
+fn ranges(idx: &Idx) -> Token {
+    Token::Array(
+        idx.iter_ranges()
+            .flat_map(|range| {
+                [
+                    Token::Uint(range.start as u64),
+                    Token::Uint(range.end as u64),
+                ]
+            })
+            .collect(),
+    )
+}
+
+fn typed_hash(hash: &TypedHash) -> [Token; 2] {
+    [
+        Token::Uint(hash.alg.as_u8() as u64),
+        Token::FixedBytes(Vec::from(hash.value)),
+    ]
+}
+
+/// Returns the leaves of the body, ordered by field id.
+fn leaves(body: &Body) -> Vec<[u8; 32]> {
+    // CRITICAL: ensure all fields are included! See `Body::hash_fields`.
+    let Body {
+        verifying_key,
+        connection_info,
+        server_ephemeral_key,
+        cert_commitment,
+        extensions,
+        transcript_commitments,
+        holder_key,
+        not_after,
+    } = body;
+
+    let mut fields = vec![
+        (
+            verifying_key.id,
+            FieldKind::VerifyingKey,
+            verifying_key.data.abi_encode(),
+        ),
+        (
+            connection_info.id,
+            FieldKind::ConnectionInfo,
+            connection_info.data.abi_encode(),
+        ),
+        (
+            server_ephemeral_key.id,
+            FieldKind::ServerEphemKey,
+            server_ephemeral_key.data.abi_encode(),
+        ),
+        (
+            cert_commitment.id,
+            FieldKind::ServerIdentityCommitment,
+            cert_commitment.data.abi_encode(),
+        ),
+    ];
+
+    if let Some(field) = holder_key {
+        fields.push((field.id, FieldKind::HolderKey, field.data.0.abi_encode()));
+    }
+
+    if let Some(field) = not_after {
+        // `(uint64 not_after)`
+        let data = encode(&[Token::Uint(field.data.0)]);
+        fields.push((field.id, FieldKind::NotAfter, data));
+    }
+
+    for field in extensions {
+        fields.push((field.id, FieldKind::Extension, field.data.abi_encode()));
+    }
+
+    for field in transcript_commitments {
+        let kind = match &field.data {
+            TranscriptCommitment::Encoding(_) => FieldKind::EncodingCommitment,
+            TranscriptCommitment::Hash(_) => FieldKind::PlaintextHash,
+            TranscriptCommitment::MaskedHash(_) => FieldKind::MaskedPlaintextHash,
+            TranscriptCommitment::Predicate(_) => FieldKind::Predicate,
+        };
+        fields.push((field.id, kind, field.data.abi_encode()));
+    }
+
+    fields.sort_by_key(|(id, ..)| *id);
+    fields
+        .into_iter()
+        .map(|(id, kind, data)| {
+            let leaf = encode(&[
+                Token::Uint(id.0 as u64),
+                Token::Uint(kind as u64),
+                Token::Bytes(data),
+            ]);
+            keccak256(&[[LEAF_PREFIX].as_slice(), &leaf].concat())
+        })
+        .collect()
+}
+
+/// Computes the Merkle root of the body.
+pub(crate) fn body_root(body: &Body) -> TypedHash {
+    TypedHash {
+        alg: HashAlgId::KECCAK256,
+        value: Hash::try_from(root(leaves(body)).to_vec()).expect("hash is 32 bytes"),
+    }
+}
+
+/// Computes the Merkle root of the leaves.
+fn root(mut level: Vec<[u8; 32]>) -> [u8; 32] {
+    while level.len() > 1 {
+        if level.len() % 2 == 1 {
+            level.push([0; 32]);
+        }
+
+        level = level
+            .chunks_exact(2)
+            .map(|pair| keccak256(&[[NODE_PREFIX].as_slice(), &pair[0], &pair[1]].concat()))
+            .collect();
+    }
+
+    level.first().copied().unwrap_or_default()
+}
+
+/// Returns the message which is signed, i.e. the pre-image of the EIP-712
+/// digest.
+pub(crate) fn signing_message(header: &Header, domain: &Eip712Domain) -> Vec<u8> {
+    let separator = keccak256(&encode(&[
+        Token::FixedBytes(keccak256(DOMAIN_TYPE.as_bytes()).to_vec()),
+        Token::FixedBytes(keccak256(DOMAIN_NAME.as_bytes()).to_vec()),
+        Token::FixedBytes(keccak256(header.version.0.to_string().as_bytes()).to_vec()),
+        Token::Uint(domain.chain_id),
+        Token::Address(domain.verifying_contract),
+    ]));
+
+    let header = keccak256(&encode(&[
+        Token::FixedBytes(keccak256(ATTESTATION_TYPE.as_bytes()).to_vec()),
+        Token::FixedBytes(header.id.0.to_vec()),
+        Token::Uint(header.version.0 as u64),
+        Token::FixedBytes(Vec::from(header.root.value)),
+    ]));
+
+    [[0x19, 0x01].as_slice(), &separator, &header].concat()
+}
+
+/// Returns the EIP-712 digest of the header.
+pub fn signing_hash(header: &Header, domain: &Eip712Domain) -> [u8; 32] {
+    keccak256(&signing_message(header, domain))
+}
+
+/// Recovers the Ethereum address of the signer, as `ecrecover` does, and
+/// checks that it corresponds to the verifying key.
+pub(crate) fn recover(
+    header: &Header,
+    domain: &Eip712Domain,
+    signature: &Signature,
+    verifying_key: &VerifyingKey,
+) -> Result<[u8; 20], &'static str> {
+    if signature.alg != SignatureAlgId::SECP256K1ETH {
+        return Err("signature algorithm is not secp256k1eth");
+    } else if verifying_key.alg != KeyAlgId::K256 {
+        return Err("key algorithm is not k256");
+    }
+
+    let [sig @ .., v] = signature.data.as_slice() else {
+        return Err("signature is empty");
+    };
+
+    if sig.len() != 64 {
+        return Err("signature must be 65 bytes");
+    }
+
+    let recid = match *v {
+        v @ (27 | 28) => RecoveryId::from_byte(v - 27).expect("recovery id is valid"),
+        _ => return Err("invalid recovery id"),
+    };
+
+    let sig = Secp256K1Signature::from_slice(sig).map_err(|_| "invalid signature")?;
+    let recovered =
+        K256VerifyingKey::recover_from_prehash(&signing_hash(header, domain), &sig, recid)
+            .map_err(|_| "failed to recover key")?;
+
+    let key =
+        K256VerifyingKey::from_sec1_bytes(&verifying_key.data).map_err(|_| "invalid k256 key")?;
+    if recovered != key {
+        return Err("signature is not from the verifying key");
+    }
+
+    Ok(address(&key))
+}
+
+/// Returns the Ethereum address of the key.
+pub fn address(key: &K256VerifyingKey) -> [u8; 20] {
+    let point = key.to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+
+    let mut address = [0; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut output = [0; 32];
+    hasher.finalize(&mut output);
+    output
+}
+
+/// Solidity ABI value.
+enum Token {
+    /// An unsigned integer of at most 64 bits, e.g. `uint32`.
+    Uint(u64),
+    /// A `bytesN` value, `N <= 32`.
+    FixedBytes(Vec<u8>),
+    /// An `address` value.
+    Address([u8; 20]),
+    /// A `bytes` value.
+    Bytes(Vec<u8>),
+    /// A `T[]` value of static elements.
+    Array(Vec<Token>),
+}
+
+impl Token {
+    fn is_dynamic(&self) -> bool {
+        matches!(self, Token::Bytes(_) | Token::Array(_))
+    }
+}
+
+/// Encodes the tokens as a tuple, i.e. `abi.encode(tokens...)`.
+fn encode(tokens: &[Token]) -> Vec<u8> {
+    let head_len = 32 * tokens.len();
+
+    let mut head = Vec::with_capacity(head_len);
+    let mut tail = Vec::new();
+    for token in tokens {
+        match token {
+            Token::Uint(value) => head.extend(word(*value)),
+            Token::FixedBytes(value) => head.extend(padded(value)),
+            Token::Address(value) => {
+                head.extend([0; 12]);
+                head.extend(value);
+            }
+            Token::Bytes(value) => {
+                head.extend(word((head_len + tail.len()) as u64));
+                tail.extend(word(value.len() as u64));
+                tail.extend(padded(value));
+            }
+            Token::Array(values) => {
+                debug_assert!(!values.iter().any(Token::is_dynamic));
+
+                head.extend(word((head_len + tail.len()) as u64));
+                tail.extend(word(values.len() as u64));
+                tail.extend(encode(values));
+            }
+        }
+    }
+
+    head.extend(tail);
+    head
+}
+
+/// Encodes an integer as a big-endian 32-byte word.
+fn word(value: u64) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Right-pads bytes with zeros to a multiple of 32 bytes.
+fn padded(value: &[u8]) -> Vec<u8> {
+    let mut padded = value.to_vec();
+    padded.resize(value.len().div_ceil(32) * 32, 0);
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{keccak256 as keccak256_ref, Address, Bytes, FixedBytes, U256};
+    use alloy_signer_local::PrivateKeySigner;
+    use alloy_sol_types::{
+        eip712_domain, sol,
+        sol_data::{self, Array, Uint},
+        SolStruct, SolType,
+    };
+    use tlsn_data_fixtures::http::{request::GET_WITH_HEADER, response::OK_JSON};
+
+    use super::*;
+    use crate::{
+        attestation::{Attestation, AttestationEncoding, Uid, VERSION},
+        fixtures::{attestation_fixture, ConnectionFixture},
+        request::{Request, RequestConfig},
+        transcript::{predicate::Predicate, Transcript},
+        CryptoProvider,
+    };
+
+    #[test]
+    fn test_abi_encode() {
+        let encoded = encode(&[Token::Uint(1), Token::Bytes(b"abc".to_vec())]);
+
+        assert_eq!(
+            hex::encode(encoded),
+            [
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "6162630000000000000000000000000000000000000000000000000000000000",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_abi_encode_array() {
+        let encoded = encode(&[
+            Token::Array(vec![Token::Uint(1), Token::Uint(2)]),
+            Token::FixedBytes(vec![0xff; 16]),
+        ]);
+
+        assert_eq!(
+            hex::encode(encoded),
+            [
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "ffffffffffffffffffffffffffffffff00000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+            ]
+            .concat()
+        );
+    }
+
+    const DOMAIN: Eip712Domain = Eip712Domain {
+        chain_id: 11155111,
+        verifying_contract: [0x42; 20],
+    };
+
+    fn evm_attestation() -> Attestation {
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        let connection = ConnectionFixture::tlsnotary(transcript.length());
+
+        let mut builder = RequestConfig::builder();
+        builder
+            .signature_alg(SignatureAlgId::SECP256K1ETH)
+            .hash_alg(HashAlgId::KECCAK256)
+            .encoding(AttestationEncoding::Evm(DOMAIN));
+        let config = builder.build().unwrap();
+
+        let mut builder = Request::builder(&config);
+        builder
+            .server_name(connection.server_name.clone())
+            .server_cert_data(connection.server_cert_data.clone())
+            .transcript(transcript);
+        let (request, _) = builder.build(&CryptoProvider::default()).unwrap();
+
+        attestation_fixture(request, connection, SignatureAlgId::SECP256K1ETH, &[])
+    }
+
+    #[test]
+    fn test_verify_evm() {
+        let attestation = evm_attestation();
+
+        let signer = PrivateKeySigner::from_slice(&[42u8; 32]).unwrap();
+        assert_eq!(
+            attestation.verify_evm().unwrap(),
+            signer.address().into_array()
+        );
+    }
+
+    #[test]
+    fn test_verify_evm_tampered_body() {
+        let mut attestation = evm_attestation();
+        attestation.body.connection_info.data.time += 1;
+
+        assert!(attestation.verify_evm().is_err());
+    }
+
+    #[test]
+    fn test_verify_evm_tampered_signature() {
+        let mut attestation = evm_attestation();
+        *attestation.signature.data.last_mut().unwrap() = 29;
+
+        assert!(attestation.verify_evm().is_err());
+    }
+
+    #[test]
+    fn test_verify_evm_other_domain() {
+        let mut attestation = evm_attestation();
+        attestation.encoding = AttestationEncoding::Evm(Eip712Domain {
+            chain_id: 1,
+            ..DOMAIN
+        });
+
+        assert!(attestation.verify_evm().is_err());
+    }
+
+    #[test]
+    fn test_evm_encoding_requires_algs() {
+        let mut builder = RequestConfig::builder();
+        builder.encoding(AttestationEncoding::Evm(DOMAIN));
+
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn test_body_root() {
+        let leaves: Vec<[u8; 32]> = (0..3u8).map(|i| [i; 32]).collect();
+
+        let node = |left: &[u8], right: &[u8]| {
+            keccak256_ref([[NODE_PREFIX].as_slice(), left, right].concat()).0
+        };
+        let expected = node(&node(&leaves[0], &leaves[1]), &node(&leaves[2], &[0; 32]));
+
+        assert_eq!(root(leaves), expected);
+    }
+
+    #[test]
+    fn test_eip712_signing_hash() {
+        sol! {
+            struct Attestation {
+                bytes16 id;
+                uint32 version;
+                bytes32 root;
+            }
+        }
+
+        let header = Header {
+            id: Uid([0x11; 16]),
+            version: VERSION,
+            root: TypedHash {
+                alg: HashAlgId::KECCAK256,
+                value: Hash::try_from(vec![0xab; 32]).unwrap(),
+            },
+        };
+
+        let expected = Attestation {
+            id: FixedBytes(header.id.0),
+            version: header.version.0,
+            root: FixedBytes([0xab; 32]),
+        }
+        .eip712_signing_hash(&eip712_domain! {
+            name: DOMAIN_NAME,
+            version: VERSION.0.to_string(),
+            chain_id: DOMAIN.chain_id,
+            verifying_contract: Address::from(DOMAIN.verifying_contract),
+        });
+
+        let message = signing_message(&header, &DOMAIN);
+        assert_eq!(message.len(), 66);
+        assert_eq!(message[..2], [0x19, 0x01]);
+        assert_eq!(signing_hash(&header, &DOMAIN), expected.0);
+    }
+
+    #[test]
+    fn test_abi_encode_predicate() {
+        let commitment = TranscriptCommitment::Predicate(Predicate {
+            direction: Direction::Sent,
+            idx: Idx::new([0..3, 5..8]),
+            kind: PredicateKind::Compare {
+                op: CmpOp::Gte,
+                value: 100,
+            },
+        });
+
+        assert_eq!(
+            hex::encode(commitment.abi_encode()),
+            [
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000080",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000120",
+                "0000000000000000000000000000000000000000000000000000000000000004",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "0000000000000000000000000000000000000000000000000000000000000005",
+                "0000000000000000000000000000000000000000000000000000000000000008",
+                "0000000000000000000000000000000000000000000000000000000000000040",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "0000000000000000000000000000000000000000000000000000000000000064",
+            ]
+            .concat()
+        );
+
+        type CompareAbi = (Uint<8>, Uint<64>);
+        type PredicateAbi = (Uint<8>, Array<Uint<256>>, Uint<8>, sol_data::Bytes);
+
+        let expected = PredicateAbi::abi_encode_params(&(
+            0,
+            [0, 3, 5, 8].map(U256::from).to_vec(),
+            1,
+            Bytes::from(CompareAbi::abi_encode_params(&(3, 100))),
+        ));
+        assert_eq!(commitment.abi_encode(), expected);
+    }
+}
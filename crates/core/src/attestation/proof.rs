@@ -3,7 +3,7 @@ use std::fmt;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    attestation::{Attestation, Body, Header},
+    attestation::{evm, Attestation, AttestationEncoding, Body, Header},
     hash::HashAlgorithm,
     merkle::{MerkleProof, MerkleTree},
     serialize::CanonicalSerialize,
@@ -17,6 +17,7 @@ pub struct AttestationProof {
     signature: Signature,
     header: Header,
     body: BodyProof,
+    encoding: AttestationEncoding,
 }
 
 impl AttestationProof {
@@ -24,17 +25,25 @@ impl AttestationProof {
         provider: &CryptoProvider,
         attestation: &Attestation,
     ) -> Result<Self, AttestationError> {
-        let hasher = provider
-            .hash
-            .get(&attestation.header.root.alg)
-            .map_err(|e| AttestationError::new(ErrorKind::Provider, e))?;
-
-        let body = BodyProof::new(hasher, attestation.body.clone())?;
+        let body = match attestation.encoding {
+            AttestationEncoding::Bcs => {
+                let hasher = provider
+                    .hash
+                    .get(&attestation.header.root.alg)
+                    .map_err(|e| AttestationError::new(ErrorKind::Provider, e))?;
+
+                BodyProof::new(hasher, attestation.body.clone())?
+            }
+            // The root is recomputed from the entire body, see
+            // `BodyProof::verify_evm`.
+            AttestationEncoding::Evm(_) => BodyProof::new_evm(attestation.body.clone()),
+        };
 
         Ok(Self {
             signature: attestation.signature.clone(),
             header: attestation.header.clone(),
             body,
+            encoding: attestation.encoding,
         })
     }
 
@@ -43,6 +52,18 @@ impl AttestationProof {
         self.body.verifying_key()
     }
 
+    /// Returns the attestation header.
+    pub(crate) fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the attestation body.
+    ///
+    /// The body is not verified, see [`AttestationProof::verify`].
+    pub(crate) fn body_unchecked(&self) -> &Body {
+        &self.body.body
+    }
+
     /// Verifies the attestation proof.
     ///
     /// # Arguments
@@ -50,27 +71,35 @@ impl AttestationProof {
     /// * `provider` - Cryptography provider.
     /// * `verifying_key` - Verifying key for the Notary signature.
     pub fn verify(self, provider: &CryptoProvider) -> Result<Attestation, AttestationError> {
+        self.header.check_version()?;
+
         let signature_verifier = provider
             .signature
             .get(&self.signature.alg)
             .map_err(|e| AttestationError::new(ErrorKind::Provider, e))?;
 
         // Verify that the body is corresponding to the header.
-        let body = self.body.verify_with_provider(provider, &self.header)?;
+        let (body, msg) = match self.encoding {
+            AttestationEncoding::Bcs => (
+                self.body.verify_with_provider(provider, &self.header)?,
+                CanonicalSerialize::serialize(&self.header),
+            ),
+            AttestationEncoding::Evm(domain) => (
+                self.body.verify_evm(&self.header)?,
+                evm::signing_message(&self.header, &domain),
+            ),
+        };
 
         // Verify signature of the header.
         signature_verifier
-            .verify(
-                &body.verifying_key.data,
-                &CanonicalSerialize::serialize(&self.header),
-                &self.signature.data,
-            )
+            .verify(&body.verifying_key.data, &msg, &self.signature.data)
             .map_err(|e| AttestationError::new(ErrorKind::Signature, e))?;
 
         Ok(Attestation {
             signature: self.signature,
             header: self.header,
             body,
+            encoding: self.encoding,
         })
     }
 }
@@ -80,8 +109,9 @@ impl AttestationProof {
 pub(crate) struct BodyProof {
     body: Body,
     /// A proof of inclusion of a subset of fields in the `body`.
-    // Currently, proves the inclusion of all fields.
-    proof: MerkleProof,
+    // Currently, proves the inclusion of all fields. Not used with
+    // `AttestationEncoding::Evm`.
+    proof: Option<MerkleProof>,
 }
 
 impl BodyProof {
@@ -103,7 +133,16 @@ impl BodyProof {
 
         let proof = tree.proof(&indices);
 
-        Ok(BodyProof { body, proof })
+        Ok(BodyProof {
+            body,
+            proof: Some(proof),
+        })
+    }
+
+    /// Returns a new body proof for an [`AttestationEncoding::Evm`]
+    /// attestation.
+    pub(crate) fn new_evm(body: Body) -> BodyProof {
+        BodyProof { body, proof: None }
     }
 
     pub(crate) fn verifying_key(&self) -> &VerifyingKey {
@@ -127,12 +166,31 @@ impl BodyProof {
             .into_iter()
             .map(|(id, hash)| (id.0 as usize, hash));
 
-        self.proof
+        let proof = self
+            .proof
+            .ok_or_else(|| AttestationError::new(ErrorKind::Body, "missing body proof"))?;
+
+        proof
             .verify(hasher, &header.root, fields)
             .map_err(|e| AttestationError::new(ErrorKind::Body, e))?;
 
         Ok(self.body)
     }
+
+    /// Verifies the body against an [`AttestationEncoding::Evm`] header.
+    ///
+    /// The proof covers the entire body, so the root is recomputed from the
+    /// fields instead.
+    pub(crate) fn verify_evm(self, header: &Header) -> Result<Body, AttestationError> {
+        if evm::body_root(&self.body) != header.root {
+            return Err(AttestationError::new(
+                ErrorKind::Body,
+                "body root does not match the header",
+            ));
+        }
+
+        Ok(self.body)
+    }
 }
 
 /// Error for [`AttestationProof`].
@@ -143,7 +201,7 @@ pub struct AttestationError {
 }
 
 impl AttestationError {
-    fn new<E>(kind: ErrorKind, source: E) -> Self
+    pub(super) fn new<E>(kind: ErrorKind, source: E) -> Self
     where
         E: Into<Box<dyn std::error::Error + Send + Sync>>,
     {
@@ -162,6 +220,8 @@ impl fmt::Display for AttestationError {
             ErrorKind::Provider => f.write_str("provider error")?,
             ErrorKind::Signature => f.write_str("signature error")?,
             ErrorKind::Body => f.write_str("body proof error")?,
+            ErrorKind::Encoding => f.write_str("encoding error")?,
+            ErrorKind::Version => f.write_str("version error")?,
         }
 
         if let Some(source) = &self.source {
@@ -173,8 +233,10 @@ impl fmt::Display for AttestationError {
 }
 
 #[derive(Debug)]
-enum ErrorKind {
+pub(super) enum ErrorKind {
     Provider,
     Signature,
     Body,
+    Encoding,
+    Version,
 }
@@ -0,0 +1,235 @@
+use std::{collections::BTreeSet, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attestation::{Attestation, Uid},
+    serialize::CanonicalSerialize,
+    signing::{Signature, Signer, VerifyingKey},
+    CryptoProvider,
+};
+
+/// Domain separator of the message signed by the Notary.
+const DOMAIN: &[u8] = b"tlsn/attestation/revocation";
+
+/// A list of attestations revoked by a Notary.
+///
+/// The list only applies to attestations signed with the key of the
+/// [`issuer`](RevocationList::issuer).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevocationList {
+    /// Verifying key of the Notary which issued the list.
+    pub issuer: VerifyingKey,
+    /// Time at which the list was issued, in seconds since the UNIX epoch.
+    pub issued_at: u64,
+    /// Identifiers of the revoked attestations.
+    pub revoked: BTreeSet<Uid>,
+}
+
+impl RevocationList {
+    /// Creates a new empty revocation list.
+    ///
+    /// # Arguments
+    ///
+    /// * `issuer` - Verifying key of the Notary.
+    /// * `issued_at` - Time at which the list is issued, in seconds since the
+    ///   UNIX epoch.
+    pub fn new(issuer: VerifyingKey, issued_at: u64) -> Self {
+        Self {
+            issuer,
+            issued_at,
+            revoked: BTreeSet::new(),
+        }
+    }
+
+    /// Revokes the attestation with the given identifier.
+    pub fn revoke(&mut self, id: Uid) -> &mut Self {
+        self.revoked.insert(id);
+        self
+    }
+
+    /// Returns `true` if the attestation was issued by the issuer of the list
+    /// and is revoked.
+    pub fn is_revoked(&self, attestation: &Attestation) -> bool {
+        attestation.body.verifying_key() == &self.issuer
+            && self.revoked.contains(&attestation.header.id)
+    }
+
+    /// Signs the list.
+    ///
+    /// # Arguments
+    ///
+    /// * `signer` - Signer with the key of the issuer.
+    pub fn sign(self, signer: &dyn Signer) -> Result<SignedRevocationList, RevocationError> {
+        if signer.verifying_key() != self.issuer {
+            return Err(RevocationError::new(
+                ErrorKind::Issuer,
+                "signer key does not match the issuer",
+            ));
+        }
+
+        let signature = signer
+            .sign(&self.message())
+            .map_err(|e| RevocationError::new(ErrorKind::Signature, e))?;
+
+        Ok(SignedRevocationList {
+            list: self,
+            signature,
+        })
+    }
+
+    fn message(&self) -> Vec<u8> {
+        [DOMAIN, &CanonicalSerialize::serialize(self)].concat()
+    }
+}
+
+/// A [`RevocationList`] signed by its issuer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedRevocationList {
+    list: RevocationList,
+    signature: Signature,
+}
+
+impl SignedRevocationList {
+    /// Returns the verifying key of the issuer.
+    pub fn issuer(&self) -> &VerifyingKey {
+        &self.list.issuer
+    }
+
+    /// Verifies the signature of the issuer, returning the list.
+    ///
+    /// This does not check whether the issuer is trusted.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    pub fn verify(self, provider: &CryptoProvider) -> Result<RevocationList, RevocationError> {
+        let verifier = provider
+            .signature
+            .get(&self.signature.alg)
+            .map_err(|e| RevocationError::new(ErrorKind::Provider, e))?;
+
+        verifier
+            .verify(
+                &self.list.issuer,
+                &self.list.message(),
+                &self.signature.data,
+            )
+            .map_err(|e| RevocationError::new(ErrorKind::Signature, e))?;
+
+        Ok(self.list)
+    }
+}
+
+/// Error for [`RevocationList`].
+#[derive(Debug, thiserror::Error)]
+pub struct RevocationError {
+    kind: ErrorKind,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl RevocationError {
+    fn new<E>(kind: ErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self {
+            kind,
+            source: Some(source.into()),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Provider,
+    Issuer,
+    Signature,
+}
+
+impl fmt::Display for RevocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("revocation list error: ")?;
+
+        match self.kind {
+            ErrorKind::Provider => f.write_str("provider error")?,
+            ErrorKind::Issuer => f.write_str("issuer error")?,
+            ErrorKind::Signature => f.write_str("signature error")?,
+        }
+
+        if let Some(source) = &self.source {
+            write!(f, " caused by: {}", source)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlsn_data_fixtures::http::{request::GET_WITH_HEADER, response::OK_JSON};
+
+    use super::*;
+    use crate::{
+        fixtures::{
+            attestation_fixture, encoding_provider, request_fixture, ConnectionFixture,
+            RequestFixture,
+        },
+        hash::Blake3,
+        signing::{Secp256k1Signer, SignatureAlgId},
+        transcript::Transcript,
+    };
+
+    fn attestation() -> Attestation {
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        let connection = ConnectionFixture::tlsnotary(transcript.length());
+        let RequestFixture { request, .. } = request_fixture(
+            transcript,
+            encoding_provider(GET_WITH_HEADER, OK_JSON),
+            connection.clone(),
+            Blake3::default(),
+            Vec::new(),
+        );
+
+        attestation_fixture(request, connection, SignatureAlgId::SECP256K1, &[])
+    }
+
+    #[test]
+    fn test_revocation_list() {
+        let provider = CryptoProvider::default();
+        // Same key as the attestation fixture.
+        let signer = Secp256k1Signer::new(&[42u8; 32]).unwrap();
+        let revoked = attestation();
+        let other = attestation();
+
+        let mut list = RevocationList::new(signer.verifying_key(), 1700000000);
+        list.revoke(revoked.header.id.clone());
+
+        let list = list.sign(&signer).unwrap().verify(&provider).unwrap();
+        assert!(list.is_revoked(&revoked));
+        assert!(!list.is_revoked(&other));
+
+        // A list of another issuer does not apply.
+        let other_signer = Secp256k1Signer::new(&[1u8; 32]).unwrap();
+        let mut list = RevocationList::new(other_signer.verifying_key(), 1700000000);
+        list.revoke(revoked.header.id.clone());
+        assert!(!list.is_revoked(&revoked));
+
+        let err = list.clone().sign(&signer).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Issuer));
+    }
+
+    #[test]
+    fn test_revocation_list_tampered() {
+        let provider = CryptoProvider::default();
+        let signer = Secp256k1Signer::new(&[42u8; 32]).unwrap();
+
+        let mut signed = RevocationList::new(signer.verifying_key(), 1700000000)
+            .sign(&signer)
+            .unwrap();
+        signed.list.revoke(attestation().header.id);
+
+        let err = signed.verify(&provider).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Signature));
+    }
+}
@@ -242,6 +242,9 @@ pub fn attestation_fixture(
     match signature_alg {
         SignatureAlgId::SECP256K1 => provider.signer.set_secp256k1(&[42u8; 32]).unwrap(),
         SignatureAlgId::SECP256R1 => provider.signer.set_secp256r1(&[42u8; 32]).unwrap(),
+        SignatureAlgId::SECP256K1ETH => provider.signer.set_secp256k1eth(&[42u8; 32]).unwrap(),
+        SignatureAlgId::ED25519 => provider.signer.set_ed25519(&[42u8; 32]).unwrap(),
+        SignatureAlgId::BLS12381 => provider.signer.set_bls12381(&[42u8; 32]).unwrap(),
         _ => unimplemented!(),
     };
 
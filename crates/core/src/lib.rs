@@ -97,6 +97,8 @@
 //! The Notary's [`CryptoProvider`] must be configured with an appropriate
 //! signing key for attestations. See
 //! [`SignerProvider`](crate::signing::SignerProvider) for more information.
+//! The key may also be kept outside of the Notary, e.g. in a KMS or HSM, using a
+//! [`RemoteSigner`](crate::signing::RemoteSigner).
 //!
 //! # Constructing a presentation
 //!
@@ -182,6 +184,7 @@ pub mod connection;
 pub mod fixtures;
 pub mod hash;
 pub(crate) mod merkle;
+pub mod policy;
 pub mod presentation;
 mod provider;
 pub mod request;
@@ -189,9 +192,12 @@ mod secrets;
 pub(crate) mod serialize;
 pub mod signing;
 pub mod transcript;
+pub mod transparency;
+pub mod verify;
 
 pub use provider::CryptoProvider;
 pub use secrets::Secrets;
+pub use verify::{PolicyError, VerifyConfig, VerifyConfigBuilder, VerifyConfigBuilderError};
 
 use rangeset::ToRangeSet;
 use serde::{Deserialize, Serialize};
@@ -343,41 +349,6 @@ enum ProveConfigBuilderErrorRepr {
     },
 }
 
-/// Configuration to verify information from the prover.
-#[derive(Debug, Default, Clone)]
-pub struct VerifyConfig {}
-
-impl VerifyConfig {
-    /// Creates a new builder.
-    pub fn builder() -> VerifyConfigBuilder {
-        VerifyConfigBuilder::new()
-    }
-}
-
-/// Builder for [`VerifyConfig`].
-#[derive(Debug, Default)]
-pub struct VerifyConfigBuilder {}
-
-impl VerifyConfigBuilder {
-    /// Creates a new builder.
-    pub fn new() -> Self {
-        Self {}
-    }
-
-    /// Builds the configuration.
-    pub fn build(self) -> Result<VerifyConfig, VerifyConfigBuilderError> {
-        Ok(VerifyConfig {})
-    }
-}
-
-/// Error for [`VerifyConfigBuilder`].
-#[derive(Debug, thiserror::Error)]
-#[error(transparent)]
-pub struct VerifyConfigBuilderError(#[from] VerifyConfigBuilderErrorRepr);
-
-#[derive(Debug, thiserror::Error)]
-enum VerifyConfigBuilderErrorRepr {}
-
 /// Payload sent to the verifier.
 #[doc(hidden)]
 #[derive(Debug, Serialize, Deserialize)]
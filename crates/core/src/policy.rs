@@ -0,0 +1,708 @@
+//! Verification policy.
+//!
+//! [`Presentation::verify`](crate::presentation::Presentation::verify) checks
+//! that a presentation is authentic, but leaves it to the Verifier to decide
+//! whether the Notary which signed the attestation is trusted, and whether the
+//! presentation discloses what the Verifier needs.
+//!
+//! A [`VerificationPolicy`] captures these requirements:
+//!
+//! - the Notaries which are trusted, in a [`TrustStore`], with the validity
+//!   period of their keys and whether a key was revoked,
+//! - the accepted server names,
+//! - the maximum age of the attested connection,
+//! - the transcript data which must be disclosed,
+//! - the [`Challenge`] the presentation must be bound to, if any,
+//! - the revocation lists of the Notaries,
+//! - whether the attestation must be in the
+//!   [transparency log](crate::transparency) of its Notary.
+//!
+//! Expired attestations are always rejected.
+//!
+//! [`Presentation::verify_with_policy`](crate::presentation::Presentation::verify_with_policy)
+//! verifies the presentation and checks it against the policy, returning a
+//! [`PolicyError`] with all the violations if the presentation is not
+//! acceptable.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use web_time::Duration;
+
+use crate::{
+    attestation::{RevocationList, Uid},
+    connection::ServerName,
+    presentation::{Challenge, PresentationOutput},
+    signing::VerifyingKey,
+    transcript::{Direction, Idx},
+};
+
+/// A Notary trusted by the Verifier.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrustedNotary {
+    /// Verifying key of the Notary.
+    pub key: VerifyingKey,
+    /// Start of the validity period of the key, in seconds since the UNIX
+    /// epoch.
+    pub not_before: Option<u64>,
+    /// End of the validity period of the key (exclusive), in seconds since the
+    /// UNIX epoch.
+    pub not_after: Option<u64>,
+    /// Whether the key was revoked.
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+impl TrustedNotary {
+    /// Creates a new trusted Notary whose key is valid indefinitely.
+    pub fn new(key: VerifyingKey) -> Self {
+        Self {
+            key,
+            not_before: None,
+            not_after: None,
+            revoked: false,
+        }
+    }
+
+    /// Sets the start of the validity period of the key, in seconds since the
+    /// UNIX epoch.
+    pub fn not_before(mut self, time: u64) -> Self {
+        self.not_before = Some(time);
+        self
+    }
+
+    /// Sets the end of the validity period of the key (exclusive), in seconds
+    /// since the UNIX epoch.
+    pub fn not_after(mut self, time: u64) -> Self {
+        self.not_after = Some(time);
+        self
+    }
+
+    /// Checks that the key was valid at the given time.
+    fn check(&self, time: u64) -> Result<(), PolicyViolation> {
+        if self.revoked {
+            Err(PolicyViolation::RevokedNotary(self.key.clone()))
+        } else if self.not_before.is_some_and(|not_before| time < not_before)
+            || self.not_after.is_some_and(|not_after| time >= not_after)
+        {
+            Err(PolicyViolation::ExpiredNotary(self.key.clone()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Set of Notaries trusted by the Verifier.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    notaries: Vec<TrustedNotary>,
+}
+
+impl TrustStore {
+    /// Creates an empty trust store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a trusted Notary, replacing any Notary with the same key.
+    pub fn add(&mut self, notary: TrustedNotary) -> &mut Self {
+        self.notaries.retain(|trusted| trusted.key != notary.key);
+        self.notaries.push(notary);
+        self
+    }
+
+    /// Revokes the key of a Notary.
+    ///
+    /// Returns `false` if the key is not in the trust store.
+    pub fn revoke(&mut self, key: &VerifyingKey) -> bool {
+        match self.notaries.iter_mut().find(|notary| notary.key == *key) {
+            Some(notary) => {
+                notary.revoked = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the trusted Notary with the given key.
+    pub fn get(&self, key: &VerifyingKey) -> Option<&TrustedNotary> {
+        self.notaries.iter().find(|notary| notary.key == *key)
+    }
+
+    /// Returns an iterator over the trusted Notaries.
+    pub fn iter(&self) -> impl Iterator<Item = &TrustedNotary> {
+        self.notaries.iter()
+    }
+
+    /// Checks that the key is trusted at the given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Verifying key of the Notary.
+    /// * `time` - Time of the attested connection, in seconds since the UNIX
+    ///   epoch.
+    pub fn check(&self, key: &VerifyingKey, time: u64) -> Result<(), PolicyViolation> {
+        self.get(key)
+            .ok_or_else(|| PolicyViolation::UntrustedNotary(key.clone()))?
+            .check(time)
+    }
+}
+
+/// Policy which a presentation must satisfy to be accepted by the Verifier.
+///
+/// See the [module level documentation](crate::policy) for more information.
+#[derive(Debug, Clone)]
+pub struct VerificationPolicy {
+    trust_store: TrustStore,
+    server_names: Vec<ServerName>,
+    max_age: Option<Duration>,
+    require_server_identity: bool,
+    required_sent: Idx,
+    required_received: Idx,
+    challenge: Option<Challenge>,
+    revocation_lists: Vec<RevocationList>,
+    require_logged: bool,
+}
+
+impl VerificationPolicy {
+    /// Creates a new policy accepting presentations attested by one of the
+    /// Notaries in the trust store.
+    pub fn new(trust_store: TrustStore) -> Self {
+        Self {
+            trust_store,
+            server_names: Vec::new(),
+            max_age: None,
+            require_server_identity: false,
+            required_sent: Idx::empty(),
+            required_received: Idx::empty(),
+            challenge: None,
+            revocation_lists: Vec::new(),
+            require_logged: false,
+        }
+    }
+
+    /// Returns the trust store.
+    pub fn trust_store(&self) -> &TrustStore {
+        &self.trust_store
+    }
+
+    /// Returns a mutable reference to the trust store, e.g. to revoke a key.
+    pub fn trust_store_mut(&mut self) -> &mut TrustStore {
+        &mut self.trust_store
+    }
+
+    /// Accepts the given server name.
+    ///
+    /// If no server name is set, any server is accepted. Setting a server name
+    /// requires the server identity to be disclosed.
+    pub fn server_name(&mut self, name: ServerName) -> &mut Self {
+        self.server_names.push(name);
+        self
+    }
+
+    /// Sets the maximum age of the attested connection.
+    pub fn max_age(&mut self, max_age: Duration) -> &mut Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Requires the server identity to be disclosed.
+    pub fn require_server_identity(&mut self) -> &mut Self {
+        self.require_server_identity = true;
+        self
+    }
+
+    /// Requires the given ranges of the transcript to be disclosed.
+    pub fn require_disclosed(&mut self, direction: Direction, idx: &Idx) -> &mut Self {
+        let required = match direction {
+            Direction::Sent => &mut self.required_sent,
+            Direction::Received => &mut self.required_received,
+        };
+
+        required.union_mut(idx);
+
+        self
+    }
+
+    /// Requires the presentation to be bound to the given challenge, which
+    /// must not be expired.
+    ///
+    /// The Verifier is responsible for accepting each challenge only once.
+    pub fn challenge(&mut self, challenge: Challenge) -> &mut Self {
+        self.challenge = Some(challenge);
+        self
+    }
+
+    /// Adds the revocation list of a Notary.
+    ///
+    /// The list must be verified, see
+    /// [`SignedRevocationList::verify`](crate::attestation::SignedRevocationList::verify).
+    pub fn revocation_list(&mut self, list: RevocationList) -> &mut Self {
+        self.revocation_lists.push(list);
+        self
+    }
+
+    /// Requires a proof that the attestation is in the transparency log of its
+    /// Notary.
+    ///
+    /// The Verifier is responsible for checking that the reported
+    /// [tree head](PresentationOutput::tree_head) is consistent with the heads
+    /// they have seen.
+    pub fn require_logged(&mut self) -> &mut Self {
+        self.require_logged = true;
+        self
+    }
+
+    /// Checks the output of a verified presentation against the policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - Output of the verified presentation.
+    /// * `now` - Current time, in seconds since the UNIX epoch.
+    pub fn check(&self, output: &PresentationOutput, now: u64) -> Result<(), PolicyError> {
+        let mut violations = Vec::new();
+        let time = output.connection_info.time;
+
+        // Notary.
+        if let Err(violation) = self
+            .trust_store
+            .check(output.attestation.body.verifying_key(), time)
+        {
+            violations.push(violation);
+        }
+
+        // Expiry and revocation.
+        let id = &output.attestation.header.id;
+
+        if let Some(not_after) = output.attestation.body.not_after() {
+            if now > not_after {
+                violations.push(PolicyViolation::AttestationExpired {
+                    id: id.clone(),
+                    not_after,
+                });
+            }
+        }
+
+        if self
+            .revocation_lists
+            .iter()
+            .any(|list| list.is_revoked(&output.attestation))
+        {
+            violations.push(PolicyViolation::AttestationRevoked(id.clone()));
+        }
+
+        // Connection time.
+        if let Some(max_age) = self.max_age {
+            let age = Duration::from_secs(now.saturating_sub(time));
+            if age > max_age {
+                violations.push(PolicyViolation::TooOld { age, max_age });
+            }
+        }
+
+        // Server identity.
+        match &output.server_name {
+            Some(name) => {
+                if !self.server_names.is_empty() && !self.server_names.contains(name) {
+                    violations.push(PolicyViolation::ServerName(name.clone()));
+                }
+            }
+            None => {
+                if self.require_server_identity || !self.server_names.is_empty() {
+                    violations.push(PolicyViolation::MissingServerIdentity);
+                }
+            }
+        }
+
+        // Transcript.
+        for (direction, required) in [
+            (Direction::Sent, &self.required_sent),
+            (Direction::Received, &self.required_received),
+        ] {
+            if required.is_empty() {
+                continue;
+            }
+
+            let missing = match &output.transcript {
+                Some(transcript) => required.difference(match direction {
+                    Direction::Sent => transcript.sent_authed(),
+                    Direction::Received => transcript.received_authed(),
+                }),
+                None => required.clone(),
+            };
+
+            if !missing.is_empty() {
+                violations.push(PolicyViolation::Undisclosed {
+                    direction,
+                    idx: missing,
+                });
+            }
+        }
+
+        // Binding.
+        if let Some(challenge) = &self.challenge {
+            match &output.binding {
+                Some(binding) if binding != challenge => {
+                    violations.push(PolicyViolation::ChallengeMismatch)
+                }
+                Some(_) => {}
+                None => violations.push(PolicyViolation::Unbound),
+            }
+
+            if challenge.is_expired(now) {
+                violations.push(PolicyViolation::ChallengeExpired {
+                    expires_at: challenge.expires_at,
+                });
+            }
+        }
+
+        // Transparency.
+        if self.require_logged && output.tree_head.is_none() {
+            violations.push(PolicyViolation::NotLogged);
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(PolicyError { violations })
+        }
+    }
+}
+
+/// Reason for which a presentation does not satisfy a [`VerificationPolicy`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PolicyViolation {
+    /// The Notary is not in the trust store.
+    UntrustedNotary(VerifyingKey),
+    /// The key of the Notary was revoked.
+    RevokedNotary(VerifyingKey),
+    /// The key of the Notary was not valid at the time of the connection.
+    ExpiredNotary(VerifyingKey),
+    /// The attestation is expired.
+    AttestationExpired {
+        /// Identifier of the attestation.
+        id: Uid,
+        /// Expiry of the attestation, in seconds since the UNIX epoch.
+        not_after: u64,
+    },
+    /// The attestation was revoked by its Notary.
+    AttestationRevoked(Uid),
+    /// The connection is older than the maximum age.
+    TooOld {
+        /// Age of the connection.
+        age: Duration,
+        /// Maximum age of the connection.
+        max_age: Duration,
+    },
+    /// The server identity was not disclosed.
+    MissingServerIdentity,
+    /// The server name is not accepted.
+    ServerName(ServerName),
+    /// Required transcript data was not disclosed.
+    Undisclosed {
+        /// Direction of the transcript.
+        direction: Direction,
+        /// Ranges of the transcript which were not disclosed.
+        idx: Idx,
+    },
+    /// The presentation is not bound to a challenge.
+    Unbound,
+    /// The presentation is bound to a different challenge.
+    ChallengeMismatch,
+    /// The challenge is expired.
+    ChallengeExpired {
+        /// Expiry of the challenge, in seconds since the UNIX epoch.
+        expires_at: u64,
+    },
+    /// The attestation is not proven to be in the transparency log.
+    NotLogged,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::UntrustedNotary(key) => {
+                write!(f, "notary {} key is not trusted", key.alg)
+            }
+            PolicyViolation::RevokedNotary(key) => {
+                write!(f, "notary {} key was revoked", key.alg)
+            }
+            PolicyViolation::ExpiredNotary(key) => write!(
+                f,
+                "notary {} key was not valid at the time of the connection",
+                key.alg
+            ),
+            PolicyViolation::AttestationExpired { id, not_after } => {
+                write!(f, "attestation {id} expired at {not_after}")
+            }
+            PolicyViolation::AttestationRevoked(id) => {
+                write!(f, "attestation {id} was revoked")
+            }
+            PolicyViolation::TooOld { age, max_age } => write!(
+                f,
+                "connection is {}s old, at most {}s is accepted",
+                age.as_secs(),
+                max_age.as_secs()
+            ),
+            PolicyViolation::MissingServerIdentity => {
+                write!(f, "server identity was not disclosed")
+            }
+            PolicyViolation::ServerName(name) => {
+                write!(f, "server name {} is not accepted", name.as_str())
+            }
+            PolicyViolation::Undisclosed { direction, idx } => {
+                write!(f, "{direction} data {idx} was not disclosed")
+            }
+            PolicyViolation::Unbound => write!(f, "presentation is not bound to a challenge"),
+            PolicyViolation::ChallengeMismatch => {
+                write!(f, "presentation is bound to a different challenge")
+            }
+            PolicyViolation::ChallengeExpired { expires_at } => {
+                write!(f, "challenge expired at {expires_at}")
+            }
+            PolicyViolation::NotLogged => {
+                write!(f, "attestation is not proven to be in the transparency log")
+            }
+        }
+    }
+}
+
+/// Error for [`VerificationPolicy`], listing all violations of the policy.
+#[derive(Debug, thiserror::Error)]
+pub struct PolicyError {
+    violations: Vec<PolicyViolation>,
+}
+
+impl PolicyError {
+    /// Returns the violations of the policy.
+    pub fn violations(&self) -> &[PolicyViolation] {
+        &self.violations
+    }
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("policy violated: ")?;
+
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{violation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlsn_data_fixtures::http::{request::GET_WITH_HEADER, response::OK_JSON};
+
+    use super::*;
+    use crate::{
+        attestation::Attestation,
+        fixtures::{
+            attestation_fixture, encoding_provider, request_fixture, ConnectionFixture,
+            RequestFixture,
+        },
+        hash::Blake3,
+        signing::SignatureAlgId,
+        transcript::Transcript,
+        CryptoProvider,
+    };
+
+    fn attestation() -> Attestation {
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        let connection = ConnectionFixture::tlsnotary(transcript.length());
+        let RequestFixture { request, .. } = request_fixture(
+            transcript,
+            encoding_provider(GET_WITH_HEADER, OK_JSON),
+            connection.clone(),
+            Blake3::default(),
+            Vec::new(),
+        );
+
+        attestation_fixture(request, connection, SignatureAlgId::SECP256K1, &[])
+    }
+
+    /// Returns the output of a verified presentation, with the server name and
+    /// the sent data disclosed.
+    fn output() -> PresentationOutput {
+        let provider = CryptoProvider::default();
+        let mut output = attestation()
+            .presentation_builder(&provider)
+            .build()
+            .unwrap()
+            .verify(&provider)
+            .unwrap();
+
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        output.server_name = Some(ServerName::new("tlsnotary.org".to_string()));
+        output.transcript =
+            Some(transcript.to_partial(Idx::new(0..GET_WITH_HEADER.len()), Idx::empty()));
+        output
+    }
+
+    fn policy(output: &PresentationOutput) -> VerificationPolicy {
+        let mut trust_store = TrustStore::new();
+        trust_store.add(TrustedNotary::new(
+            output.attestation.body.verifying_key().clone(),
+        ));
+        VerificationPolicy::new(trust_store)
+    }
+
+    #[test]
+    fn test_policy_accepts() {
+        let output = output();
+        let time = output.connection_info.time;
+
+        let mut policy = policy(&output);
+        policy
+            .server_name(ServerName::new("tlsnotary.org".to_string()))
+            .max_age(Duration::from_secs(3600))
+            .require_disclosed(Direction::Sent, &Idx::new(0..10));
+
+        policy.check(&output, time + 60).unwrap();
+    }
+
+    #[test]
+    fn test_policy_untrusted_notary() {
+        let output = output();
+        let time = output.connection_info.time;
+
+        let err = VerificationPolicy::new(TrustStore::new())
+            .check(&output, time)
+            .unwrap_err();
+        assert_eq!(
+            err.violations(),
+            [PolicyViolation::UntrustedNotary(
+                output.attestation.body.verifying_key().clone()
+            )]
+        );
+
+        // Revoked key.
+        let mut policy = policy(&output);
+        assert!(policy
+            .trust_store_mut()
+            .revoke(output.attestation.body.verifying_key()));
+        let err = policy.check(&output, time).unwrap_err();
+        assert!(matches!(
+            err.violations()[0],
+            PolicyViolation::RevokedNotary(_)
+        ));
+
+        // Key which was not valid at the time of the connection.
+        let mut trust_store = TrustStore::new();
+        trust_store.add(
+            TrustedNotary::new(output.attestation.body.verifying_key().clone()).not_after(time),
+        );
+        let err = VerificationPolicy::new(trust_store)
+            .check(&output, time)
+            .unwrap_err();
+        assert!(matches!(
+            err.violations()[0],
+            PolicyViolation::ExpiredNotary(_)
+        ));
+    }
+
+    #[test]
+    fn test_policy_reports_all_violations() {
+        let mut output = output();
+        let time = output.connection_info.time;
+        output.server_name = Some(ServerName::new("example.com".to_string()));
+
+        let mut policy = policy(&output);
+        policy
+            .server_name(ServerName::new("tlsnotary.org".to_string()))
+            .max_age(Duration::from_secs(3600))
+            .require_disclosed(Direction::Received, &Idx::new(0..10));
+
+        let err = policy.check(&output, time + 7200).unwrap_err();
+        assert_eq!(
+            err.violations(),
+            [
+                PolicyViolation::TooOld {
+                    age: Duration::from_secs(7200),
+                    max_age: Duration::from_secs(3600)
+                },
+                PolicyViolation::ServerName(ServerName::new("example.com".to_string())),
+                PolicyViolation::Undisclosed {
+                    direction: Direction::Received,
+                    idx: Idx::new(0..10)
+                },
+            ]
+        );
+
+        // The server identity must be disclosed if a server name is required.
+        output.server_name = None;
+        let err = policy.check(&output, time).unwrap_err();
+        assert_eq!(
+            err.violations(),
+            [
+                PolicyViolation::MissingServerIdentity,
+                PolicyViolation::Undisclosed {
+                    direction: Direction::Received,
+                    idx: Idx::new(0..10)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_policy_challenge() {
+        let mut output = output();
+        let time = output.connection_info.time;
+        let challenge = Challenge::new("verifier.example", time + 60);
+
+        let mut policy = policy(&output);
+        policy.challenge(challenge.clone());
+
+        let err = policy.check(&output, time).unwrap_err();
+        assert_eq!(err.violations(), [PolicyViolation::Unbound]);
+
+        output.binding = Some(Challenge::new("verifier.example", time + 60));
+        let err = policy.check(&output, time).unwrap_err();
+        assert_eq!(err.violations(), [PolicyViolation::ChallengeMismatch]);
+
+        output.binding = Some(challenge);
+        policy.check(&output, time).unwrap();
+
+        let err = policy.check(&output, time + 61).unwrap_err();
+        assert_eq!(
+            err.violations(),
+            [PolicyViolation::ChallengeExpired {
+                expires_at: time + 60
+            }]
+        );
+    }
+
+    #[test]
+    fn test_policy_revocation() {
+        let output = output();
+        let time = output.connection_info.time;
+        let id = output.attestation.header.id.clone();
+
+        let mut list = RevocationList::new(output.attestation.body.verifying_key().clone(), time);
+        list.revoke(id.clone());
+
+        let mut policy = policy(&output);
+        policy.check(&output, time).unwrap();
+
+        policy.revocation_list(list);
+        let err = policy.check(&output, time).unwrap_err();
+        assert_eq!(err.violations(), [PolicyViolation::AttestationRevoked(id)]);
+    }
+
+    #[test]
+    fn test_policy_require_logged() {
+        let output = output();
+        let time = output.connection_info.time;
+
+        let mut policy = policy(&output);
+        policy.require_logged();
+
+        let err = policy.check(&output, time).unwrap_err();
+        assert_eq!(err.violations(), [PolicyViolation::NotLogged]);
+    }
+}
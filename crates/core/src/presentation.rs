@@ -21,19 +21,82 @@
 //! used to sign the attestation, referred to as a [`VerifyingKey`], is from a
 //! Notary they trust. See an [example](crate#verifying-a-presentation) in the
 //! crate level documentation.
+//!
+//! # Verification policy
+//!
+//! Instead of checking the output of a verified presentation themselves,
+//! Verifiers can describe which presentations they accept, e.g. which Notaries
+//! they trust and which data must be disclosed, with a
+//! [`VerificationPolicy`] and use [`Presentation::verify_with_policy`].
+//!
+//! # Expiry and revocation
+//!
+//! [`Presentation::verify`] rejects attestations which are expired at the
+//! current time. It does not check whether an attestation was revoked by its
+//! Notary. Use [`Presentation::verify_with_revocation`], or a
+//! [`VerificationPolicy`] with the
+//! [revocation lists](VerificationPolicy::revocation_list) of the Notaries.
+//!
+//! # Binding to a Verifier
+//!
+//! By default a presentation can be verified by anyone who receives it, so a
+//! Verifier can forward it to someone else. If the Prover committed to a
+//! [holder key](crate::request::RequestConfigBuilder::holder_key) in the
+//! attestation, a presentation can be bound to a [`Challenge`] issued by the
+//! Verifier using [`PresentationBuilder::bind`]. The holder signs the
+//! challenge along with the presentation, and the verified challenge is
+//! reported in [`PresentationOutput::binding`]. The Verifier must check that
+//! it is the challenge they issued and that it is not expired, e.g. with
+//! [`VerificationPolicy::challenge`].
+//!
+//! # Transparency
+//!
+//! If the Notary keeps a [transparency log](crate::transparency) of the
+//! attestations it signs, the Prover can include a proof that the attestation
+//! is in the log with [`PresentationBuilder::transparency_proof`]. The signed
+//! head of the log is reported in [`PresentationOutput::tree_head`], which the
+//! Verifier can check for consistency with the heads they have seen.
+//!
+//! # Narrowing
+//!
+//! A presentation which discloses more than a downstream party needs can be
+//! narrowed with [`Presentation::narrow`], without access to the Prover's
+//! [`Secrets`](crate::Secrets).
+//!
+//! # Bundles
+//!
+//! Facts which span several TLS sessions can be proven with a
+//! [`PresentationBundle`] of presentations, which is verified in one call.
+//! The bundle can assert that all presentations are bound to the same
+//! challenge by the same holder, or disclose the same server name.
+
+mod binding;
+mod bundle;
 
 use std::fmt;
 
+use rangeset::ToRangeSet;
 use serde::{Deserialize, Serialize};
+use web_time::{SystemTime, UNIX_EPOCH};
 
 use crate::{
-    attestation::{Attestation, AttestationError, AttestationProof, Extension},
+    attestation::{Attestation, AttestationError, AttestationProof, Extension, RevocationList},
     connection::{ConnectionInfo, ServerIdentityProof, ServerIdentityProofError, ServerName},
-    signing::VerifyingKey,
-    transcript::{PartialTranscript, TranscriptProof, TranscriptProofError},
+    policy::{PolicyError, VerificationPolicy},
+    signing::{Signer, VerifyingKey},
+    transcript::{
+        predicate::Predicate, Idx, PartialTranscript, TranscriptCommitment, TranscriptProof,
+        TranscriptProofError,
+    },
+    transparency::{TransparencyProof, TreeHead},
     CryptoProvider,
 };
 
+pub use binding::Challenge;
+pub use bundle::{BundleOutput, PresentationBundle};
+
+use binding::HolderBinding;
+
 /// A verifiable presentation.
 ///
 /// See the [module level documentation](crate::presentation) for more
@@ -43,6 +106,10 @@ pub struct Presentation {
     attestation: AttestationProof,
     identity: Option<ServerIdentityProof>,
     transcript: Option<TranscriptProof>,
+    /// Binding to a challenge of the Verifier.
+    binding: Option<HolderBinding>,
+    /// Proof that the attestation is in the transparency log of its Notary.
+    transparency: Option<TransparencyProof>,
 }
 
 impl Presentation {
@@ -59,7 +126,128 @@ impl Presentation {
         self.attestation.verifying_key()
     }
 
-    /// Verifies the presentation.
+    /// Returns a presentation which discloses only the given ranges of the
+    /// transcript.
+    ///
+    /// The ranges must be disclosed by this presentation. Commitments are
+    /// opened as a whole, so the ranges must align to the ranges the Prover
+    /// committed to, e.g. the leaves of the encoding tree, otherwise an error
+    /// is returned.
+    ///
+    /// The server identity proof, attestation and transparency proof are kept.
+    /// A [binding](PresentationBuilder::bind) is dropped, as it is signed over
+    /// the disclosed data.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `reveal_sent` - Ranges of the sent data to reveal.
+    /// * `reveal_recv` - Ranges of the received data to reveal.
+    pub fn narrow(
+        &self,
+        provider: &CryptoProvider,
+        reveal_sent: &dyn ToRangeSet<usize>,
+        reveal_recv: &dyn ToRangeSet<usize>,
+    ) -> Result<Presentation, PresentationError> {
+        let sent = Idx::new(reveal_sent.to_range_set());
+        let recv = Idx::new(reveal_recv.to_range_set());
+
+        let transcript = match &self.transcript {
+            Some(proof) => {
+                let commitment = self
+                    .attestation
+                    .body_unchecked()
+                    .transcript_commitments()
+                    .find_map(|commitment| match commitment {
+                        TranscriptCommitment::Encoding(commitment) => Some(commitment),
+                        _ => None,
+                    });
+
+                Some(proof.narrow(provider, commitment, &sent, &recv)?)
+            }
+            None if sent.is_empty() && recv.is_empty() => None,
+            None => {
+                return Err(PresentationError::new(
+                    ErrorKind::Transcript,
+                    "presentation does not disclose any transcript data",
+                ))
+            }
+        };
+
+        Ok(Presentation {
+            attestation: self.attestation.clone(),
+            identity: self.identity.clone(),
+            transcript,
+            binding: None,
+            transparency: self.transparency.clone(),
+        })
+    }
+
+    /// Verifies the presentation and checks it against a verification
+    /// policy, e.g. that the Notary is trusted.
+    ///
+    /// If the policy is violated, the error lists all the violations, see
+    /// [`PresentationError::policy_error`].
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `policy` - Verification policy.
+    pub fn verify_with_policy(
+        self,
+        provider: &CryptoProvider,
+        policy: &VerificationPolicy,
+    ) -> Result<PresentationOutput, PresentationError> {
+        let output = self.verify(provider)?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| PresentationError::new(ErrorKind::Policy, e.to_string()))?
+            .as_secs();
+
+        policy
+            .check(&output, now)
+            .map_err(|e| PresentationError::new(ErrorKind::Policy, e))?;
+
+        Ok(output)
+    }
+
+    /// Verifies the presentation, checking that the attestation is neither
+    /// expired nor revoked.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `revocation_lists` - Revocation lists of the Notaries, which must be
+    ///   verified, see
+    ///   [`SignedRevocationList::verify`](crate::attestation::SignedRevocationList::verify).
+    pub fn verify_with_revocation(
+        self,
+        provider: &CryptoProvider,
+        revocation_lists: &[RevocationList],
+    ) -> Result<PresentationOutput, PresentationError> {
+        let output = self.verify(provider)?;
+        let attestation = &output.attestation;
+
+        if revocation_lists
+            .iter()
+            .any(|list| list.is_revoked(attestation))
+        {
+            return Err(PresentationError::new(
+                ErrorKind::Revoked,
+                format!("attestation {} was revoked", attestation.header.id),
+            ));
+        }
+
+        Ok(output)
+    }
+
+    /// Verifies the presentation, checking that the attestation is not
+    /// expired at the current time.
+    ///
+    /// Revocation is not checked, use
+    /// [`verify_with_revocation`](Self::verify_with_revocation) or
+    /// [`verify_with_policy`](Self::verify_with_policy) for that.
     pub fn verify(
         self,
         provider: &CryptoProvider,
@@ -68,43 +256,135 @@ impl Presentation {
             attestation,
             identity,
             transcript,
+            binding,
+            transparency,
         } = self;
 
         let attestation = attestation.verify(provider)?;
+        check_expiry(&attestation)?;
 
-        let server_name = identity
-            .map(|identity| {
-                identity.verify_with_provider(
+        verify_disclosures(
+            provider,
+            attestation,
+            identity,
+            transcript,
+            binding,
+            transparency,
+        )
+    }
+}
+
+/// Checks that the attestation is not expired at the current time.
+fn check_expiry(attestation: &Attestation) -> Result<(), PresentationError> {
+    let Some(not_after) = attestation.body.not_after() else {
+        return Ok(());
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| PresentationError::new(ErrorKind::Expired, e.to_string()))?
+        .as_secs();
+
+    if now > not_after {
+        return Err(PresentationError::new(
+            ErrorKind::Expired,
+            format!(
+                "attestation {} expired at {not_after}",
+                attestation.header.id
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verifies the disclosed data of a presentation against its verified
+/// attestation.
+fn verify_disclosures(
+    provider: &CryptoProvider,
+    attestation: Attestation,
+    identity: Option<ServerIdentityProof>,
+    transcript: Option<TranscriptProof>,
+    binding: Option<HolderBinding>,
+    transparency: Option<TransparencyProof>,
+) -> Result<PresentationOutput, PresentationError> {
+    let binding = binding
+        .map(|binding| {
+            let holder_key = attestation.body.holder_key().ok_or_else(|| {
+                PresentationError::new(
+                    ErrorKind::Binding,
+                    "attestation does not contain a holder key",
+                )
+            })?;
+
+            binding
+                .verify(
                     provider,
-                    attestation.body.connection_info().time,
-                    attestation.body.server_ephemeral_key(),
-                    attestation.body.cert_commitment(),
+                    holder_key,
+                    &attestation.header,
+                    identity.as_ref(),
+                    transcript.as_ref(),
                 )
-            })
-            .transpose()?;
+                .map_err(|e| PresentationError::new(ErrorKind::Binding, e))
+        })
+        .transpose()?;
 
-        let transcript = transcript
-            .map(|transcript| {
-                transcript.verify_with_provider(
+    let tree_head = transparency
+        .map(|proof| {
+            proof
+                .verify(
                     provider,
-                    &attestation.body.connection_info().transcript_length,
-                    attestation.body.transcript_commitments(),
+                    &attestation.header,
+                    attestation.body.verifying_key(),
                 )
-            })
-            .transpose()?;
+                .map_err(|e| PresentationError::new(ErrorKind::Transparency, e))
+        })
+        .transpose()?;
+
+    let server_name = identity
+        .map(|identity| {
+            identity.verify_with_provider(
+                provider,
+                attestation.body.connection_info().time,
+                attestation.body.server_ephemeral_key(),
+                attestation.body.cert_commitment(),
+            )
+        })
+        .transpose()?;
+
+    let transcript = transcript
+        .map(|transcript| {
+            transcript.verify_with_provider(
+                provider,
+                &attestation.body.connection_info().transcript_length,
+                attestation.body.transcript_commitments(),
+            )
+        })
+        .transpose()?;
 
-        let connection_info = attestation.body.connection_info().clone();
+    let connection_info = attestation.body.connection_info().clone();
 
-        let extensions = attestation.body.extensions().cloned().collect();
+    let extensions = attestation.body.extensions().cloned().collect();
 
-        Ok(PresentationOutput {
-            attestation,
-            server_name,
-            connection_info,
-            transcript,
-            extensions,
+    let predicates = attestation
+        .body
+        .transcript_commitments()
+        .filter_map(|commitment| match commitment {
+            TranscriptCommitment::Predicate(predicate) => Some(predicate.clone()),
+            _ => None,
         })
-    }
+        .collect();
+
+    Ok(PresentationOutput {
+        attestation,
+        server_name,
+        connection_info,
+        transcript,
+        extensions,
+        predicates,
+        binding,
+        tree_head,
+    })
 }
 
 /// Output of a verified [`Presentation`].
@@ -121,6 +401,21 @@ pub struct PresentationOutput {
     pub transcript: Option<PartialTranscript>,
     /// Extensions.
     pub extensions: Vec<Extension>,
+    /// Predicates which were proven over the transcript.
+    pub predicates: Vec<Predicate>,
+    /// Challenge the presentation is bound to, verified with the holder key
+    /// of the attestation.
+    ///
+    /// The Verifier must check that it is the challenge they issued, and that
+    /// it is not expired.
+    pub binding: Option<Challenge>,
+    /// Head of the transparency log which includes the attestation, signed by
+    /// its Notary.
+    ///
+    /// The Verifier should check that it is consistent with the heads of the
+    /// log they have seen, see
+    /// [`ConsistencyProof`](crate::transparency::ConsistencyProof).
+    pub tree_head: Option<TreeHead>,
 }
 
 /// Builder for [`Presentation`].
@@ -129,6 +424,8 @@ pub struct PresentationBuilder<'a> {
     attestation: &'a Attestation,
     identity_proof: Option<ServerIdentityProof>,
     transcript_proof: Option<TranscriptProof>,
+    binding: Option<(Challenge, &'a dyn Signer)>,
+    transparency_proof: Option<TransparencyProof>,
 }
 
 impl<'a> PresentationBuilder<'a> {
@@ -138,6 +435,8 @@ impl<'a> PresentationBuilder<'a> {
             attestation,
             identity_proof: None,
             transcript_proof: None,
+            binding: None,
+            transparency_proof: None,
         }
     }
 
@@ -153,14 +452,58 @@ impl<'a> PresentationBuilder<'a> {
         self
     }
 
+    /// Includes a proof that the attestation is in the transparency log of its
+    /// Notary.
+    pub fn transparency_proof(&mut self, proof: TransparencyProof) -> &mut Self {
+        self.transparency_proof = Some(proof);
+        self
+    }
+
+    /// Binds the presentation to a challenge of the Verifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `challenge` - Challenge issued by the Verifier.
+    /// * `signer` - Signer of the holder key committed in the attestation.
+    pub fn bind(&mut self, challenge: Challenge, signer: &'a dyn Signer) -> &mut Self {
+        self.binding = Some((challenge, signer));
+        self
+    }
+
     /// Builds the presentation.
     pub fn build(self) -> Result<Presentation, PresentationBuilderError> {
         let attestation = AttestationProof::new(self.provider, self.attestation)?;
 
+        let binding = self
+            .binding
+            .map(|(challenge, signer)| {
+                if self.attestation.body.holder_key() != Some(&signer.verifying_key()) {
+                    return Err(PresentationBuilderError {
+                        kind: BuilderErrorKind::Binding,
+                        source: Some("signer does not match the holder key".into()),
+                    });
+                }
+
+                HolderBinding::new(
+                    signer,
+                    challenge,
+                    &self.attestation.header,
+                    self.identity_proof.as_ref(),
+                    self.transcript_proof.as_ref(),
+                )
+                .map_err(|e| PresentationBuilderError {
+                    kind: BuilderErrorKind::Binding,
+                    source: Some(Box::new(e)),
+                })
+            })
+            .transpose()?;
+
         Ok(Presentation {
             attestation,
             identity: self.identity_proof,
             transcript: self.transcript_proof,
+            binding,
+            transparency: self.transparency_proof,
         })
     }
 }
@@ -175,6 +518,7 @@ pub struct PresentationBuilderError {
 #[derive(Debug)]
 enum BuilderErrorKind {
     Attestation,
+    Binding,
 }
 
 impl fmt::Display for PresentationBuilderError {
@@ -183,6 +527,7 @@ impl fmt::Display for PresentationBuilderError {
 
         match self.kind {
             BuilderErrorKind::Attestation => f.write_str("attestation error")?,
+            BuilderErrorKind::Binding => f.write_str("binding error")?,
         }
 
         if let Some(source) = &self.source {
@@ -209,9 +554,33 @@ pub struct PresentationError {
     source: Option<Box<dyn std::error::Error + Send + Sync>>,
 }
 
+impl PresentationError {
+    /// Returns the policy error if the presentation violates the
+    /// verification policy.
+    pub fn policy_error(&self) -> Option<&PolicyError> {
+        self.source.as_ref()?.downcast_ref()
+    }
+
+    fn new<E>(kind: ErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self {
+            kind,
+            source: Some(source.into()),
+        }
+    }
+}
+
 #[derive(Debug)]
 enum ErrorKind {
     Attestation,
+    Policy,
+    Binding,
+    Bundle,
+    Expired,
+    Revoked,
+    Transparency,
     Identity,
     Transcript,
 }
@@ -222,6 +591,12 @@ impl fmt::Display for PresentationError {
 
         match self.kind {
             ErrorKind::Attestation => f.write_str("attestation error")?,
+            ErrorKind::Policy => f.write_str("verification policy error")?,
+            ErrorKind::Binding => f.write_str("binding error")?,
+            ErrorKind::Bundle => f.write_str("bundle error")?,
+            ErrorKind::Expired => f.write_str("attestation expired")?,
+            ErrorKind::Revoked => f.write_str("attestation revoked")?,
+            ErrorKind::Transparency => f.write_str("transparency error")?,
             ErrorKind::Identity => f.write_str("server identity error")?,
             ErrorKind::Transcript => f.write_str("transcript error")?,
         }
@@ -260,3 +635,272 @@ impl From<TranscriptProofError> for PresentationError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tlsn_data_fixtures::http::{request::GET_WITH_HEADER, response::OK_JSON};
+
+    use super::*;
+    use crate::{
+        fixtures::{
+            attestation_fixture, encoder_secret, encoding_provider, request_fixture,
+            ConnectionFixture, RequestFixture,
+        },
+        hash::{Blake3, HashAlgId},
+        signing::{Ed25519Signer, Secp256k1Signer, SignatureAlgId},
+        transcript::{
+            encoding::EncodingCommitment, Transcript, TranscriptProofBuilder, TranscriptSecret,
+        },
+        transparency::TransparencyLog,
+    };
+
+    const ALGS: [SignatureAlgId; 3] = [
+        SignatureAlgId::SECP256K1,
+        SignatureAlgId::SECP256R1,
+        SignatureAlgId::ED25519,
+    ];
+
+    /// Returns attestations from notaries with different keys.
+    fn attestations(connection: ConnectionFixture) -> Vec<Attestation> {
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        let RequestFixture { request, .. } = request_fixture(
+            transcript,
+            encoding_provider(GET_WITH_HEADER, OK_JSON),
+            connection.clone(),
+            Blake3::default(),
+            Vec::new(),
+        );
+
+        ALGS.iter()
+            .map(|alg| {
+                let mut request = request.clone();
+                request.signature_alg = *alg;
+                attestation_fixture(request, connection.clone(), *alg, &[])
+            })
+            .collect()
+    }
+
+    fn connection() -> ConnectionFixture {
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        ConnectionFixture::tlsnotary(transcript.length())
+    }
+
+    /// Returns an attestation committing to the key of the holder.
+    fn holder_attestation(holder: &dyn Signer) -> Attestation {
+        let connection = connection();
+        let RequestFixture { mut request, .. } = request_fixture(
+            Transcript::new(GET_WITH_HEADER, OK_JSON),
+            encoding_provider(GET_WITH_HEADER, OK_JSON),
+            connection.clone(),
+            Blake3::default(),
+            Vec::new(),
+        );
+        request.holder_key = Some(holder.verifying_key());
+
+        attestation_fixture(request, connection, SignatureAlgId::SECP256K1, &[])
+    }
+
+    #[test]
+    fn test_holder_binding() {
+        let provider = CryptoProvider::default();
+        let holder = Ed25519Signer::new(&[7u8; 32]).unwrap();
+        let attestation = holder_attestation(&holder);
+        assert_eq!(attestation.body.holder_key(), Some(&holder.verifying_key()));
+
+        let challenge = Challenge::new("verifier.example", 1700000000);
+
+        let mut builder = attestation.presentation_builder(&provider);
+        builder.bind(challenge.clone(), &holder);
+        let output = builder.build().unwrap().verify(&provider).unwrap();
+
+        assert_eq!(output.binding, Some(challenge));
+    }
+
+    #[test]
+    fn test_holder_binding_wrong_key() {
+        let provider = CryptoProvider::default();
+        let holder = Ed25519Signer::new(&[7u8; 32]).unwrap();
+        let other = Ed25519Signer::new(&[8u8; 32]).unwrap();
+        let challenge = Challenge::new("verifier.example", 1700000000);
+
+        // The signer must be the holder.
+        let attestation = holder_attestation(&holder);
+        let mut builder = attestation.presentation_builder(&provider);
+        builder.bind(challenge.clone(), &other);
+        let err = builder.build().unwrap_err();
+        assert!(matches!(err.kind, BuilderErrorKind::Binding));
+
+        // The attestation must commit to a holder key.
+        let attestation = attestations(connection()).remove(0);
+        let mut builder = attestation.presentation_builder(&provider);
+        builder.bind(challenge, &holder);
+        let err = builder.build().unwrap_err();
+        assert!(matches!(err.kind, BuilderErrorKind::Binding));
+    }
+
+    #[test]
+    fn test_holder_binding_replay() {
+        let provider = CryptoProvider::default();
+        let holder = Ed25519Signer::new(&[7u8; 32]).unwrap();
+        let challenge = Challenge::new("verifier.example", 1700000000);
+
+        let attestation = holder_attestation(&holder);
+        let mut builder = attestation.presentation_builder(&provider);
+        builder.bind(challenge, &holder);
+        let bound = builder.build().unwrap();
+
+        // The binding can not be moved to a presentation of another
+        // attestation.
+        let other = holder_attestation(&holder);
+        let mut presentation = other.presentation_builder(&provider).build().unwrap();
+        presentation.binding = bound.binding.clone();
+
+        let err = presentation.verify(&provider).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Binding));
+
+        // Nor to an attestation without a holder key.
+        let other = attestations(connection()).remove(0);
+        let mut presentation = other.presentation_builder(&provider).build().unwrap();
+        presentation.binding = bound.binding;
+
+        let err = presentation.verify(&provider).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Binding));
+    }
+
+    #[test]
+    fn test_narrow() {
+        let provider = CryptoProvider::default();
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        let connection = connection();
+        let RequestFixture {
+            request,
+            encoding_tree,
+        } = request_fixture(
+            transcript.clone(),
+            encoding_provider(GET_WITH_HEADER, OK_JSON),
+            connection.clone(),
+            Blake3::default(),
+            Vec::new(),
+        );
+
+        let commitment = TranscriptCommitment::Encoding(EncodingCommitment {
+            root: encoding_tree.root(),
+            secret: encoder_secret(),
+        });
+        let attestation = attestation_fixture(
+            request,
+            connection,
+            SignatureAlgId::SECP256K1,
+            &[commitment],
+        );
+
+        let secrets = [TranscriptSecret::Encoding(encoding_tree)];
+        let mut builder = TranscriptProofBuilder::new(&transcript, &secrets);
+        builder
+            .reveal_sent(&(0..GET_WITH_HEADER.len()))
+            .unwrap()
+            .reveal_recv(&(0..OK_JSON.len()))
+            .unwrap();
+        let transcript_proof = builder.build().unwrap();
+
+        let mut builder = attestation.presentation_builder(&provider);
+        builder.transcript_proof(transcript_proof);
+        let presentation = builder.build().unwrap();
+
+        // The whole sent data is committed at once, so it can only be revealed
+        // entirely.
+        let narrowed = presentation
+            .narrow(&provider, &(0..GET_WITH_HEADER.len()), &(0..0))
+            .unwrap();
+        let partial_transcript = narrowed.verify(&provider).unwrap().transcript.unwrap();
+        assert_eq!(
+            partial_transcript.sent_authed(),
+            &Idx::new(0..GET_WITH_HEADER.len())
+        );
+        assert!(partial_transcript.received_authed().is_empty());
+
+        let err = presentation
+            .narrow(&provider, &(0..10), &(0..0))
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Transcript));
+    }
+
+    #[test]
+    fn test_bundle() {
+        let provider = CryptoProvider::default();
+        let holder = Ed25519Signer::new(&[7u8; 32]).unwrap();
+        let challenge = Challenge::new("verifier.example", 1700000000);
+        let attestations = [holder_attestation(&holder), holder_attestation(&holder)];
+
+        let bound = |attestation: &Attestation| {
+            let mut builder = attestation.presentation_builder(&provider);
+            builder.bind(challenge.clone(), &holder);
+            builder.build().unwrap()
+        };
+
+        // The first attestation is presented twice.
+        let mut bundle = PresentationBundle::new([
+            bound(&attestations[0]),
+            bound(&attestations[0]),
+            bound(&attestations[1]),
+        ]);
+        bundle.require_same_holder();
+        assert_eq!(bundle.len(), 3);
+        assert_eq!(bundle.verifying_keys().count(), 2);
+
+        let output = bundle.verify(&provider).unwrap();
+        assert_eq!(output.presentations.len(), 3);
+        assert_eq!(output.holder_key, Some(holder.verifying_key()));
+        assert_eq!(output.binding, Some(challenge.clone()));
+        assert_eq!(output.server_name, None);
+
+        // Presentations which are not bound to the same challenge.
+        let mut other = attestations[1].presentation_builder(&provider);
+        other.bind(Challenge::new("verifier.example", 1700000000), &holder);
+        let mut bundle = PresentationBundle::new([bound(&attestations[0])]);
+        bundle.push(other.build().unwrap()).require_same_holder();
+
+        let err = bundle.verify(&provider).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Bundle));
+
+        // The server name is not disclosed.
+        let mut bundle = PresentationBundle::new([bound(&attestations[0])]);
+        bundle.require_same_server_name();
+
+        let err = bundle.verify(&provider).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Bundle));
+
+        let err = PresentationBundle::new([]).verify(&provider).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Bundle));
+    }
+
+    #[test]
+    fn test_transparency_proof() {
+        let provider = CryptoProvider::default();
+        let attestations = attestations(connection());
+        // Same key as the secp256k1 attestation fixture.
+        let signer = Secp256k1Signer::new(&[42u8; 32]).unwrap();
+
+        let mut log = TransparencyLog::new(HashAlgId::SHA256);
+        for attestation in attestations.iter().rev() {
+            log.append(&provider, &attestation.header).unwrap();
+        }
+
+        let head = log.tree_head(&provider, 1700000000).unwrap();
+        let proof = TransparencyProof {
+            tree_head: head.clone().sign(&signer).unwrap(),
+            inclusion: log.inclusion_proof(&provider, 2, 3).unwrap(),
+        };
+
+        let mut builder = attestations[0].presentation_builder(&provider);
+        builder.transparency_proof(proof.clone());
+        let output = builder.build().unwrap().verify(&provider).unwrap();
+        assert_eq!(output.tree_head, Some(head));
+
+        // The proof does not apply to the attestation of another Notary.
+        let mut builder = attestations[1].presentation_builder(&provider);
+        builder.transparency_proof(proof);
+        let err = builder.build().unwrap().verify(&provider).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Transparency));
+    }
+}
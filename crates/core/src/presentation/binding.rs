@@ -0,0 +1,126 @@
+use rand::{rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attestation::Header,
+    connection::ServerIdentityProof,
+    serialize::CanonicalSerialize,
+    signing::{Signature, SignatureError, Signer, VerifyingKey},
+    transcript::TranscriptProof,
+    CryptoProvider,
+};
+
+/// Domain separator of the message signed by the holder.
+const DOMAIN: &[u8] = b"tlsn/presentation/binding";
+
+/// A challenge issued by a Verifier, which a presentation can be bound to.
+///
+/// The Verifier is expected to issue a fresh challenge for every
+/// presentation it requests, and to accept each challenge only once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Challenge {
+    /// Random nonce chosen by the Verifier.
+    pub nonce: Vec<u8>,
+    /// Identifier of the Verifier the presentation is intended for.
+    pub audience: String,
+    /// Expiry of the challenge, in seconds since the UNIX epoch.
+    pub expires_at: u64,
+}
+
+impl Challenge {
+    /// Creates a new challenge with a random nonce.
+    ///
+    /// # Arguments
+    ///
+    /// * `audience` - Identifier of the Verifier.
+    /// * `expires_at` - Expiry of the challenge, in seconds since the UNIX
+    ///   epoch.
+    pub fn new(audience: impl Into<String>, expires_at: u64) -> Self {
+        Self {
+            nonce: rng().random::<[u8; 32]>().to_vec(),
+            audience: audience.into(),
+            expires_at,
+        }
+    }
+
+    /// Returns `true` if the challenge is expired at the given time.
+    pub fn is_expired(&self, now: u64) -> bool {
+        now > self.expires_at
+    }
+}
+
+/// Binding of a presentation to a [`Challenge`], signed by the holder key
+/// committed in the attestation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct HolderBinding {
+    challenge: Challenge,
+    signature: Signature,
+}
+
+/// Message signed by the holder.
+///
+/// Covers the attestation and the disclosed data, so that the binding can not
+/// be moved to another presentation.
+#[derive(Serialize)]
+struct Message<'a> {
+    header: &'a Header,
+    challenge: &'a Challenge,
+    identity: Option<&'a ServerIdentityProof>,
+    transcript: Option<&'a TranscriptProof>,
+}
+
+impl Message<'_> {
+    fn to_bytes(&self) -> Vec<u8> {
+        [DOMAIN, &CanonicalSerialize::serialize(self)].concat()
+    }
+}
+
+impl HolderBinding {
+    /// Signs the binding of a presentation to the challenge.
+    pub(crate) fn new(
+        signer: &dyn Signer,
+        challenge: Challenge,
+        header: &Header,
+        identity: Option<&ServerIdentityProof>,
+        transcript: Option<&TranscriptProof>,
+    ) -> Result<Self, SignatureError> {
+        let msg = Message {
+            header,
+            challenge: &challenge,
+            identity,
+            transcript,
+        }
+        .to_bytes();
+
+        let signature = signer.sign(&msg)?;
+
+        Ok(Self {
+            challenge,
+            signature,
+        })
+    }
+
+    /// Verifies the binding against the holder key, returning the challenge.
+    pub(crate) fn verify(
+        self,
+        provider: &CryptoProvider,
+        holder_key: &VerifyingKey,
+        header: &Header,
+        identity: Option<&ServerIdentityProof>,
+        transcript: Option<&TranscriptProof>,
+    ) -> Result<Challenge, Box<dyn std::error::Error + Send + Sync>> {
+        let verifier = provider.signature.get(&self.signature.alg)?;
+
+        let msg = Message {
+            header,
+            challenge: &self.challenge,
+            identity,
+            transcript,
+        }
+        .to_bytes();
+
+        verifier.verify(holder_key, &msg, &self.signature.data)?;
+
+        Ok(self.challenge)
+    }
+}
@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attestation::AttestationProof,
+    connection::{ServerIdentityProof, ServerName},
+    presentation::{
+        check_expiry, verify_disclosures, Challenge, ErrorKind, HolderBinding, Presentation,
+        PresentationError, PresentationOutput,
+    },
+    signing::VerifyingKey,
+    transcript::TranscriptProof,
+    transparency::TransparencyProof,
+    CryptoProvider,
+};
+
+/// A bundle of presentations, e.g. of several TLS sessions.
+///
+/// Attestations which are shared by multiple presentations are only included,
+/// and verified, once.
+///
+/// See the [module level documentation](crate::presentation) for more
+/// information.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresentationBundle {
+    /// Attestations of the presentations, with unique headers.
+    attestations: Vec<AttestationProof>,
+    presentations: Vec<BundledPresentation>,
+    same_holder: bool,
+    same_server_name: bool,
+}
+
+/// A presentation in a bundle, referring to the attestations of the bundle by
+/// index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BundledPresentation {
+    attestation: usize,
+    identity: Option<ServerIdentityProof>,
+    transcript: Option<TranscriptProof>,
+    binding: Option<HolderBinding>,
+    transparency: Option<TransparencyProof>,
+}
+
+impl PresentationBundle {
+    /// Creates a new bundle of the given presentations.
+    pub fn new(presentations: impl IntoIterator<Item = Presentation>) -> Self {
+        let mut bundle = Self {
+            attestations: Vec::new(),
+            presentations: Vec::new(),
+            same_holder: false,
+            same_server_name: false,
+        };
+
+        for presentation in presentations {
+            bundle.push(presentation);
+        }
+
+        bundle
+    }
+
+    /// Adds a presentation to the bundle.
+    pub fn push(&mut self, presentation: Presentation) -> &mut Self {
+        let Presentation {
+            attestation,
+            identity,
+            transcript,
+            binding,
+            transparency,
+        } = presentation;
+
+        let attestation = self.insert_attestation(attestation);
+
+        self.presentations.push(BundledPresentation {
+            attestation,
+            identity,
+            transcript,
+            binding,
+            transparency,
+        });
+        self
+    }
+
+    /// Asserts that all presentations are bound to the same challenge by the
+    /// same holder.
+    pub fn require_same_holder(&mut self) -> &mut Self {
+        self.same_holder = true;
+        self
+    }
+
+    /// Asserts that all presentations disclose the same server name.
+    pub fn require_same_server_name(&mut self) -> &mut Self {
+        self.same_server_name = true;
+        self
+    }
+
+    /// Returns the number of presentations in the bundle.
+    pub fn len(&self) -> usize {
+        self.presentations.len()
+    }
+
+    /// Returns `true` if the bundle is empty.
+    pub fn is_empty(&self) -> bool {
+        self.presentations.is_empty()
+    }
+
+    /// Returns the verifying keys of the unique attestations in the bundle.
+    pub fn verifying_keys(&self) -> impl Iterator<Item = &VerifyingKey> {
+        self.attestations
+            .iter()
+            .map(|attestation| attestation.verifying_key())
+    }
+
+    /// Verifies all presentations in the bundle.
+    ///
+    /// Fails if any presentation is invalid, if any attestation is expired, or
+    /// if an assertion of the bundle does not hold. This does not check whether
+    /// the Notaries are trusted.
+    pub fn verify(self, provider: &CryptoProvider) -> Result<BundleOutput, PresentationError> {
+        let Self {
+            attestations,
+            presentations,
+            same_holder,
+            same_server_name,
+        } = self;
+
+        if presentations.is_empty() {
+            return Err(PresentationError::new(
+                ErrorKind::Bundle,
+                "bundle does not contain any presentations",
+            ));
+        }
+
+        let attestations = attestations
+            .into_iter()
+            .map(|proof| {
+                let attestation = proof.verify(provider)?;
+                check_expiry(&attestation)?;
+                Ok(attestation)
+            })
+            .collect::<Result<Vec<_>, PresentationError>>()?;
+
+        let get = |idx: usize| {
+            attestations.get(idx).cloned().ok_or_else(|| {
+                PresentationError::new(ErrorKind::Bundle, "attestation index is out of bounds")
+            })
+        };
+
+        let mut outputs = Vec::with_capacity(presentations.len());
+        for presentation in presentations {
+            outputs.push(verify_disclosures(
+                provider,
+                get(presentation.attestation)?,
+                presentation.identity,
+                presentation.transcript,
+                presentation.binding,
+                presentation.transparency,
+            )?);
+        }
+
+        let holder = shared(&outputs, |output| {
+            Some((
+                output.attestation.body.holder_key()?.clone(),
+                output.binding.clone()?,
+            ))
+        });
+
+        if same_holder && holder.is_none() {
+            return Err(PresentationError::new(
+                ErrorKind::Bundle,
+                "presentations are not bound to the same challenge by the same holder",
+            ));
+        }
+
+        let server_name = shared(&outputs, |output| output.server_name.clone());
+
+        if same_server_name && server_name.is_none() {
+            return Err(PresentationError::new(
+                ErrorKind::Bundle,
+                "presentations do not disclose the same server name",
+            ));
+        }
+
+        let (holder_key, binding) = holder.unzip();
+
+        Ok(BundleOutput {
+            presentations: outputs,
+            holder_key,
+            binding,
+            server_name,
+        })
+    }
+
+    /// Inserts an attestation unless one with the same header is already
+    /// present, returning its index.
+    fn insert_attestation(&mut self, attestation: AttestationProof) -> usize {
+        match self
+            .attestations
+            .iter()
+            .position(|other| other.header() == attestation.header())
+        {
+            Some(idx) => idx,
+            None => {
+                self.attestations.push(attestation);
+                self.attestations.len() - 1
+            }
+        }
+    }
+}
+
+/// Returns the value if it is present and the same for all outputs.
+fn shared<T: PartialEq>(
+    outputs: &[PresentationOutput],
+    f: impl Fn(&PresentationOutput) -> Option<T>,
+) -> Option<T> {
+    let mut values = outputs.iter().map(f);
+    let first = values.next()??;
+
+    values
+        .all(|value| value.as_ref() == Some(&first))
+        .then_some(first)
+}
+
+/// Output of a verified [`PresentationBundle`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct BundleOutput {
+    /// Outputs of the presentations, in the order of the bundle.
+    pub presentations: Vec<PresentationOutput>,
+    /// Holder key, if all presentations are bound by the same holder to the
+    /// same challenge.
+    pub holder_key: Option<VerifyingKey>,
+    /// Challenge which all presentations are bound to.
+    pub binding: Option<Challenge>,
+    /// Server name, if all presentations disclose the same server name.
+    pub server_name: Option<ServerName>,
+}
@@ -19,10 +19,10 @@ mod config;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    attestation::{Attestation, Extension},
+    attestation::{Attestation, AttestationEncoding, Extension, VERSION},
     connection::ServerCertCommitment,
     hash::HashAlgId,
-    signing::SignatureAlgId,
+    signing::{SignatureAlgId, VerifyingKey},
 };
 
 pub use builder::{RequestBuilder, RequestBuilderError};
@@ -33,8 +33,10 @@ pub use config::{RequestConfig, RequestConfigBuilder, RequestConfigBuilderError}
 pub struct Request {
     pub(crate) signature_alg: SignatureAlgId,
     pub(crate) hash_alg: HashAlgId,
+    pub(crate) encoding: AttestationEncoding,
     pub(crate) server_cert_commitment: ServerCertCommitment,
     pub(crate) extensions: Vec<Extension>,
+    pub(crate) holder_key: Option<VerifyingKey>,
 }
 
 impl Request {
@@ -45,6 +47,13 @@ impl Request {
 
     /// Validates the content of the attestation against this request.
     pub fn validate(&self, attestation: &Attestation) -> Result<(), InconsistentAttestation> {
+        if attestation.header.version != VERSION {
+            return Err(InconsistentAttestation(format!(
+                "version: expected {:?}, got {:?}",
+                VERSION, attestation.header.version
+            )));
+        }
+
         if attestation.signature.alg != self.signature_alg {
             return Err(InconsistentAttestation(format!(
                 "signature algorithm: expected {:?}, got {:?}",
@@ -59,12 +68,25 @@ impl Request {
             )));
         }
 
+        if attestation.encoding != self.encoding {
+            return Err(InconsistentAttestation(format!(
+                "encoding: expected {:?}, got {:?}",
+                self.encoding, attestation.encoding
+            )));
+        }
+
         if attestation.body.cert_commitment() != &self.server_cert_commitment {
             return Err(InconsistentAttestation(
                 "server certificate commitment does not match".to_string(),
             ));
         }
 
+        if attestation.body.holder_key() != self.holder_key.as_ref() {
+            return Err(InconsistentAttestation(
+                "holder key does not match".to_string(),
+            ));
+        }
+
         // TODO: improve the O(M*N) complexity of this check.
         for extension in &self.extensions {
             if !attestation.body.extensions().any(|e| e == extension) {
@@ -88,6 +110,7 @@ mod test {
     use tlsn_data_fixtures::http::{request::GET_WITH_HEADER, response::OK_JSON};
 
     use crate::{
+        attestation::Version,
         connection::{ServerCertOpening, TranscriptLength},
         fixtures::{
             attestation_fixture, encoding_provider, request_fixture, ConnectionFixture,
@@ -118,6 +141,28 @@ mod test {
         assert!(request.validate(&attestation).is_ok())
     }
 
+    #[test]
+    fn test_wrong_version() {
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+        let connection = ConnectionFixture::tlsnotary(transcript.length());
+
+        let RequestFixture { request, .. } = request_fixture(
+            transcript,
+            encoding_provider(GET_WITH_HEADER, OK_JSON),
+            connection.clone(),
+            Blake3::default(),
+            Vec::new(),
+        );
+
+        let mut attestation =
+            attestation_fixture(request.clone(), connection, SignatureAlgId::SECP256K1, &[]);
+
+        attestation.header.version = Version(0);
+
+        let res = request.validate(&attestation);
+        assert!(res.is_err())
+    }
+
     #[test]
     fn test_wrong_signature_alg() {
         let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
@@ -97,8 +97,10 @@ impl<'a> RequestBuilder<'a> {
         let request = Request {
             signature_alg,
             hash_alg,
+            encoding: config.encoding(),
             server_cert_commitment,
             extensions,
+            holder_key: config.holder_key().cloned(),
         };
 
         let secrets = Secrets {
@@ -1,5 +1,7 @@
 use crate::{
-    attestation::Extension, hash::HashAlgId, signing::SignatureAlgId,
+    attestation::{AttestationEncoding, Extension},
+    hash::HashAlgId,
+    signing::{SignatureAlgId, VerifyingKey},
     transcript::TranscriptCommitConfig,
 };
 
@@ -8,7 +10,9 @@ use crate::{
 pub struct RequestConfig {
     signature_alg: SignatureAlgId,
     hash_alg: HashAlgId,
+    encoding: AttestationEncoding,
     extensions: Vec<Extension>,
+    holder_key: Option<VerifyingKey>,
     transcript_commit: Option<TranscriptCommitConfig>,
 }
 
@@ -34,11 +38,21 @@ impl RequestConfig {
         &self.hash_alg
     }
 
+    /// Returns the attestation encoding.
+    pub fn encoding(&self) -> AttestationEncoding {
+        self.encoding
+    }
+
     /// Returns the extensions.
     pub fn extensions(&self) -> &[Extension] {
         &self.extensions
     }
 
+    /// Returns the holder key.
+    pub fn holder_key(&self) -> Option<&VerifyingKey> {
+        self.holder_key.as_ref()
+    }
+
     /// Returns the transcript commitment configuration.
     pub fn transcript_commit(&self) -> Option<&TranscriptCommitConfig> {
         self.transcript_commit.as_ref()
@@ -50,7 +64,9 @@ impl RequestConfig {
 pub struct RequestConfigBuilder {
     signature_alg: SignatureAlgId,
     hash_alg: HashAlgId,
+    encoding: AttestationEncoding,
     extensions: Vec<Extension>,
+    holder_key: Option<VerifyingKey>,
     transcript_commit: Option<TranscriptCommitConfig>,
 }
 
@@ -59,7 +75,9 @@ impl Default for RequestConfigBuilder {
         Self {
             signature_alg: SignatureAlgId::SECP256K1,
             hash_alg: HashAlgId::BLAKE3,
+            encoding: AttestationEncoding::default(),
             extensions: Vec::new(),
+            holder_key: None,
             transcript_commit: None,
         }
     }
@@ -78,12 +96,31 @@ impl RequestConfigBuilder {
         self
     }
 
+    /// Sets the attestation encoding.
+    ///
+    /// [`AttestationEncoding::Evm`] requires the [`HashAlgId::KECCAK256`] hash
+    /// algorithm and the [`SignatureAlgId::SECP256K1ETH`] signature algorithm.
+    pub fn encoding(&mut self, encoding: AttestationEncoding) -> &mut Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Adds an extension to the request.
     pub fn extension(&mut self, extension: Extension) -> &mut Self {
         self.extensions.push(extension);
         self
     }
 
+    /// Sets the holder key.
+    ///
+    /// The key is included in the attestation, and presentations can then be
+    /// bound to a challenge of a Verifier with a signature of this key, see
+    /// [`PresentationBuilder::bind`](crate::presentation::PresentationBuilder::bind).
+    pub fn holder_key(&mut self, key: VerifyingKey) -> &mut Self {
+        self.holder_key = Some(key);
+        self
+    }
+
     /// Sets the transcript commitment configuration.
     pub fn transcript_commit(&mut self, transcript_commit: TranscriptCommitConfig) -> &mut Self {
         self.transcript_commit = Some(transcript_commit);
@@ -92,10 +129,16 @@ impl RequestConfigBuilder {
 
     /// Builds the config.
     pub fn build(self) -> Result<RequestConfig, RequestConfigBuilderError> {
+        self.encoding
+            .check_algs(&self.signature_alg, &self.hash_alg)
+            .map_err(|message| RequestConfigBuilderError { message })?;
+
         Ok(RequestConfig {
             signature_alg: self.signature_alg,
             hash_alg: self.hash_alg,
+            encoding: self.encoding,
             extensions: self.extensions,
+            holder_key: self.holder_key,
             transcript_commit: self.transcript_commit,
         })
     }
@@ -15,6 +15,10 @@ impl KeyAlgId {
     pub const K256: Self = Self(1);
     /// NIST P-256 elliptic curve key algorithm.
     pub const P256: Self = Self(2);
+    /// Ed25519 key algorithm.
+    pub const ED25519: Self = Self(3);
+    /// BLS12-381 key algorithm, with public keys in G1.
+    pub const BLS12381: Self = Self(4);
 
     /// Creates a new key algorithm identifier.
     ///
@@ -42,6 +46,8 @@ impl std::fmt::Display for KeyAlgId {
         match *self {
             KeyAlgId::K256 => write!(f, "k256"),
             KeyAlgId::P256 => write!(f, "p256"),
+            KeyAlgId::ED25519 => write!(f, "ed25519"),
+            KeyAlgId::BLS12381 => write!(f, "bls12381"),
             _ => write!(f, "custom({:02x})", self.0),
         }
     }
@@ -61,6 +67,12 @@ impl SignatureAlgId {
     /// Uses secp256k1 with Keccak-256 hashing. The signature is a concatenation
     /// of `r || s || v` as defined in Solidity's ecrecover().
     pub const SECP256K1ETH: Self = Self(3);
+    /// Ed25519 signature algorithm.
+    pub const ED25519: Self = Self(4);
+    /// BLS12-381 signature algorithm.
+    ///
+    /// Uses the basic scheme with public keys in G1 and signatures in G2.
+    pub const BLS12381: Self = Self(5);
 
     /// Creates a new signature algorithm identifier.
     ///
@@ -89,6 +101,8 @@ impl std::fmt::Display for SignatureAlgId {
             SignatureAlgId::SECP256K1 => write!(f, "secp256k1"),
             SignatureAlgId::SECP256R1 => write!(f, "secp256r1"),
             SignatureAlgId::SECP256K1ETH => write!(f, "secp256k1eth"),
+            SignatureAlgId::ED25519 => write!(f, "ed25519"),
+            SignatureAlgId::BLS12381 => write!(f, "bls12381"),
             _ => write!(f, "custom({:02x})", self.0),
         }
     }
@@ -137,8 +151,22 @@ impl SignerProvider {
         Ok(self)
     }
 
+    /// Configures an ed25519 signer with the provided signing key.
+    pub fn set_ed25519(&mut self, key: &[u8]) -> Result<&mut Self, SignerError> {
+        self.set_signer(Box::new(Ed25519Signer::new(key)?));
+
+        Ok(self)
+    }
+
+    /// Configures a bls12381 signer with the provided signing key.
+    pub fn set_bls12381(&mut self, key: &[u8]) -> Result<&mut Self, SignerError> {
+        self.set_signer(Box::new(Bls12381Signer::new(key)?));
+
+        Ok(self)
+    }
+
     /// Returns a signer for the given algorithm.
-    pub(crate) fn get(
+    pub fn get(
         &self,
         alg: &SignatureAlgId,
     ) -> Result<&(dyn Signer + Send + Sync), UnknownSignatureAlgId> {
@@ -181,6 +209,8 @@ impl Default for SignatureVerifierProvider {
             SignatureAlgId::SECP256K1ETH,
             Box::new(Secp256k1EthVerifier) as _,
         );
+        verifiers.insert(SignatureAlgId::ED25519, Box::new(Ed25519Verifier) as _);
+        verifiers.insert(SignatureAlgId::BLS12381, Box::new(Bls12381Verifier) as _);
 
         Self { verifiers }
     }
@@ -504,6 +534,189 @@ mod secp256k1eth {
 
 pub use secp256k1eth::{Secp256k1EthSigner, Secp256k1EthVerifier};
 
+mod ed25519 {
+    use ed25519_dalek::{
+        Signature as Ed25519Signature, Signer as _, SigningKey, VerifyingKey as Ed25519Key,
+    };
+
+    use super::*;
+
+    /// ed25519 signer.
+    pub struct Ed25519Signer(SigningKey);
+
+    impl Ed25519Signer {
+        /// Creates a new ed25519 signer with the provided signing key.
+        ///
+        /// The signing key is the 32 byte seed as defined in RFC 8032.
+        pub fn new(key: &[u8]) -> Result<Self, SignerError> {
+            SigningKey::try_from(key)
+                .map(Self)
+                .map_err(|_| SignerError("invalid key".to_string()))
+        }
+    }
+
+    impl Signer for Ed25519Signer {
+        fn alg_id(&self) -> SignatureAlgId {
+            SignatureAlgId::ED25519
+        }
+
+        fn sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
+            let sig: Ed25519Signature = self.0.sign(msg);
+
+            Ok(Signature {
+                alg: SignatureAlgId::ED25519,
+                data: sig.to_bytes().to_vec(),
+            })
+        }
+
+        fn verifying_key(&self) -> VerifyingKey {
+            VerifyingKey {
+                alg: KeyAlgId::ED25519,
+                data: self.0.verifying_key().to_bytes().to_vec(),
+            }
+        }
+    }
+
+    /// ed25519 verifier.
+    ///
+    /// Uses strict verification, which rejects weak keys and malleable
+    /// signatures.
+    pub struct Ed25519Verifier;
+
+    impl SignatureVerifier for Ed25519Verifier {
+        fn alg_id(&self) -> SignatureAlgId {
+            SignatureAlgId::ED25519
+        }
+
+        fn verify(&self, key: &VerifyingKey, msg: &[u8], sig: &[u8]) -> Result<(), SignatureError> {
+            if key.alg != KeyAlgId::ED25519 {
+                return Err(SignatureError("key algorithm is not ed25519".to_string()));
+            }
+
+            let key = key
+                .data
+                .as_slice()
+                .try_into()
+                .ok()
+                .and_then(|key| Ed25519Key::from_bytes(key).ok())
+                .ok_or_else(|| SignatureError("invalid ed25519 key".to_string()))?;
+
+            let sig = Ed25519Signature::from_slice(sig)
+                .map_err(|_| SignatureError("invalid ed25519 signature".to_string()))?;
+
+            key.verify_strict(msg, &sig)
+                .map_err(|_| SignatureError("ed25519 signature verification failed".to_string()))?;
+
+            Ok(())
+        }
+    }
+}
+
+pub use ed25519::{Ed25519Signer, Ed25519Verifier};
+
+mod bls12381 {
+    use blst::{
+        min_pk::{PublicKey, SecretKey, Signature as Bls12381Signature},
+        BLST_ERROR,
+    };
+
+    use super::*;
+
+    /// Domain separation tag of the basic ciphersuite.
+    const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+    /// bls12381 signer.
+    pub struct Bls12381Signer(SecretKey);
+
+    impl Bls12381Signer {
+        /// Creates a new bls12381 signer with the provided signing key.
+        ///
+        /// The signing key is the 32 byte big-endian secret scalar.
+        pub fn new(key: &[u8]) -> Result<Self, SignerError> {
+            SecretKey::from_bytes(key)
+                .map(Self)
+                .map_err(|_| SignerError("invalid key".to_string()))
+        }
+
+        /// Derives a new bls12381 signer from the provided key material, using
+        /// `KeyGen` as defined in the BLS signature specification.
+        ///
+        /// # Arguments
+        ///
+        /// * `ikm` - Secret key material of at least 32 bytes.
+        pub fn from_seed(ikm: &[u8]) -> Result<Self, SignerError> {
+            SecretKey::key_gen(ikm, &[])
+                .map(Self)
+                .map_err(|_| SignerError("key material must be at least 32 bytes".to_string()))
+        }
+
+        /// Returns the signing key as the 32 byte big-endian secret scalar.
+        pub fn to_bytes(&self) -> [u8; 32] {
+            self.0.to_bytes()
+        }
+    }
+
+    impl Signer for Bls12381Signer {
+        fn alg_id(&self) -> SignatureAlgId {
+            SignatureAlgId::BLS12381
+        }
+
+        fn sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
+            let sig = self.0.sign(msg, DST, &[]);
+
+            Ok(Signature {
+                alg: SignatureAlgId::BLS12381,
+                data: sig.compress().to_vec(),
+            })
+        }
+
+        fn verifying_key(&self) -> VerifyingKey {
+            VerifyingKey {
+                alg: KeyAlgId::BLS12381,
+                data: self.0.sk_to_pk().compress().to_vec(),
+            }
+        }
+    }
+
+    /// bls12381 verifier.
+    pub struct Bls12381Verifier;
+
+    impl SignatureVerifier for Bls12381Verifier {
+        fn alg_id(&self) -> SignatureAlgId {
+            SignatureAlgId::BLS12381
+        }
+
+        fn verify(&self, key: &VerifyingKey, msg: &[u8], sig: &[u8]) -> Result<(), SignatureError> {
+            let key = public_key(key)?;
+
+            let sig = Bls12381Signature::sig_validate(sig, true)
+                .map_err(|_| SignatureError("invalid bls12381 signature".to_string()))?;
+
+            match sig.verify(true, msg, DST, &[], &key, true) {
+                BLST_ERROR::BLST_SUCCESS => Ok(()),
+                _ => Err(SignatureError(
+                    "bls12381 signature verification failed".to_string(),
+                )),
+            }
+        }
+    }
+
+    fn public_key(key: &VerifyingKey) -> Result<PublicKey, SignatureError> {
+        if key.alg != KeyAlgId::BLS12381 {
+            return Err(SignatureError("key algorithm is not bls12381".to_string()));
+        }
+
+        PublicKey::key_validate(&key.data)
+            .map_err(|_| SignatureError("invalid bls12381 key".to_string()))
+    }
+}
+
+pub use bls12381::{Bls12381Signer, Bls12381Verifier};
+
+pub mod remote;
+
+pub use remote::{RemoteSigner, RemoteSignerEndpoint};
+
 #[cfg(test)]
 mod test {
     use alloy_primitives::utils::eip191_message;
@@ -544,10 +757,30 @@ mod test {
         )
     }
 
+    #[fixture]
+    #[once]
+    fn ed25519_pair() -> (Box<dyn Signer>, Box<dyn SignatureVerifier>) {
+        (
+            Box::new(Ed25519Signer::new(&rand::random::<[u8; 32]>()).unwrap()),
+            Box::new(Ed25519Verifier {}),
+        )
+    }
+
+    #[fixture]
+    #[once]
+    fn bls12381_pair() -> (Box<dyn Signer>, Box<dyn SignatureVerifier>) {
+        (
+            Box::new(Bls12381Signer::from_seed(&rand::random::<[u8; 32]>()).unwrap()),
+            Box::new(Bls12381Verifier {}),
+        )
+    }
+
     #[rstest]
     #[case::r1(secp256r1_pair(), SignatureAlgId::SECP256R1)]
     #[case::k1(secp256k1_pair(), SignatureAlgId::SECP256K1)]
     #[case::k1eth(secp256k1eth_pair(), SignatureAlgId::SECP256K1ETH)]
+    #[case::ed25519(ed25519_pair(), SignatureAlgId::ED25519)]
+    #[case::bls12381(bls12381_pair(), SignatureAlgId::BLS12381)]
     fn test_success(
         #[case] pair: (Box<dyn Signer>, Box<dyn SignatureVerifier>),
         #[case] alg: SignatureAlgId,
@@ -567,6 +800,8 @@ mod test {
     #[rstest]
     #[case::r1(secp256r1_pair())]
     #[case::k1eth(secp256k1eth_pair())]
+    #[case::ed25519(ed25519_pair())]
+    #[case::bls12381(bls12381_pair())]
     fn test_wrong_signer(#[case] pair: (Box<dyn Signer>, Box<dyn SignatureVerifier>)) {
         let (signer, _) = pair;
 
@@ -583,9 +818,13 @@ mod test {
     #[case::corrupted_signature_r1(secp256r1_pair(), true, false)]
     #[case::corrupted_signature_k1(secp256k1_pair(), true, false)]
     #[case::corrupted_signature_k1eth(secp256k1eth_pair(), true, false)]
+    #[case::corrupted_signature_ed25519(ed25519_pair(), true, false)]
+    #[case::corrupted_signature_bls12381(bls12381_pair(), true, false)]
     #[case::wrong_signature_r1(secp256r1_pair(), false, true)]
     #[case::wrong_signature_k1(secp256k1_pair(), false, true)]
     #[case::wrong_signature_k1eth(secp256k1eth_pair(), false, true)]
+    #[case::wrong_signature_ed25519(ed25519_pair(), false, true)]
+    #[case::wrong_signature_bls12381(bls12381_pair(), false, true)]
     fn test_failure(
         #[case] pair: (Box<dyn Signer>, Box<dyn SignatureVerifier>),
         #[case] corrupted_signature: bool,
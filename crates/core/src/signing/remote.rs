@@ -0,0 +1,406 @@
+//! Remote signer.
+//!
+//! A [`RemoteSigner`] delegates signing to an external signer process, so that
+//! the signing key can be kept in a KMS or HSM instead of the Notary's memory.
+//!
+//! # Protocol
+//!
+//! The Notary connects to the signer over TCP or a Unix domain socket. Each
+//! connection carries a single request followed by a single response. Integers
+//! are big-endian.
+//!
+//! Request:
+//!
+//! | Field     | Size      | Description                                      |
+//! |-----------|-----------|--------------------------------------------------|
+//! | `op`      | 1         | `0x01` to get the verifying key, `0x02` to sign  |
+//! | `alg`     | 1         | [`SignatureAlgId`] of the requested algorithm    |
+//! | `len`     | 4         | Length of `payload`                              |
+//! | `payload` | `len`     | Empty for `0x01`, the message to sign for `0x02` |
+//!
+//! Response:
+//!
+//! | Field     | Size      | Description                                      |
+//! |-----------|-----------|--------------------------------------------------|
+//! | `status`  | 1         | `0x00` on success, `0x01` on error               |
+//! | `len`     | 4         | Length of `payload`                              |
+//! | `payload` | `len`     | See below                                        |
+//!
+//! On success, the payload of a verifying key request is the [`KeyAlgId`]
+//! followed by the key data, e.g. a compressed SEC1 point, and the payload of
+//! a sign request is the signature data in the format of the algorithm. On
+//! error, the payload is a UTF-8 error message.
+//!
+//! Payloads are limited to [`MAX_PAYLOAD_LEN`] bytes.
+//!
+//! Signers can use [`serve_remote_signer`] to implement the protocol.
+
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    net::TcpStream,
+    str::FromStr,
+    time::Duration,
+};
+
+use super::*;
+
+/// Maximum length of request and response payloads.
+pub const MAX_PAYLOAD_LEN: usize = 1 << 16;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const OP_VERIFYING_KEY: u8 = 0x01;
+const OP_SIGN: u8 = 0x02;
+const STATUS_OK: u8 = 0x00;
+const STATUS_ERROR: u8 = 0x01;
+
+/// Endpoint of a remote signer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteSignerEndpoint {
+    /// TCP address, e.g. `tcp://127.0.0.1:7049`.
+    Tcp(String),
+    /// Path of a Unix domain socket, e.g. `unix:///run/notary/signer.sock`.
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl FromStr for RemoteSignerEndpoint {
+    type Err = SignerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            Ok(Self::Tcp(addr.to_string()))
+        } else if let Some(path) = s.strip_prefix("unix://") {
+            #[cfg(unix)]
+            return Ok(Self::Unix(path.into()));
+            #[cfg(not(unix))]
+            return Err(SignerError(format!(
+                "unix sockets are not supported on this platform: {path}"
+            )));
+        } else {
+            Err(SignerError(format!(
+                "remote signer endpoint must start with tcp:// or unix://, got {s}"
+            )))
+        }
+    }
+}
+
+impl fmt::Display for RemoteSignerEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "tcp://{addr}"),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+/// Signer which delegates signing to a remote signer.
+///
+/// See the [module level documentation](self) for the protocol.
+///
+/// # Note
+///
+/// [`Signer`] is synchronous, so signing blocks the calling thread until the
+/// remote signer responds or the timeout elapses.
+#[derive(Debug)]
+pub struct RemoteSigner {
+    endpoint: RemoteSignerEndpoint,
+    alg: SignatureAlgId,
+    key: VerifyingKey,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    /// Connects to a remote signer and fetches its verifying key.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - Endpoint of the remote signer.
+    /// * `alg` - Signature algorithm to request.
+    pub fn connect(
+        endpoint: RemoteSignerEndpoint,
+        alg: SignatureAlgId,
+    ) -> Result<Self, SignerError> {
+        Self::connect_with_timeout(endpoint, alg, DEFAULT_TIMEOUT)
+    }
+
+    /// Connects to a remote signer with a timeout for each request.
+    pub fn connect_with_timeout(
+        endpoint: RemoteSignerEndpoint,
+        alg: SignatureAlgId,
+        timeout: Duration,
+    ) -> Result<Self, SignerError> {
+        let mut signer = Self {
+            endpoint,
+            alg,
+            key: VerifyingKey {
+                alg: KeyAlgId(0),
+                data: Vec::new(),
+            },
+            timeout,
+        };
+
+        let payload = signer.request(OP_VERIFYING_KEY, &[])?;
+        let (key_alg, data) = payload
+            .split_first()
+            .ok_or_else(|| SignerError("remote signer returned an empty key".to_string()))?;
+        signer.key = VerifyingKey {
+            alg: KeyAlgId(*key_alg),
+            data: data.to_vec(),
+        };
+
+        Ok(signer)
+    }
+
+    /// Returns the endpoint of the remote signer.
+    pub fn endpoint(&self) -> &RemoteSignerEndpoint {
+        &self.endpoint
+    }
+
+    fn request(&self, op: u8, payload: &[u8]) -> Result<Vec<u8>, SignerError> {
+        let err = |e: io::Error| SignerError(format!("remote signer {}: {e}", self.endpoint));
+
+        match &self.endpoint {
+            RemoteSignerEndpoint::Tcp(addr) => {
+                let mut stream = TcpStream::connect(addr).map_err(err)?;
+                stream.set_read_timeout(Some(self.timeout)).map_err(err)?;
+                stream.set_write_timeout(Some(self.timeout)).map_err(err)?;
+                self.exchange(&mut stream, op, payload)
+            }
+            #[cfg(unix)]
+            RemoteSignerEndpoint::Unix(path) => {
+                let mut stream = std::os::unix::net::UnixStream::connect(path).map_err(err)?;
+                stream.set_read_timeout(Some(self.timeout)).map_err(err)?;
+                stream.set_write_timeout(Some(self.timeout)).map_err(err)?;
+                self.exchange(&mut stream, op, payload)
+            }
+        }
+    }
+
+    fn exchange<S: Read + Write>(
+        &self,
+        stream: &mut S,
+        op: u8,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, SignerError> {
+        let err = |e: io::Error| SignerError(format!("remote signer {}: {e}", self.endpoint));
+
+        stream.write_all(&[op, self.alg.0]).map_err(err)?;
+        write_payload(stream, payload).map_err(err)?;
+        stream.flush().map_err(err)?;
+
+        let mut status = [0u8; 1];
+        stream.read_exact(&mut status).map_err(err)?;
+        let payload = read_payload(stream).map_err(err)?;
+
+        match status[0] {
+            STATUS_OK => Ok(payload),
+            STATUS_ERROR => Err(SignerError(format!(
+                "remote signer {} returned an error: {}",
+                self.endpoint,
+                String::from_utf8_lossy(&payload)
+            ))),
+            status => Err(SignerError(format!(
+                "remote signer {} returned an invalid status: {status}",
+                self.endpoint
+            ))),
+        }
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn alg_id(&self) -> SignatureAlgId {
+        self.alg
+    }
+
+    fn sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
+        let data = self
+            .request(OP_SIGN, msg)
+            .map_err(|e| SignatureError(e.to_string()))?;
+
+        // Catch a misconfigured signer before the signature ends up in an
+        // attestation.
+        if let Ok(verifier) = SignatureVerifierProvider::default().get(&self.alg) {
+            verifier.verify(&self.key, msg, &data)?;
+        }
+
+        Ok(Signature {
+            alg: self.alg,
+            data,
+        })
+    }
+
+    fn verifying_key(&self) -> VerifyingKey {
+        self.key.clone()
+    }
+}
+
+/// Serves a single request of the remote signer protocol with the provided
+/// signer.
+///
+/// This is intended for implementing remote signers, see the [module level
+/// documentation](self) for the protocol.
+pub fn serve_remote_signer<S: Read + Write>(stream: &mut S, signer: &dyn Signer) -> io::Result<()> {
+    let mut header = [0u8; 2];
+    stream.read_exact(&mut header)?;
+    let payload = read_payload(stream)?;
+    let [op, alg] = header;
+
+    let result = if alg != signer.alg_id().0 {
+        Err(format!(
+            "unsupported signature algorithm {}, expected {}",
+            SignatureAlgId(alg),
+            signer.alg_id()
+        ))
+    } else {
+        match op {
+            OP_VERIFYING_KEY => {
+                let key = signer.verifying_key();
+                let mut data = vec![key.alg.0];
+                data.extend_from_slice(&key.data);
+                Ok(data)
+            }
+            OP_SIGN => signer
+                .sign(&payload)
+                .map(|sig| sig.data)
+                .map_err(|e| e.to_string()),
+            op => Err(format!("unknown operation {op}")),
+        }
+    };
+
+    match result {
+        Ok(data) => {
+            stream.write_all(&[STATUS_OK])?;
+            write_payload(stream, &data)?;
+        }
+        Err(msg) => {
+            stream.write_all(&[STATUS_ERROR])?;
+            write_payload(stream, msg.as_bytes())?;
+        }
+    }
+
+    stream.flush()
+}
+
+fn write_payload<W: Write>(writer: &mut W, payload: &[u8]) -> io::Result<()> {
+    if payload.len() > MAX_PAYLOAD_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "payload is too long",
+        ));
+    }
+
+    writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+    writer.write_all(payload)
+}
+
+fn read_payload<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_PAYLOAD_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "payload is too long",
+        ));
+    }
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread};
+
+    use super::*;
+
+    /// Spawns a remote signer serving the given number of requests.
+    fn spawn_signer(signer: impl Signer + Send + 'static, requests: usize) -> RemoteSignerEndpoint {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                serve_remote_signer(&mut stream.unwrap(), &signer).unwrap();
+            }
+        });
+
+        RemoteSignerEndpoint::Tcp(addr.to_string())
+    }
+
+    #[test]
+    fn test_remote_signer() {
+        let local = Secp256k1Signer::new(&[1u8; 32]).unwrap();
+        let endpoint = spawn_signer(Secp256k1Signer::new(&[1u8; 32]).unwrap(), 2);
+
+        let remote = RemoteSigner::connect(endpoint, SignatureAlgId::SECP256K1).unwrap();
+        assert_eq!(remote.alg_id(), SignatureAlgId::SECP256K1);
+        assert_eq!(remote.verifying_key(), local.verifying_key());
+
+        let sig = remote.sign(b"hello").unwrap();
+        Secp256k1Verifier
+            .verify(&local.verifying_key(), b"hello", &sig.data)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_remote_signer_wrong_alg() {
+        let endpoint = spawn_signer(Secp256k1Signer::new(&[1u8; 32]).unwrap(), 1);
+
+        let err = RemoteSigner::connect(endpoint, SignatureAlgId::SECP256R1).unwrap_err();
+        assert!(err.to_string().contains("unsupported signature algorithm"));
+    }
+
+    #[test]
+    fn test_remote_signer_key_mismatch() {
+        // A signer which returns the verifying key of another key.
+        struct Mismatch(Secp256k1Signer, Secp256k1Signer);
+
+        impl Signer for Mismatch {
+            fn alg_id(&self) -> SignatureAlgId {
+                self.0.alg_id()
+            }
+
+            fn sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
+                self.0.sign(msg)
+            }
+
+            fn verifying_key(&self) -> VerifyingKey {
+                self.1.verifying_key()
+            }
+        }
+
+        let endpoint = spawn_signer(
+            Mismatch(
+                Secp256k1Signer::new(&[1u8; 32]).unwrap(),
+                Secp256k1Signer::new(&[2u8; 32]).unwrap(),
+            ),
+            2,
+        );
+
+        let remote = RemoteSigner::connect(endpoint, SignatureAlgId::SECP256K1).unwrap();
+        assert!(remote.sign(b"hello").is_err());
+    }
+
+    #[test]
+    fn test_endpoint_from_str() {
+        assert_eq!(
+            "tcp://127.0.0.1:7049"
+                .parse::<RemoteSignerEndpoint>()
+                .unwrap(),
+            RemoteSignerEndpoint::Tcp("127.0.0.1:7049".to_string())
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            "unix:///run/signer.sock"
+                .parse::<RemoteSignerEndpoint>()
+                .unwrap(),
+            RemoteSignerEndpoint::Unix("/run/signer.sock".into())
+        );
+        assert!("http://127.0.0.1".parse::<RemoteSignerEndpoint>().is_err());
+    }
+}
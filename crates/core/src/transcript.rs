@@ -35,6 +35,7 @@ mod commit;
 #[doc(hidden)]
 pub mod encoding;
 pub mod hash;
+pub mod predicate;
 mod proof;
 
 use std::{fmt, ops::Range};
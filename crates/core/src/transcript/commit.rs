@@ -9,7 +9,10 @@ use crate::{
     hash::{impl_domain_separator, HashAlgId},
     transcript::{
         encoding::{EncodingCommitment, EncodingTree},
-        hash::{PlaintextHash, PlaintextHashSecret},
+        hash::{
+            MaskedPlaintextHash, MaskedPlaintextHashSecret, PlaintextHash, PlaintextHashSecret,
+        },
+        predicate::{Dfa, Predicate, PredicateKind},
         Direction, Idx, Transcript,
     },
 };
@@ -34,6 +37,12 @@ pub enum TranscriptCommitmentKind {
         /// The hash algorithm used.
         alg: HashAlgId,
     },
+    /// A hash commitment to a masked view of the transcript, which hides the
+    /// committed ranges from the verifier.
+    MaskedHash {
+        /// The hash algorithm used.
+        alg: HashAlgId,
+    },
 }
 
 impl fmt::Display for TranscriptCommitmentKind {
@@ -41,6 +50,7 @@ impl fmt::Display for TranscriptCommitmentKind {
         match self {
             Self::Encoding => f.write_str("encoding"),
             Self::Hash { alg } => write!(f, "hash ({alg})"),
+            Self::MaskedHash { alg } => write!(f, "masked hash ({alg})"),
         }
     }
 }
@@ -53,6 +63,10 @@ pub enum TranscriptCommitment {
     Encoding(EncodingCommitment),
     /// Plaintext hash commitment.
     Hash(PlaintextHash),
+    /// Masked plaintext hash commitment.
+    MaskedHash(MaskedPlaintextHash),
+    /// Predicate which was proven over committed plaintext.
+    Predicate(Predicate),
 }
 
 impl_domain_separator!(TranscriptCommitment);
@@ -65,6 +79,8 @@ pub enum TranscriptSecret {
     Encoding(EncodingTree),
     /// Plaintext hash secret.
     Hash(PlaintextHashSecret),
+    /// Masked plaintext hash secret.
+    MaskedHash(MaskedPlaintextHashSecret),
 }
 
 impl_domain_separator!(TranscriptSecret);
@@ -75,7 +91,9 @@ pub struct TranscriptCommitConfig {
     encoding_hash_alg: HashAlgId,
     has_encoding: bool,
     has_hash: bool,
+    has_masked_hash: bool,
     commits: Vec<((Direction, Idx), TranscriptCommitmentKind)>,
+    predicates: Vec<Predicate>,
 }
 
 impl TranscriptCommitConfig {
@@ -99,6 +117,16 @@ impl TranscriptCommitConfig {
         self.has_hash
     }
 
+    /// Returns `true` if the configuration has any masked hash commitments.
+    pub fn has_masked_hash(&self) -> bool {
+        self.has_masked_hash
+    }
+
+    /// Returns `true` if the configuration has any predicates.
+    pub fn has_predicates(&self) -> bool {
+        !self.predicates.is_empty()
+    }
+
     /// Returns an iterator over the encoding commitment indices.
     pub fn iter_encoding(&self) -> impl Iterator<Item = &(Direction, Idx)> {
         self.commits.iter().filter_map(|(idx, kind)| match kind {
@@ -115,6 +143,19 @@ impl TranscriptCommitConfig {
         })
     }
 
+    /// Returns an iterator over the masked hash commitment indices.
+    pub fn iter_masked_hash(&self) -> impl Iterator<Item = (&(Direction, Idx), &HashAlgId)> {
+        self.commits.iter().filter_map(|(idx, kind)| match kind {
+            TranscriptCommitmentKind::MaskedHash { alg } => Some((idx, alg)),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the predicates.
+    pub fn iter_predicates(&self) -> impl Iterator<Item = &Predicate> {
+        self.predicates.iter()
+    }
+
     /// Returns a request for the transcript commitments.
     pub fn to_request(&self) -> TranscriptCommitRequest {
         TranscriptCommitRequest {
@@ -123,6 +164,11 @@ impl TranscriptCommitConfig {
                 .iter_hash()
                 .map(|((dir, idx), alg)| (*dir, idx.clone(), *alg))
                 .collect(),
+            masked_hash: self
+                .iter_masked_hash()
+                .map(|((dir, _), alg)| (*dir, *alg))
+                .collect(),
+            predicates: self.predicates.clone(),
         }
     }
 }
@@ -137,8 +183,10 @@ pub struct TranscriptCommitConfigBuilder<'a> {
     encoding_hash_alg: HashAlgId,
     has_encoding: bool,
     has_hash: bool,
+    has_masked_hash: bool,
     default_kind: TranscriptCommitmentKind,
     commits: HashSet<((Direction, Idx), TranscriptCommitmentKind)>,
+    predicates: Vec<Predicate>,
 }
 
 impl<'a> TranscriptCommitConfigBuilder<'a> {
@@ -149,8 +197,10 @@ impl<'a> TranscriptCommitConfigBuilder<'a> {
             encoding_hash_alg: HashAlgId::BLAKE3,
             has_encoding: false,
             has_hash: false,
+            has_masked_hash: false,
             default_kind: TranscriptCommitmentKind::Encoding,
             commits: HashSet::default(),
+            predicates: Vec::new(),
         }
     }
 
@@ -196,6 +246,7 @@ impl<'a> TranscriptCommitConfigBuilder<'a> {
         match kind {
             TranscriptCommitmentKind::Encoding => self.has_encoding = true,
             TranscriptCommitmentKind::Hash { .. } => self.has_hash = true,
+            TranscriptCommitmentKind::MaskedHash { .. } => self.has_masked_hash = true,
         }
 
         self.commits.insert(((direction, idx), kind));
@@ -241,13 +292,91 @@ impl<'a> TranscriptCommitConfigBuilder<'a> {
         self.commit(ranges, Direction::Received)
     }
 
+    /// Adds a predicate to prove over committed plaintext.
+    ///
+    /// The same ranges must also be committed with a
+    /// [`TranscriptCommitmentKind::Hash`] commitment.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranges` - The ranges of the plaintext.
+    /// * `direction` - The direction of the transcript.
+    /// * `kind` - The kind of predicate.
+    pub fn predicate(
+        &mut self,
+        ranges: &dyn ToRangeSet<usize>,
+        direction: Direction,
+        kind: PredicateKind,
+    ) -> Result<&mut Self, TranscriptCommitConfigBuilderError> {
+        let idx = Idx::new(ranges.to_range_set());
+
+        if idx.is_empty() {
+            return Err(TranscriptCommitConfigBuilderError::new(
+                ErrorKind::Predicate,
+                "predicate range is empty",
+            ));
+        }
+
+        let Some(data) = self.transcript.get(direction, &idx) else {
+            return Err(TranscriptCommitConfigBuilderError::new(
+                ErrorKind::Index,
+                format!(
+                    "range is out of bounds of the transcript ({}): {} > {}",
+                    direction,
+                    idx.end(),
+                    self.transcript.len_of_direction(direction)
+                ),
+            ));
+        };
+
+        if let PredicateKind::Regex { pattern } = &kind {
+            Dfa::new(pattern).map_err(|err| {
+                TranscriptCommitConfigBuilderError::new(ErrorKind::Predicate, err)
+            })?;
+        }
+
+        let predicate = Predicate {
+            direction,
+            idx,
+            kind,
+        };
+
+        if !predicate.eval(data.data()) {
+            return Err(TranscriptCommitConfigBuilderError::new(
+                ErrorKind::Predicate,
+                format!("predicate does not hold: {predicate}"),
+            ));
+        }
+
+        self.predicates.push(predicate);
+
+        Ok(self)
+    }
+
     /// Builds the configuration.
     pub fn build(self) -> Result<TranscriptCommitConfig, TranscriptCommitConfigBuilderError> {
+        for predicate in &self.predicates {
+            let committed = self.commits.iter().any(|((direction, idx), kind)| {
+                matches!(kind, TranscriptCommitmentKind::Hash { .. })
+                    && *direction == predicate.direction
+                    && *idx == predicate.idx
+            });
+
+            if !committed {
+                return Err(TranscriptCommitConfigBuilderError::new(
+                    ErrorKind::Predicate,
+                    format!("predicate range is not hash committed: {predicate}"),
+                ));
+            }
+        }
+
         Ok(TranscriptCommitConfig {
             encoding_hash_alg: self.encoding_hash_alg,
             has_encoding: self.has_encoding,
             has_hash: self.has_hash,
+            has_masked_hash: self.has_masked_hash,
             commits: Vec::from_iter(self.commits),
+            predicates: self.predicates,
         })
     }
 }
@@ -274,12 +403,14 @@ impl TranscriptCommitConfigBuilderError {
 #[derive(Debug)]
 enum ErrorKind {
     Index,
+    Predicate,
 }
 
 impl fmt::Display for TranscriptCommitConfigBuilderError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.kind {
             ErrorKind::Index => f.write_str("index error")?,
+            ErrorKind::Predicate => f.write_str("predicate error")?,
         }
 
         if let Some(source) = &self.source {
@@ -295,6 +426,8 @@ impl fmt::Display for TranscriptCommitConfigBuilderError {
 pub struct TranscriptCommitRequest {
     encoding: bool,
     hash: Vec<(Direction, Idx, HashAlgId)>,
+    masked_hash: Vec<(Direction, HashAlgId)>,
+    predicates: Vec<Predicate>,
 }
 
 impl TranscriptCommitRequest {
@@ -312,11 +445,37 @@ impl TranscriptCommitRequest {
     pub fn iter_hash(&self) -> impl Iterator<Item = &(Direction, Idx, HashAlgId)> {
         self.hash.iter()
     }
+
+    /// Returns `true` if a masked hash commitment is requested.
+    pub fn has_masked_hash(&self) -> bool {
+        !self.masked_hash.is_empty()
+    }
+
+    /// Returns an iterator over the masked hash commitments.
+    ///
+    /// The committed ranges are not part of the request.
+    pub fn iter_masked_hash(&self) -> impl Iterator<Item = &(Direction, HashAlgId)> {
+        self.masked_hash.iter()
+    }
+
+    /// Returns `true` if any predicates are requested.
+    pub fn has_predicates(&self) -> bool {
+        !self.predicates.is_empty()
+    }
+
+    /// Returns an iterator over the predicates.
+    ///
+    /// Each predicate is over a range which is also requested as a hash
+    /// commitment.
+    pub fn iter_predicates(&self) -> impl Iterator<Item = &Predicate> {
+        self.predicates.iter()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transcript::predicate::CmpOp;
 
     #[test]
     fn test_range_out_of_bounds() {
@@ -329,4 +488,100 @@ mod tests {
         assert!(builder.commit_sent(&(10..15)).is_err());
         assert!(builder.commit_recv(&(10..15)).is_err());
     }
+
+    #[test]
+    fn test_masked_hash_request_hides_idx() {
+        let transcript = Transcript::new([0; 12], [0; 12]);
+        let mut builder = TranscriptCommitConfigBuilder::new(&transcript);
+
+        builder
+            .commit_with_kind(
+                &(2..5),
+                Direction::Received,
+                TranscriptCommitmentKind::MaskedHash {
+                    alg: HashAlgId::SHA256,
+                },
+            )
+            .unwrap();
+
+        let config = builder.build().unwrap();
+        let request = config.to_request();
+
+        assert!(config.has_masked_hash());
+        assert!(!request.has_hash());
+        assert_eq!(
+            request.iter_masked_hash().collect::<Vec<_>>(),
+            vec![&(Direction::Received, HashAlgId::SHA256)]
+        );
+    }
+
+    #[test]
+    fn test_predicate_requires_hash_commitment() {
+        let transcript = Transcript::new(b"GET /", b"{\"balance\":1523}");
+        let kind = PredicateKind::Compare {
+            op: CmpOp::Gt,
+            value: 1000,
+        };
+
+        let mut builder = TranscriptCommitConfigBuilder::new(&transcript);
+        builder
+            .predicate(&(11..15), Direction::Received, kind.clone())
+            .unwrap();
+        assert!(builder.build().is_err());
+
+        let mut builder = TranscriptCommitConfigBuilder::new(&transcript);
+        builder
+            .commit_with_kind(
+                &(11..15),
+                Direction::Received,
+                TranscriptCommitmentKind::Hash {
+                    alg: HashAlgId::SHA256,
+                },
+            )
+            .unwrap()
+            .predicate(&(11..15), Direction::Received, kind)
+            .unwrap();
+
+        let request = builder.build().unwrap().to_request();
+        assert_eq!(request.iter_predicates().count(), 1);
+    }
+
+    #[test]
+    fn test_predicate_must_hold() {
+        let transcript = Transcript::new(b"GET /", b"{\"balance\":1523}");
+        let mut builder = TranscriptCommitConfigBuilder::new(&transcript);
+
+        assert!(builder
+            .predicate(
+                &(11..15),
+                Direction::Received,
+                PredicateKind::Compare {
+                    op: CmpOp::Lt,
+                    value: 1000,
+                },
+            )
+            .is_err());
+
+        assert!(builder
+            .predicate(
+                &(11..15),
+                Direction::Received,
+                PredicateKind::Regex {
+                    pattern: "[0-9".to_string(),
+                },
+            )
+            .unwrap_err()
+            .to_string()
+            .contains("invalid regex"));
+
+        assert!(builder
+            .predicate(
+                &(11..15),
+                Direction::Received,
+                PredicateKind::Regex {
+                    pattern: "[1-9][0-9]*".to_string(),
+                },
+            )
+            .is_ok());
+    }
 }
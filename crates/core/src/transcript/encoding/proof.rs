@@ -4,7 +4,7 @@ use rangeset::{RangeSet, UnionMut};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    hash::{Blinder, HashProviderError},
+    hash::{Blinder, Hash, HashAlgorithm, HashProviderError},
     merkle::{MerkleError, MerkleProof},
     transcript::{
         commit::MAX_TOTAL_COMMITTED_DATA,
@@ -32,9 +32,73 @@ pub struct EncodingProof {
     /// commitments.
     pub(super) inclusion_proof: MerkleProof,
     pub(super) openings: HashMap<usize, Opening>,
+    /// Hashes of the leaves which are included in the Merkle proof but not
+    /// opened, see [`EncodingProof::hide`].
+    pub(super) hidden: HashMap<usize, Hash>,
 }
 
 impl EncodingProof {
+    /// Returns an iterator over the opened indices of the transcript.
+    pub(crate) fn opened(&self) -> impl Iterator<Item = (Direction, &Idx)> {
+        self.openings
+            .values()
+            .map(|opening| (opening.direction, &opening.idx))
+    }
+
+    /// Returns a proof with the openings selected by `hide` replaced with the
+    /// hashes of their leaves.
+    ///
+    /// The proof remains valid against the same commitment, without
+    /// disclosing the data of the hidden openings.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Crypto provider.
+    /// * `commitment` - Encoding commitment of the proof.
+    /// * `sent` - Sent data of the proof.
+    /// * `recv` - Received data of the proof.
+    /// * `hide` - Returns whether to hide an opening.
+    pub(crate) fn hide(
+        &self,
+        provider: &CryptoProvider,
+        commitment: &EncodingCommitment,
+        sent: &[u8],
+        recv: &[u8],
+        hide: impl Fn(Direction, &Idx) -> bool,
+    ) -> Result<Self, EncodingProofError> {
+        let hasher = provider.hash.get(&commitment.root.alg)?;
+        let encoder = new_encoder(&commitment.secret);
+
+        let mut openings = HashMap::new();
+        let mut hidden = self.hidden.clone();
+        for (id, opening) in &self.openings {
+            if !hide(opening.direction, &opening.idx) {
+                openings.insert(*id, opening.clone());
+                continue;
+            }
+
+            let data = match opening.direction {
+                Direction::Sent => sent,
+                Direction::Received => recv,
+            };
+
+            if opening.idx.end() > data.len() {
+                return Err(EncodingProofError::new(
+                    ErrorKind::Proof,
+                    "index out of bounds of the transcript",
+                ));
+            }
+
+            hidden.insert(*id, leaf_hash(hasher, &encoder, opening, data));
+        }
+
+        Ok(Self {
+            inclusion_proof: self.inclusion_proof.clone(),
+            openings,
+            hidden,
+        })
+    }
+
     /// Verifies the proof against the commitment.
     ///
     /// Returns the authenticated indices of the sent and received data,
@@ -59,6 +123,7 @@ impl EncodingProof {
         let Self {
             inclusion_proof,
             openings,
+            hidden,
         } = self;
 
         let mut leaves = Vec::with_capacity(openings.len());
@@ -115,6 +180,9 @@ impl EncodingProof {
             auth.union_mut(idx.as_range_set());
         }
 
+        // Leaves which are not opened do not authenticate any data.
+        leaves.extend(hidden.iter().map(|(id, hash)| (*id, *hash)));
+
         // Verify that the expected hashes are present in the merkle tree.
         //
         // This proves the Prover committed to the purported data prior to the encoder
@@ -126,6 +194,22 @@ impl EncodingProof {
     }
 }
 
+/// Computes the hash of the leaf of an opening.
+fn leaf_hash(
+    hasher: &dyn HashAlgorithm,
+    encoder: &dyn Encoder,
+    opening: &Opening,
+    data: &[u8],
+) -> Hash {
+    let mut leaf = Vec::new();
+    for range in opening.idx.iter_ranges() {
+        encoder.encode_data(opening.direction, range.clone(), &data[range], &mut leaf);
+    }
+    leaf.extend_from_slice(opening.blinder.as_bytes());
+
+    hasher.hash(&leaf)
+}
+
 /// Error for [`EncodingProof`].
 #[derive(Debug, thiserror::Error)]
 pub struct EncodingProofError {
@@ -206,6 +290,7 @@ mod validation {
     pub(super) struct EncodingProofUnchecked {
         inclusion_proof: MerkleProof,
         openings: HashMap<usize, Opening>,
+        hidden: HashMap<usize, Hash>,
     }
 
     impl TryFrom<EncodingProofUnchecked> for EncodingProof {
@@ -221,6 +306,7 @@ mod validation {
             Ok(Self {
                 inclusion_proof: unchecked.inclusion_proof,
                 openings: unchecked.openings,
+                hidden: unchecked.hidden,
             })
         }
     }
@@ -167,6 +167,7 @@ impl EncodingTree {
         Ok(EncodingProof {
             inclusion_proof: self.tree.proof(&indices),
             openings,
+            hidden: HashMap::new(),
         })
     }
 
@@ -17,6 +17,46 @@ pub fn hash_plaintext(hasher: &dyn HashAlgorithm, msg: &[u8], blinder: &Blinder)
     }
 }
 
+/// Hashes a masked view of plaintext with a blinder.
+///
+/// The view has the length of `data`, with all bytes outside of `idx` set to
+/// zero. The mask returned by [`plaintext_mask`] is hashed along with the view
+/// so that the hash binds the committed ranges, i.e. `H(view | mask |
+/// blinder)`.
+///
+/// # Panics
+///
+/// Panics if `idx` is out of bounds of `data`.
+pub fn hash_masked_plaintext(
+    hasher: &dyn HashAlgorithm,
+    data: &[u8],
+    idx: &Idx,
+    blinder: &Blinder,
+) -> TypedHash {
+    let mut msg = vec![0; data.len()];
+    for range in idx.iter_ranges() {
+        msg[range.clone()].copy_from_slice(&data[range]);
+    }
+    msg.extend(plaintext_mask(idx, data.len()));
+
+    TypedHash {
+        alg: hasher.id(),
+        value: hasher.hash_prefixed(&msg, blinder.as_bytes()),
+    }
+}
+
+/// Returns the mask of `idx` over `len` bytes.
+///
+/// The mask has one bit per byte, least significant bit first, which is set if
+/// the byte is in `idx`.
+pub fn plaintext_mask(idx: &Idx, len: usize) -> Vec<u8> {
+    let mut mask = vec![0; len.div_ceil(8)];
+    for i in idx.iter() {
+        mask[i / 8] |= 1 << (i % 8);
+    }
+    mask
+}
+
 /// Hash of plaintext in the transcript.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PlaintextHash {
@@ -44,3 +84,32 @@ pub struct PlaintextHashSecret {
 }
 
 opaque_debug::implement!(PlaintextHashSecret);
+
+/// Hash of a masked view of one direction of the transcript.
+///
+/// Unlike [`PlaintextHash`] this does not disclose which ranges are committed,
+/// see [`hash_masked_plaintext`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MaskedPlaintextHash {
+    /// Direction of the plaintext.
+    pub direction: Direction,
+    /// The hash of the masked data.
+    pub hash: TypedHash,
+}
+
+impl_domain_separator!(MaskedPlaintextHash);
+
+/// Secret component of [`MaskedPlaintextHash`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MaskedPlaintextHashSecret {
+    /// Direction of the plaintext.
+    pub direction: Direction,
+    /// Index of plaintext.
+    pub idx: Idx,
+    /// The algorithm of the hash.
+    pub alg: HashAlgId,
+    /// Blinder for the hash.
+    pub blinder: Blinder,
+}
+
+opaque_debug::implement!(MaskedPlaintextHashSecret);
@@ -0,0 +1,214 @@
+//! Predicates over committed plaintext.
+//!
+//! A predicate is a statement about a range of the transcript, e.g. "the
+//! `balance` field is greater than 1000", which the Prover proves to the
+//! Verifier in zero-knowledge without revealing the plaintext.
+//!
+//! Predicates can only be proven over ranges which are also committed with a
+//! [`PlaintextHash`](crate::transcript::hash::PlaintextHash), both are computed
+//! from the same plaintext in the VM.
+
+mod regex;
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hash::impl_domain_separator,
+    transcript::{Direction, Idx},
+};
+
+pub use regex::{Dfa, RegexError, MAX_STATES};
+
+/// A predicate over a range of the transcript.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Predicate {
+    /// Direction of the plaintext.
+    pub direction: Direction,
+    /// Index of the plaintext.
+    pub idx: Idx,
+    /// The statement about the plaintext.
+    pub kind: PredicateKind,
+}
+
+impl_domain_separator!(Predicate);
+
+impl Predicate {
+    /// Evaluates the predicate on the plaintext of `idx`.
+    pub fn eval(&self, data: &[u8]) -> bool {
+        self.kind.eval(data)
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:?} {}", self.direction, self.idx, self.kind)
+    }
+}
+
+/// Kind of predicate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum PredicateKind {
+    /// The plaintext is equal to a public constant.
+    Equal {
+        /// The constant.
+        value: Vec<u8>,
+    },
+    /// The plaintext is an ASCII decimal number which compares to a public
+    /// constant.
+    ///
+    /// Every byte of the plaintext must be a digit, leading zeros are allowed.
+    Compare {
+        /// The comparison operator, with the plaintext on the left-hand side.
+        op: CmpOp,
+        /// The constant.
+        value: u64,
+    },
+    /// The plaintext contains a public substring.
+    Contains {
+        /// The substring.
+        needle: Vec<u8>,
+    },
+    /// The whole plaintext matches a public regular expression.
+    ///
+    /// See [`Dfa`] for the supported syntax. Patterns which are invalid or
+    /// compile to more than [`MAX_STATES`] states never hold.
+    Regex {
+        /// The regular expression.
+        pattern: String,
+    },
+}
+
+impl PredicateKind {
+    /// Evaluates the predicate on `data`.
+    pub fn eval(&self, data: &[u8]) -> bool {
+        match self {
+            PredicateKind::Equal { value } => data == value.as_slice(),
+            PredicateKind::Compare { op, value } => {
+                if data.is_empty() || !data.iter().all(u8::is_ascii_digit) {
+                    return false;
+                }
+
+                // Numbers which don't fit in a u64 are greater than any constant.
+                let lhs = data.iter().try_fold(0u64, |acc, digit| {
+                    acc.checked_mul(10)?.checked_add((digit - b'0') as u64)
+                });
+
+                match lhs {
+                    Some(lhs) => op.eval(lhs, *value),
+                    None => matches!(op, CmpOp::Gt | CmpOp::Gte),
+                }
+            }
+            PredicateKind::Contains { needle } => {
+                needle.is_empty() || data.windows(needle.len()).any(|w| w == needle.as_slice())
+            }
+            PredicateKind::Regex { pattern } => Dfa::new(pattern)
+                .map(|dfa| dfa.is_match(data))
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl fmt::Display for PredicateKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PredicateKind::Equal { value } => {
+                write!(f, "== {:?}", String::from_utf8_lossy(value))
+            }
+            PredicateKind::Compare { op, value } => write!(f, "{op} {value}"),
+            PredicateKind::Contains { needle } => {
+                write!(f, "contains {:?}", String::from_utf8_lossy(needle))
+            }
+            PredicateKind::Regex { pattern } => write!(f, "matches {pattern:?}"),
+        }
+    }
+}
+
+/// Comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CmpOp {
+    /// Less than.
+    Lt,
+    /// Less than or equal.
+    Lte,
+    /// Greater than.
+    Gt,
+    /// Greater than or equal.
+    Gte,
+}
+
+impl CmpOp {
+    /// Evaluates `lhs op rhs`.
+    pub fn eval(&self, lhs: u64, rhs: u64) -> bool {
+        match self {
+            CmpOp::Lt => lhs < rhs,
+            CmpOp::Lte => lhs <= rhs,
+            CmpOp::Gt => lhs > rhs,
+            CmpOp::Gte => lhs >= rhs,
+        }
+    }
+}
+
+impl fmt::Display for CmpOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CmpOp::Lt => f.write_str("<"),
+            CmpOp::Lte => f.write_str("<="),
+            CmpOp::Gt => f.write_str(">"),
+            CmpOp::Gte => f.write_str(">="),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::gt(b"1523", CmpOp::Gt, 1000, true)]
+    #[case::gt_equal(b"1000", CmpOp::Gt, 1000, false)]
+    #[case::gte_equal(b"1000", CmpOp::Gte, 1000, true)]
+    #[case::lt_leading_zeros(b"00999", CmpOp::Lt, 1000, true)]
+    #[case::lte(b"1001", CmpOp::Lte, 1000, false)]
+    #[case::overflow(b"99999999999999999999", CmpOp::Gt, u64::MAX, true)]
+    #[case::not_a_number(b"10a0", CmpOp::Gt, 1, false)]
+    #[case::empty(b"", CmpOp::Lt, 1, false)]
+    fn test_compare(
+        #[case] data: &[u8],
+        #[case] op: CmpOp,
+        #[case] value: u64,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(PredicateKind::Compare { op, value }.eval(data), expected);
+    }
+
+    #[test]
+    fn test_contains() {
+        let kind = PredicateKind::Contains {
+            needle: b"ok".to_vec(),
+        };
+
+        assert!(kind.eval(b"status: ok"));
+        assert!(!kind.eval(b"status: error"));
+        assert!(!kind.eval(b"o"));
+    }
+
+    #[test]
+    fn test_regex() {
+        let kind = PredicateKind::Regex {
+            pattern: r#"\{"role":"(admin|owner)"\}"#.to_string(),
+        };
+
+        assert!(kind.eval(br#"{"role":"admin"}"#));
+        assert!(!kind.eval(br#"{"role":"user"}"#));
+
+        // Invalid patterns never hold.
+        let kind = PredicateKind::Regex {
+            pattern: "(".to_string(),
+        };
+        assert!(!kind.eval(b"("));
+    }
+}
@@ -0,0 +1,663 @@
+//! Regular expressions compiled to deterministic finite automata.
+//!
+//! Patterns are matched against bytes and must match the whole plaintext, as
+//! if they were wrapped in `^(?:...)$`. The supported syntax is:
+//!
+//! - literal bytes, where a non-ASCII character matches its UTF-8 encoding,
+//! - `.` for any byte,
+//! - classes such as `[a-z_]` or `[^"]`, with ASCII or escaped members,
+//! - the escapes `\d`, `\D`, `\w`, `\W`, `\s`, `\S`, `\n`, `\r`, `\t`, `\xHH`
+//!   and any escaped punctuation, e.g. `\.`,
+//! - groups `(...)` and alternation `|`,
+//! - the quantifiers `*`, `+`, `?`, `{n}`, `{n,}` and `{n,m}`.
+//!
+//! The automaton is evaluated one byte at a time, which allows the same
+//! [`Dfa`] to be used for native matching and to build a circuit.
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Maximum number of states of a [`Dfa`].
+pub const MAX_STATES: usize = 256;
+/// Maximum count of a counted repetition, e.g. `{n,m}`.
+const MAX_REPEAT: u32 = 256;
+/// Maximum number of states of the intermediate NFA.
+const MAX_NFA_STATES: usize = 1 << 14;
+/// Maximum nesting depth of groups.
+const MAX_DEPTH: usize = 64;
+
+const DEAD: u32 = u32::MAX;
+
+/// Error for [`Dfa`].
+#[derive(Debug, thiserror::Error)]
+#[error("invalid regex: {0}")]
+pub struct RegexError(String);
+
+/// A deterministic finite automaton which matches a regular expression.
+///
+/// States which can not reach an accepting state are removed, hence
+/// [`Dfa::next`] returns `None` once the input can no longer match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dfa {
+    /// Transitions of each state, indexed by the input byte.
+    transitions: Vec<[u32; 256]>,
+    accepting: Vec<bool>,
+}
+
+impl Dfa {
+    /// Compiles a regular expression.
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        let ast = Parser {
+            pattern: pattern.as_bytes(),
+            pos: 0,
+            depth: 0,
+        }
+        .parse()?;
+
+        let mut nfa = Nfa::default();
+        let end = nfa.push(NfaState::Match)?;
+        let start = nfa.compile(&ast, end)?;
+
+        Ok(Self::determinize(&nfa, start)?.prune())
+    }
+
+    /// Returns the initial state.
+    pub fn start(&self) -> usize {
+        0
+    }
+
+    /// Returns the number of states.
+    pub fn num_states(&self) -> usize {
+        self.accepting.len()
+    }
+
+    /// Returns whether the state is accepting.
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state]
+    }
+
+    /// Returns the state reached from `state` with the input `byte`, or `None`
+    /// if the input can no longer match.
+    pub fn next(&self, state: usize, byte: u8) -> Option<usize> {
+        match self.transitions[state][byte as usize] {
+            DEAD => None,
+            next => Some(next as usize),
+        }
+    }
+
+    /// Returns whether the regular expression matches the whole `data`.
+    pub fn is_match(&self, data: &[u8]) -> bool {
+        let mut state = self.start();
+        for byte in data {
+            match self.next(state, *byte) {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+
+        self.is_accepting(state)
+    }
+
+    /// Builds the automaton with the subset construction.
+    fn determinize(nfa: &Nfa, start: usize) -> Result<Self, RegexError> {
+        let mut dfa = Self {
+            transitions: Vec::new(),
+            accepting: Vec::new(),
+        };
+        let mut ids: HashMap<BTreeSet<usize>, u32> = HashMap::new();
+        let mut sets = vec![nfa.closure([start])];
+        ids.insert(sets[0].clone(), 0);
+
+        while dfa.accepting.len() < sets.len() {
+            let set = sets[dfa.accepting.len()].clone();
+
+            let mut transitions = [DEAD; 256];
+            for (byte, transition) in transitions.iter_mut().enumerate() {
+                let targets = set.iter().filter_map(|state| match &nfa.states[*state] {
+                    NfaState::Byte(bytes, next) if bytes.contains(byte as u8) => Some(*next),
+                    _ => None,
+                });
+                let target = nfa.closure(targets);
+                if target.is_empty() {
+                    continue;
+                }
+
+                *transition = match ids.get(&target) {
+                    Some(id) => *id,
+                    None => {
+                        if sets.len() == MAX_STATES {
+                            return Err(RegexError(format!(
+                                "the automaton has more than {MAX_STATES} states"
+                            )));
+                        }
+
+                        let id = sets.len() as u32;
+                        ids.insert(target.clone(), id);
+                        sets.push(target);
+                        id
+                    }
+                };
+            }
+
+            dfa.transitions.push(transitions);
+            dfa.accepting.push(
+                set.iter()
+                    .any(|state| matches!(nfa.states[*state], NfaState::Match)),
+            );
+        }
+
+        Ok(dfa)
+    }
+
+    /// Removes the states from which no accepting state can be reached, except
+    /// the initial state.
+    fn prune(self) -> Self {
+        let len = self.num_states();
+
+        let mut live = self.accepting.clone();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for state in 0..len {
+                if !live[state]
+                    && self.transitions[state]
+                        .iter()
+                        .any(|next| *next != DEAD && live[*next as usize])
+                {
+                    live[state] = true;
+                    changed = true;
+                }
+            }
+        }
+        live[0] = true;
+
+        let mut ids = vec![DEAD; len];
+        let mut next_id = 0;
+        for (state, id) in ids.iter_mut().enumerate() {
+            if live[state] {
+                *id = next_id;
+                next_id += 1;
+            }
+        }
+
+        let mut dfa = Self {
+            transitions: Vec::new(),
+            accepting: Vec::new(),
+        };
+        for state in (0..len).filter(|state| live[*state]) {
+            dfa.transitions
+                .push(self.transitions[state].map(|next| match next {
+                    DEAD => DEAD,
+                    next => ids[next as usize],
+                }));
+            dfa.accepting.push(self.accepting[state]);
+        }
+
+        dfa
+    }
+}
+
+/// A set of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ByteSet([u64; 4]);
+
+impl ByteSet {
+    const EMPTY: Self = Self([0; 4]);
+    const ALL: Self = Self([u64::MAX; 4]);
+
+    fn byte(byte: u8) -> Self {
+        let mut set = Self::EMPTY;
+        set.insert(byte);
+        set
+    }
+
+    fn range(start: u8, end: u8) -> Self {
+        let mut set = Self::EMPTY;
+        for byte in start..=end {
+            set.insert(byte);
+        }
+        set
+    }
+
+    fn insert(&mut self, byte: u8) {
+        self.0[byte as usize / 64] |= 1 << (byte % 64);
+    }
+
+    fn contains(&self, byte: u8) -> bool {
+        self.0[byte as usize / 64] >> (byte % 64) & 1 == 1
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] | other.0[i]))
+    }
+
+    fn complement(self) -> Self {
+        Self(self.0.map(|word| !word))
+    }
+
+    fn digit() -> Self {
+        Self::range(b'0', b'9')
+    }
+
+    fn word() -> Self {
+        Self::range(b'a', b'z')
+            .union(Self::range(b'A', b'Z'))
+            .union(Self::digit())
+            .union(Self::byte(b'_'))
+    }
+
+    fn space() -> Self {
+        [b' ', b'\t', b'\n', b'\r', 0x0b, 0x0c]
+            .into_iter()
+            .fold(Self::EMPTY, |set, byte| set.union(Self::byte(byte)))
+    }
+}
+
+#[derive(Debug)]
+enum Ast {
+    Empty,
+    Bytes(ByteSet),
+    Concat(Vec<Ast>),
+    Alt(Vec<Ast>),
+    Repeat {
+        ast: Box<Ast>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+struct Parser<'a> {
+    pattern: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn parse(mut self) -> Result<Ast, RegexError> {
+        let ast = self.alt()?;
+        match self.peek() {
+            None => Ok(ast),
+            Some(b')') => Err(self.error("unopened group")),
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    fn error(&self, msg: &str) -> RegexError {
+        RegexError(format!("{msg} at offset {}", self.pos))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.pattern.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alt(&mut self) -> Result<Ast, RegexError> {
+        let mut alts = vec![self.concat()?];
+        while self.eat(b'|') {
+            alts.push(self.concat()?);
+        }
+
+        Ok(if alts.len() == 1 {
+            alts.pop().expect("one alternative")
+        } else {
+            Ast::Alt(alts)
+        })
+    }
+
+    fn concat(&mut self) -> Result<Ast, RegexError> {
+        let mut items = Vec::new();
+        while let Some(byte) = self.peek() {
+            if byte == b'|' || byte == b')' {
+                break;
+            }
+
+            let atom = self.atom()?;
+            items.push(self.repeat(atom)?);
+        }
+
+        Ok(match items.len() {
+            0 => Ast::Empty,
+            1 => items.pop().expect("one item"),
+            _ => Ast::Concat(items),
+        })
+    }
+
+    fn atom(&mut self) -> Result<Ast, RegexError> {
+        let start = self.pos;
+        let byte = self.bump().expect("caller checked for input");
+        Ok(match byte {
+            b'(' => {
+                self.depth += 1;
+                if self.depth > MAX_DEPTH {
+                    return Err(self.error("groups are nested too deeply"));
+                }
+                // Non-capturing groups are the same as groups.
+                if self.pattern[self.pos..].starts_with(b"?:") {
+                    self.pos += 2;
+                }
+                let ast = self.alt()?;
+                if !self.eat(b')') {
+                    return Err(self.error("unclosed group"));
+                }
+                self.depth -= 1;
+                ast
+            }
+            b'[' => Ast::Bytes(self.class()?),
+            b'.' => Ast::Bytes(ByteSet::ALL),
+            b'\\' => Ast::Bytes(self.escape()?),
+            b'*' | b'+' | b'?' | b'{' => {
+                self.pos = start;
+                return Err(self.error("quantifier without a preceding item"));
+            }
+            b'^' | b'$' => {
+                self.pos = start;
+                return Err(self.error("anchors are not supported, the whole input is matched"));
+            }
+            byte => Ast::Bytes(ByteSet::byte(byte)),
+        })
+    }
+
+    fn repeat(&mut self, mut ast: Ast) -> Result<Ast, RegexError> {
+        loop {
+            let (min, max) = match self.peek() {
+                Some(b'*') => (0, None),
+                Some(b'+') => (1, None),
+                Some(b'?') => (0, Some(1)),
+                Some(b'{') => {
+                    self.pos += 1;
+                    let min = self.number()?;
+                    let max = if self.eat(b',') {
+                        if self.peek() == Some(b'}') {
+                            None
+                        } else {
+                            Some(self.number()?)
+                        }
+                    } else {
+                        Some(min)
+                    };
+                    if self.peek() != Some(b'}') {
+                        return Err(self.error("unclosed counted repetition"));
+                    }
+                    if max.is_some_and(|max| max < min) {
+                        return Err(self.error("invalid counted repetition"));
+                    }
+                    (min, max)
+                }
+                _ => return Ok(ast),
+            };
+            self.pos += 1;
+
+            ast = Ast::Repeat {
+                ast: Box::new(ast),
+                min,
+                max,
+            };
+        }
+    }
+
+    fn number(&mut self) -> Result<u32, RegexError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        std::str::from_utf8(&self.pattern[start..self.pos])
+            .expect("digits are valid UTF-8")
+            .parse::<u32>()
+            .ok()
+            .filter(|n| *n <= MAX_REPEAT)
+            .ok_or_else(|| self.error("invalid repetition count"))
+    }
+
+    /// Parses an escape sequence after the backslash.
+    fn escape(&mut self) -> Result<ByteSet, RegexError> {
+        let Some(byte) = self.bump() else {
+            return Err(self.error("incomplete escape"));
+        };
+
+        Ok(match byte {
+            b'd' => ByteSet::digit(),
+            b'D' => ByteSet::digit().complement(),
+            b'w' => ByteSet::word(),
+            b'W' => ByteSet::word().complement(),
+            b's' => ByteSet::space(),
+            b'S' => ByteSet::space().complement(),
+            b'n' => ByteSet::byte(b'\n'),
+            b'r' => ByteSet::byte(b'\r'),
+            b't' => ByteSet::byte(b'\t'),
+            b'x' => {
+                let hex = self
+                    .pattern
+                    .get(self.pos..self.pos + 2)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| self.error("invalid hex escape"))?;
+                self.pos += 2;
+                ByteSet::byte(hex)
+            }
+            byte if byte.is_ascii_punctuation() => ByteSet::byte(byte),
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("unsupported escape"));
+            }
+        })
+    }
+
+    /// Parses a class after the opening bracket.
+    fn class(&mut self) -> Result<ByteSet, RegexError> {
+        let negated = self.eat(b'^');
+
+        let mut set = ByteSet::EMPTY;
+        let mut first = true;
+        loop {
+            let start = match self.bump() {
+                None => return Err(self.error("unclosed class")),
+                // A closing bracket is a member if it comes first.
+                Some(b']') if !first => break,
+                Some(b'\\') => {
+                    let escaped = self.escape()?;
+                    if escaped.0.iter().map(|word| word.count_ones()).sum::<u32>() != 1 {
+                        set = set.union(escaped);
+                        first = false;
+                        continue;
+                    }
+                    (0..=255u8)
+                        .find(|byte| escaped.contains(*byte))
+                        .expect("set has one member")
+                }
+                Some(byte) if !byte.is_ascii() => {
+                    return Err(self.error("non-ASCII class members are not supported"))
+                }
+                Some(byte) => byte,
+            };
+            first = false;
+
+            // A trailing dash is a member.
+            if self.peek() == Some(b'-') && self.pattern.get(self.pos + 1) != Some(&b']') {
+                self.pos += 1;
+                let end = match self.bump() {
+                    Some(b'\\') => {
+                        let escaped = self.escape()?;
+                        (0..=255u8)
+                            .find(|byte| escaped.contains(*byte))
+                            .filter(|byte| ByteSet::byte(*byte) == escaped)
+                            .ok_or_else(|| self.error("invalid class range"))?
+                    }
+                    Some(byte) if byte.is_ascii() => byte,
+                    _ => return Err(self.error("invalid class range")),
+                };
+                if end < start {
+                    return Err(self.error("invalid class range"));
+                }
+                set = set.union(ByteSet::range(start, end));
+            } else {
+                set.insert(start);
+            }
+        }
+
+        Ok(if negated { set.complement() } else { set })
+    }
+}
+
+#[derive(Debug)]
+enum NfaState {
+    /// Consumes a byte of the set.
+    Byte(ByteSet, usize),
+    /// Continues with any of the states without consuming input.
+    Split(Vec<usize>),
+    Match,
+}
+
+#[derive(Debug, Default)]
+struct Nfa {
+    states: Vec<NfaState>,
+}
+
+impl Nfa {
+    fn push(&mut self, state: NfaState) -> Result<usize, RegexError> {
+        if self.states.len() == MAX_NFA_STATES {
+            return Err(RegexError("the pattern is too large".to_string()));
+        }
+
+        self.states.push(state);
+        Ok(self.states.len() - 1)
+    }
+
+    /// Compiles the expression followed by the state `next`, returning the
+    /// initial state.
+    fn compile(&mut self, ast: &Ast, next: usize) -> Result<usize, RegexError> {
+        match ast {
+            Ast::Empty => Ok(next),
+            Ast::Bytes(bytes) => self.push(NfaState::Byte(*bytes, next)),
+            Ast::Concat(items) => items
+                .iter()
+                .rev()
+                .try_fold(next, |next, item| self.compile(item, next)),
+            Ast::Alt(alts) => {
+                let starts = alts
+                    .iter()
+                    .map(|alt| self.compile(alt, next))
+                    .collect::<Result<_, _>>()?;
+                self.push(NfaState::Split(starts))
+            }
+            Ast::Repeat { ast, min, max } => {
+                let mut next = next;
+                match max {
+                    None => {
+                        let split = self.push(NfaState::Split(Vec::new()))?;
+                        let body = self.compile(ast, split)?;
+                        self.states[split] = NfaState::Split(vec![body, next]);
+                        next = split;
+                    }
+                    Some(max) => {
+                        for _ in *min..*max {
+                            let body = self.compile(ast, next)?;
+                            next = self.push(NfaState::Split(vec![body, next]))?;
+                        }
+                    }
+                }
+
+                for _ in 0..*min {
+                    next = self.compile(ast, next)?;
+                }
+
+                Ok(next)
+            }
+        }
+    }
+
+    /// Returns the states reachable from `states` without consuming input.
+    fn closure(&self, states: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut closure = BTreeSet::new();
+        let mut stack: Vec<usize> = states.into_iter().collect();
+        while let Some(state) = stack.pop() {
+            if closure.insert(state) {
+                if let NfaState::Split(next) = &self.states[state] {
+                    stack.extend(next);
+                }
+            }
+        }
+
+        closure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::literal("ok", b"ok", true)]
+    #[case::literal_prefix("ok", b"okay", false)]
+    #[case::any(".*\"admin\":true.*", b"{\"admin\":true}", true)]
+    #[case::class(r"[a-f0-9]{8}", b"deadbeef", true)]
+    #[case::class_short(r"[a-f0-9]{8}", b"deadbee", false)]
+    #[case::class_negated(r#""[^"]*""#, b"\"a b\"", true)]
+    #[case::class_negated_fail(r#""[^"]*""#, b"\"a\"b\"", false)]
+    #[case::alt("GET|POST", b"POST", true)]
+    #[case::alt_fail("GET|POST", b"PUT", false)]
+    #[case::group(r"(ab)+c?", b"ababc", true)]
+    #[case::group_fail(r"(ab)+c?", b"abac", false)]
+    #[case::counted(r"\d{2,4}", b"12345", false)]
+    #[case::counted_open(r"\d{2,}", b"12345", true)]
+    #[case::escape(r"\d+\.\d\d", b"10.50", true)]
+    #[case::word(r"\w+\s\W", b"a_1 !", true)]
+    #[case::hex(r"\x41\x42", b"AB", true)]
+    #[case::dash("[-a]+", b"a-a", true)]
+    #[case::bracket("[]a]+", b"]a", true)]
+    #[case::empty("", b"", true)]
+    #[case::empty_fail("", b"a", false)]
+    #[case::utf8("café", "café".as_bytes(), true)]
+    #[case::date(
+        r"[0-9]{4}-(0[1-9]|1[0-2])-(0[1-9]|[12][0-9]|3[01])",
+        b"2024-02-29",
+        true
+    )]
+    #[case::date_fail(
+        r"[0-9]{4}-(0[1-9]|1[0-2])-(0[1-9]|[12][0-9]|3[01])",
+        b"2024-13-01",
+        false
+    )]
+    fn test_is_match(#[case] pattern: &str, #[case] data: &[u8], #[case] expected: bool) {
+        assert_eq!(Dfa::new(pattern).unwrap().is_match(data), expected);
+    }
+
+    #[rstest]
+    #[case::unclosed_group("(ab")]
+    #[case::unopened_group("ab)")]
+    #[case::unclosed_class("[ab")]
+    #[case::anchor("^ab$")]
+    #[case::dangling_quantifier("*a")]
+    #[case::invalid_range("[z-a]")]
+    #[case::invalid_count("a{3,2}")]
+    #[case::large_count("a{1000}")]
+    #[case::unsupported_escape(r"\b")]
+    #[case::too_many_states("[ab]*a[ab]{10}")]
+    fn test_invalid(#[case] pattern: &str) {
+        assert!(Dfa::new(pattern).is_err());
+    }
+
+    #[test]
+    fn test_prune() {
+        let dfa = Dfa::new("a|b").unwrap();
+
+        // Initial state and the accepting state reached by either byte.
+        assert_eq!(dfa.num_states(), 2);
+        assert_eq!(dfa.next(dfa.start(), b'c'), None);
+        assert_eq!(dfa.next(dfa.start(), b'a'), dfa.next(dfa.start(), b'b'));
+    }
+}
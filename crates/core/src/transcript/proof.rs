@@ -9,9 +9,12 @@ use crate::{
     hash::HashAlgId,
     transcript::{
         commit::{TranscriptCommitment, TranscriptCommitmentKind},
-        encoding::{EncodingProof, EncodingProofError, EncodingTree},
-        hash::{hash_plaintext, PlaintextHash, PlaintextHashSecret},
-        Direction, Idx, PartialTranscript, Transcript, TranscriptSecret,
+        encoding::{EncodingCommitment, EncodingProof, EncodingProofError, EncodingTree},
+        hash::{
+            hash_masked_plaintext, hash_plaintext, MaskedPlaintextHash, MaskedPlaintextHashSecret,
+            PlaintextHash, PlaintextHashSecret,
+        },
+        Direction, Idx, PartialTranscript, Subsequence, Transcript, TranscriptSecret,
     },
     CryptoProvider,
 };
@@ -31,6 +34,7 @@ pub struct TranscriptProof {
     transcript: PartialTranscript,
     encoding_proof: Option<EncodingProof>,
     hash_secrets: Vec<PlaintextHashSecret>,
+    masked_hash_secrets: Vec<MaskedPlaintextHashSecret>,
 }
 
 opaque_debug::implement!(TranscriptProof);
@@ -52,6 +56,7 @@ impl TranscriptProof {
     ) -> Result<PartialTranscript, TranscriptProofError> {
         let mut encoding_commitment = None;
         let mut hash_commitments = HashSet::new();
+        let mut masked_hash_commitments = HashSet::new();
         // Index commitments.
         for commitment in commitments {
             match commitment {
@@ -66,6 +71,11 @@ impl TranscriptProof {
                 TranscriptCommitment::Hash(plaintext_hash) => {
                     hash_commitments.insert(plaintext_hash);
                 }
+                TranscriptCommitment::MaskedHash(masked_hash) => {
+                    masked_hash_commitments.insert(masked_hash);
+                }
+                // Predicates are not used to authenticate plaintext.
+                TranscriptCommitment::Predicate(_) => {}
             }
         }
 
@@ -149,6 +159,47 @@ impl TranscriptProof {
             auth.union_mut(&expected.idx);
         }
 
+        for MaskedPlaintextHashSecret {
+            direction,
+            idx,
+            alg,
+            blinder,
+        } in self.masked_hash_secrets
+        {
+            let hasher = provider.hash.get(&alg).map_err(|_| {
+                TranscriptProofError::new(
+                    ErrorKind::Hash,
+                    format!("masked hash opening has unknown algorithm: {alg}"),
+                )
+            })?;
+
+            let (plaintext, auth) = match direction {
+                Direction::Sent => (self.transcript.sent_unsafe(), &mut total_auth_sent),
+                Direction::Received => (self.transcript.received_unsafe(), &mut total_auth_recv),
+            };
+
+            if idx.end() > plaintext.len() {
+                return Err(TranscriptProofError::new(
+                    ErrorKind::Hash,
+                    "masked hash opening index is out of bounds",
+                ));
+            }
+
+            let expected = MaskedPlaintextHash {
+                direction,
+                hash: hash_masked_plaintext(hasher, plaintext, &idx, &blinder),
+            };
+
+            if !masked_hash_commitments.contains(&expected) {
+                return Err(TranscriptProofError::new(
+                    ErrorKind::Hash,
+                    "masked hash opening does not match any commitment",
+                ));
+            }
+
+            auth.union_mut(&idx);
+        }
+
         // Assert that all the authenticated data are covered by the proof.
         if &total_auth_sent != self.transcript.sent_authed()
             || &total_auth_recv != self.transcript.received_authed()
@@ -161,6 +212,135 @@ impl TranscriptProof {
 
         Ok(self.transcript)
     }
+
+    /// Returns a proof which discloses only the given ranges of the
+    /// transcript.
+    ///
+    /// The ranges must be disclosed by this proof, and each commitment opened
+    /// by the proof must be either fully revealed or fully hidden.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - The crypto provider.
+    /// * `commitment` - The encoding commitment, if the proof contains an
+    ///   encoding proof.
+    /// * `sent` - The ranges of the sent data to reveal.
+    /// * `recv` - The ranges of the received data to reveal.
+    pub(crate) fn narrow(
+        &self,
+        provider: &CryptoProvider,
+        commitment: Option<&EncodingCommitment>,
+        sent: &Idx,
+        recv: &Idx,
+    ) -> Result<Self, TranscriptProofError> {
+        let reveal = |direction: Direction| match direction {
+            Direction::Sent => sent,
+            Direction::Received => recv,
+        };
+
+        for direction in [Direction::Sent, Direction::Received] {
+            let authed = match direction {
+                Direction::Sent => self.transcript.sent_authed(),
+                Direction::Received => self.transcript.received_authed(),
+            };
+
+            if !reveal(direction).is_subset(authed) {
+                let missing = reveal(direction).difference(authed);
+                return Err(TranscriptProofError::new(
+                    ErrorKind::Index,
+                    format!("{direction} data {missing} is not disclosed by the proof"),
+                ));
+            }
+        }
+
+        // Commitments can only be opened as a whole.
+        let opened = self
+            .encoding_proof
+            .iter()
+            .flat_map(|proof| proof.opened())
+            .chain(
+                self.hash_secrets
+                    .iter()
+                    .map(|secret| (secret.direction, &secret.idx)),
+            )
+            .chain(
+                self.masked_hash_secrets
+                    .iter()
+                    .map(|secret| (secret.direction, &secret.idx)),
+            );
+
+        for (direction, idx) in opened {
+            let hidden = idx.difference(reveal(direction));
+            if !hidden.is_empty() && hidden != *idx {
+                return Err(TranscriptProofError::new(
+                    ErrorKind::Alignment,
+                    format!(
+                        "{direction} commitment {idx} would only be partially revealed, \
+                         the revealed ranges must align to the committed ranges"
+                    ),
+                ));
+            }
+        }
+
+        let is_revealed =
+            |direction: Direction, idx: &Idx| !idx.is_empty() && idx.is_subset(reveal(direction));
+
+        let encoding_proof = match &self.encoding_proof {
+            Some(proof) if proof.opened().any(|(dir, idx)| is_revealed(dir, idx)) => {
+                let commitment = commitment.ok_or_else(|| {
+                    TranscriptProofError::new(
+                        ErrorKind::Encoding,
+                        "contains an encoding proof but missing encoding commitment",
+                    )
+                })?;
+
+                Some(proof.hide(
+                    provider,
+                    commitment,
+                    self.transcript.sent_unsafe(),
+                    self.transcript.received_unsafe(),
+                    |dir, idx| !is_revealed(dir, idx),
+                )?)
+            }
+            _ => None,
+        };
+
+        let hash_secrets = self
+            .hash_secrets
+            .iter()
+            .filter(|secret| is_revealed(secret.direction, &secret.idx))
+            .cloned()
+            .collect();
+
+        let masked_hash_secrets = self
+            .masked_hash_secrets
+            .iter()
+            .filter(|secret| is_revealed(secret.direction, &secret.idx))
+            .cloned()
+            .collect();
+
+        let mut transcript =
+            PartialTranscript::new(self.transcript.len_sent(), self.transcript.len_received());
+        for (direction, idx) in [(Direction::Sent, sent), (Direction::Received, recv)] {
+            let data = match direction {
+                Direction::Sent => self.transcript.sent_unsafe(),
+                Direction::Received => self.transcript.received_unsafe(),
+            };
+
+            for range in idx.iter_ranges() {
+                let seq = Subsequence::new(Idx::new(range.clone()), data[range].to_vec())
+                    .expect("subsequence is the length of the range");
+                transcript.union_subsequence(direction, &seq);
+            }
+        }
+
+        Ok(Self {
+            transcript,
+            encoding_proof,
+            hash_secrets,
+            masked_hash_secrets,
+        })
+    }
 }
 
 /// Error for [`TranscriptProof`].
@@ -187,6 +367,8 @@ enum ErrorKind {
     Encoding,
     Hash,
     Proof,
+    Index,
+    Alignment,
 }
 
 impl fmt::Display for TranscriptProofError {
@@ -197,6 +379,8 @@ impl fmt::Display for TranscriptProofError {
             ErrorKind::Encoding => f.write_str("encoding error")?,
             ErrorKind::Hash => f.write_str("hash error")?,
             ErrorKind::Proof => f.write_str("proof error")?,
+            ErrorKind::Index => f.write_str("index error")?,
+            ErrorKind::Alignment => f.write_str("alignment error")?,
         }
 
         if let Some(source) = &self.source {
@@ -254,6 +438,7 @@ pub struct TranscriptProofBuilder<'a> {
     transcript: &'a Transcript,
     encoding_tree: Option<&'a EncodingTree>,
     hash_secrets: Vec<&'a PlaintextHashSecret>,
+    masked_hash_secrets: Vec<&'a MaskedPlaintextHashSecret>,
     committed_sent: Idx,
     committed_recv: Idx,
     query_idx: QueryIdx,
@@ -270,6 +455,7 @@ impl<'a> TranscriptProofBuilder<'a> {
 
         let mut encoding_tree = None;
         let mut hash_secrets = Vec::new();
+        let mut masked_hash_secrets = Vec::new();
         for secret in secrets {
             match secret {
                 TranscriptSecret::Encoding(tree) => {
@@ -284,6 +470,13 @@ impl<'a> TranscriptProofBuilder<'a> {
                     }
                     hash_secrets.push(hash);
                 }
+                TranscriptSecret::MaskedHash(hash) => {
+                    match hash.direction {
+                        Direction::Sent => committed_sent.union_mut(&hash.idx),
+                        Direction::Received => committed_recv.union_mut(&hash.idx),
+                    }
+                    masked_hash_secrets.push(hash);
+                }
             }
         }
 
@@ -292,6 +485,7 @@ impl<'a> TranscriptProofBuilder<'a> {
             transcript,
             encoding_tree,
             hash_secrets,
+            masked_hash_secrets,
             committed_sent,
             committed_recv,
             query_idx: QueryIdx::new(),
@@ -387,6 +581,7 @@ impl<'a> TranscriptProofBuilder<'a> {
                 .to_partial(self.query_idx.sent.clone(), self.query_idx.recv.clone()),
             encoding_proof: None,
             hash_secrets: Vec::new(),
+            masked_hash_secrets: Vec::new(),
         };
         let mut uncovered_query_idx = self.query_idx.clone();
         let mut commitment_kinds_iter = self.commitment_kinds.iter();
@@ -471,6 +666,32 @@ impl<'a> TranscriptProofBuilder<'a> {
                                 .map(|s| PlaintextHashSecret::clone(s)),
                         );
                     }
+                    TranscriptCommitmentKind::MaskedHash { alg } => {
+                        let (sent_hashes, sent_uncovered) =
+                            uncovered_query_idx.sent.as_range_set().cover_by(
+                                self.masked_hash_secrets.iter().filter(|hash| {
+                                    hash.direction == Direction::Sent && &hash.alg == alg
+                                }),
+                                |hash| &hash.idx.0,
+                            );
+                        uncovered_query_idx.sent = Idx(sent_uncovered);
+
+                        let (recv_hashes, recv_uncovered) =
+                            uncovered_query_idx.recv.as_range_set().cover_by(
+                                self.masked_hash_secrets.iter().filter(|hash| {
+                                    hash.direction == Direction::Received && &hash.alg == alg
+                                }),
+                                |hash| &hash.idx.0,
+                            );
+                        uncovered_query_idx.recv = Idx(recv_uncovered);
+
+                        transcript_proof.masked_hash_secrets.extend(
+                            sent_hashes
+                                .into_iter()
+                                .chain(recv_hashes)
+                                .map(|s| MaskedPlaintextHashSecret::clone(s)),
+                        );
+                    }
                     #[allow(unreachable_patterns)]
                     kind => {
                         return Err(TranscriptProofBuilderError::new(
@@ -569,7 +790,9 @@ mod tests {
     use tlsn_data_fixtures::http::{request::GET_WITH_HEADER, response::OK_JSON};
 
     use crate::{
-        fixtures::{encoding_provider, request_fixture, ConnectionFixture, RequestFixture},
+        fixtures::{
+            encoder_secret, encoding_provider, request_fixture, ConnectionFixture, RequestFixture,
+        },
         hash::{Blake3, Blinder, HashAlgId},
         transcript::TranscriptCommitConfigBuilder,
     };
@@ -681,6 +904,74 @@ mod tests {
         );
     }
 
+    #[rstest]
+    fn test_reveal_with_masked_hash_commitment() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let provider = CryptoProvider::default();
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+
+        let direction = Direction::Received;
+        let idx = Idx::new(5..10);
+        let blinder: Blinder = rng.random();
+        let alg = HashAlgId::SHA256;
+        let hasher = provider.hash.get(&alg).unwrap();
+
+        let commitment = MaskedPlaintextHash {
+            direction,
+            hash: hash_masked_plaintext(hasher, transcript.received(), &idx, &blinder),
+        };
+
+        let secret = MaskedPlaintextHashSecret {
+            direction,
+            idx: idx.clone(),
+            alg,
+            blinder,
+        };
+
+        let secrets = vec![TranscriptSecret::MaskedHash(secret.clone())];
+        let mut builder = TranscriptProofBuilder::new(&transcript, &secrets);
+
+        builder.commitment_kinds(&[TranscriptCommitmentKind::MaskedHash { alg }]);
+        builder.reveal_recv(&(5..10)).unwrap();
+
+        let transcript_proof = builder.build().unwrap();
+
+        let partial_transcript = transcript_proof
+            .verify_with_provider(
+                &provider,
+                &transcript.length(),
+                &[TranscriptCommitment::MaskedHash(commitment.clone())],
+            )
+            .unwrap();
+
+        assert_eq!(
+            partial_transcript.received_unsafe()[5..10],
+            transcript.received()[5..10]
+        );
+
+        // Opening the commitment with different ranges must fail.
+        let secrets = vec![TranscriptSecret::MaskedHash(MaskedPlaintextHashSecret {
+            idx: Idx::new(4..10),
+            ..secret
+        })];
+        let mut builder = TranscriptProofBuilder::new(&transcript, &secrets);
+
+        builder.commitment_kinds(&[TranscriptCommitmentKind::MaskedHash { alg }]);
+        builder.reveal_recv(&(4..10)).unwrap();
+
+        let err = builder
+            .build()
+            .unwrap()
+            .verify_with_provider(
+                &provider,
+                &transcript.length(),
+                &[TranscriptCommitment::MaskedHash(commitment)],
+            )
+            .unwrap_err();
+
+        assert!(matches!(err.kind, ErrorKind::Hash));
+    }
+
     #[rstest]
     fn test_reveal_with_inconsistent_hash_commitment() {
         let mut rng = rand::rngs::StdRng::seed_from_u64(0);
@@ -908,4 +1199,105 @@ mod tests {
             }
         }
     }
+
+    #[rstest]
+    fn test_narrow() {
+        let provider = CryptoProvider::default();
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+
+        let idxs = [
+            (Direction::Sent, Idx::new(0..10)),
+            (Direction::Sent, Idx::new(10..20)),
+            (Direction::Received, Idx::new(0..10)),
+        ];
+        let tree = EncodingTree::new(
+            &Blake3::default(),
+            &idxs,
+            &encoding_provider(GET_WITH_HEADER, OK_JSON),
+        )
+        .unwrap();
+        let commitment = EncodingCommitment {
+            root: tree.root(),
+            secret: encoder_secret(),
+        };
+
+        let secrets = vec![TranscriptSecret::Encoding(tree)];
+        let mut builder = TranscriptProofBuilder::new(&transcript, &secrets);
+        builder
+            .commitment_kinds(&[TranscriptCommitmentKind::Encoding])
+            .reveal_sent(&(0..20))
+            .unwrap()
+            .reveal_recv(&(0..10))
+            .unwrap();
+        let proof = builder.build().unwrap();
+
+        let narrowed = proof
+            .narrow(
+                &provider,
+                Some(&commitment),
+                &Idx::new(10..20),
+                &Idx::empty(),
+            )
+            .unwrap();
+
+        let partial_transcript = narrowed
+            .verify_with_provider(
+                &provider,
+                &transcript.length(),
+                &[TranscriptCommitment::Encoding(commitment.clone())],
+            )
+            .unwrap();
+
+        assert_eq!(partial_transcript.sent_authed(), &Idx::new(10..20));
+        assert!(partial_transcript.received_authed().is_empty());
+        assert_eq!(
+            partial_transcript.sent_unsafe()[10..20],
+            transcript.sent()[10..20]
+        );
+        // Data which is no longer disclosed is removed.
+        assert!(partial_transcript.sent_unsafe()[0..10]
+            .iter()
+            .all(|b| *b == 0));
+    }
+
+    #[rstest]
+    fn test_narrow_misaligned() {
+        let provider = CryptoProvider::default();
+        let transcript = Transcript::new(GET_WITH_HEADER, OK_JSON);
+
+        let idxs = [(Direction::Sent, Idx::new(0..10))];
+        let tree = EncodingTree::new(
+            &Blake3::default(),
+            &idxs,
+            &encoding_provider(GET_WITH_HEADER, OK_JSON),
+        )
+        .unwrap();
+        let commitment = EncodingCommitment {
+            root: tree.root(),
+            secret: encoder_secret(),
+        };
+
+        let secrets = vec![TranscriptSecret::Encoding(tree)];
+        let mut builder = TranscriptProofBuilder::new(&transcript, &secrets);
+        builder
+            .commitment_kinds(&[TranscriptCommitmentKind::Encoding])
+            .reveal_sent(&(0..10))
+            .unwrap();
+        let proof = builder.build().unwrap();
+
+        let err = proof
+            .narrow(&provider, Some(&commitment), &Idx::new(0..5), &Idx::empty())
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Alignment));
+
+        let err = proof
+            .narrow(
+                &provider,
+                Some(&commitment),
+                &Idx::new(0..20),
+                &Idx::empty(),
+            )
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Index));
+    }
 }
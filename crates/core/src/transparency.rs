@@ -0,0 +1,868 @@
+//! Transparency log of attestations.
+//!
+//! A Notary can append the [`Header`] of every attestation it signs to an
+//! append-only Merkle log, so that relying parties can detect a Notary which
+//! silently issues conflicting attestations.
+//!
+//! The log follows the construction of
+//! [RFC 9162](https://www.rfc-editor.org/rfc/rfc9162#section-2.1): leaves and
+//! nodes are hashed with distinct prefixes, and the tree is not padded to a
+//! power of two.
+//!
+//! The Notary periodically signs the root of the log in a [`SignedTreeHead`].
+//! An [`InclusionProof`] shows that an attestation is in the log of a given
+//! tree head, and a [`ConsistencyProof`] shows that a tree head extends an
+//! older one. Verifiers which exchange the tree heads they have seen, and check
+//! that they are consistent, can detect a Notary presenting different logs to
+//! different parties.
+//!
+//! A Prover can attach a [`TransparencyProof`] to a
+//! [`Presentation`](crate::presentation::Presentation), which is verified
+//! alongside the attestation.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attestation::Header,
+    hash::{Hash, HashAlgId, HashAlgorithm},
+    serialize::CanonicalSerialize,
+    signing::{Signature, Signer, VerifyingKey},
+    CryptoProvider,
+};
+
+/// Domain separator of the tree heads signed by the Notary.
+const DOMAIN: &[u8] = b"tlsn/transparency/sth";
+/// Prefix of leaf hashes.
+const LEAF_PREFIX: &[u8] = &[0x00];
+/// Prefix of node hashes.
+const NODE_PREFIX: &[u8] = &[0x01];
+
+/// Head of a transparency log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeHead {
+    /// Hash algorithm of the log.
+    pub hash_alg: HashAlgId,
+    /// Number of leaves in the log.
+    pub size: u64,
+    /// Merkle root of the log.
+    pub root: Hash,
+    /// Time at which the head was issued, in seconds since the UNIX epoch.
+    pub timestamp: u64,
+}
+
+impl TreeHead {
+    /// Signs the tree head.
+    ///
+    /// # Arguments
+    ///
+    /// * `signer` - Signer of the Notary.
+    pub fn sign(self, signer: &dyn Signer) -> Result<SignedTreeHead, TransparencyError> {
+        let issuer = signer.verifying_key();
+        let signature = signer
+            .sign(&message(&issuer, &self))
+            .map_err(|e| TransparencyError::new(ErrorKind::Signature, e))?;
+
+        Ok(SignedTreeHead {
+            head: self,
+            issuer,
+            signature,
+        })
+    }
+}
+
+/// A [`TreeHead`] signed by a Notary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTreeHead {
+    head: TreeHead,
+    issuer: VerifyingKey,
+    signature: Signature,
+}
+
+impl SignedTreeHead {
+    /// Returns the tree head, which is not verified.
+    pub fn head(&self) -> &TreeHead {
+        &self.head
+    }
+
+    /// Returns the verifying key of the Notary which signed the tree head.
+    pub fn issuer(&self) -> &VerifyingKey {
+        &self.issuer
+    }
+
+    /// Verifies the signature of the issuer, returning the tree head.
+    ///
+    /// This does not check whether the issuer is trusted.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    pub fn verify(&self, provider: &CryptoProvider) -> Result<&TreeHead, TransparencyError> {
+        let verifier = provider
+            .signature
+            .get(&self.signature.alg)
+            .map_err(|e| TransparencyError::new(ErrorKind::Provider, e))?;
+
+        verifier
+            .verify(
+                &self.issuer,
+                &message(&self.issuer, &self.head),
+                &self.signature.data,
+            )
+            .map_err(|e| TransparencyError::new(ErrorKind::Signature, e))?;
+
+        Ok(&self.head)
+    }
+}
+
+fn message(issuer: &VerifyingKey, head: &TreeHead) -> Vec<u8> {
+    [DOMAIN, &CanonicalSerialize::serialize(&(issuer, head))].concat()
+}
+
+/// Proof that an attestation is included in a transparency log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// Index of the attestation in the log.
+    pub leaf_index: u64,
+    /// Size of the log the proof is for.
+    pub tree_size: u64,
+    /// Hashes of the audit path, from the leaf to the root.
+    pub path: Vec<Hash>,
+}
+
+impl InclusionProof {
+    /// Verifies that the attestation with the given header is included in the
+    /// log of the tree head.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `head` - Head of the log.
+    /// * `header` - Header of the attestation.
+    pub fn verify(
+        &self,
+        provider: &CryptoProvider,
+        head: &TreeHead,
+        header: &Header,
+    ) -> Result<(), TransparencyError> {
+        let hasher = hasher(provider, head.hash_alg)?;
+
+        if self.tree_size != head.size {
+            return Err(TransparencyError::new(
+                ErrorKind::Proof,
+                "inclusion proof is for another tree size",
+            ));
+        }
+
+        if self.leaf_index >= self.tree_size {
+            return Err(TransparencyError::new(
+                ErrorKind::Proof,
+                "leaf index is out of bounds",
+            ));
+        }
+
+        let mut fn_ = self.leaf_index;
+        let mut sn = self.tree_size - 1;
+        let mut r = leaf_hash(hasher, header);
+
+        for p in &self.path {
+            if sn == 0 {
+                return Err(TransparencyError::new(
+                    ErrorKind::Proof,
+                    "inclusion proof is too long",
+                ));
+            }
+
+            if fn_ & 1 == 1 || fn_ == sn {
+                r = node_hash(hasher, p, &r);
+                while fn_ & 1 == 0 && fn_ != 0 {
+                    fn_ >>= 1;
+                    sn >>= 1;
+                }
+            } else {
+                r = node_hash(hasher, &r, p);
+            }
+
+            fn_ >>= 1;
+            sn >>= 1;
+        }
+
+        if sn != 0 || r != head.root {
+            return Err(TransparencyError::new(
+                ErrorKind::Proof,
+                "invalid inclusion proof",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Proof that a transparency log is an extension of an older version of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    /// Size of the older log.
+    pub old_size: u64,
+    /// Size of the newer log.
+    pub new_size: u64,
+    /// Hashes of the proof.
+    pub path: Vec<Hash>,
+}
+
+impl ConsistencyProof {
+    /// Verifies that the log of the new tree head extends the log of the old
+    /// tree head.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `old` - Older head of the log.
+    /// * `new` - Newer head of the log.
+    pub fn verify(
+        &self,
+        provider: &CryptoProvider,
+        old: &TreeHead,
+        new: &TreeHead,
+    ) -> Result<(), TransparencyError> {
+        if old.hash_alg != new.hash_alg {
+            return Err(TransparencyError::new(
+                ErrorKind::Proof,
+                "tree heads use different hash algorithms",
+            ));
+        }
+
+        if self.old_size != old.size || self.new_size != new.size {
+            return Err(TransparencyError::new(
+                ErrorKind::Proof,
+                "consistency proof is for other tree sizes",
+            ));
+        }
+
+        let hasher = hasher(provider, new.hash_alg)?;
+        let invalid = || TransparencyError::new(ErrorKind::Proof, "invalid consistency proof");
+
+        if self.old_size > self.new_size {
+            return Err(invalid());
+        }
+
+        // Every log extends the empty log, and itself.
+        if self.old_size == 0 || self.old_size == self.new_size {
+            return if self.path.is_empty() && (self.old_size == 0 || old.root == new.root) {
+                Ok(())
+            } else {
+                Err(invalid())
+            };
+        }
+
+        let mut path = self.path.iter();
+        let first = if self.old_size.is_power_of_two() {
+            old.root
+        } else {
+            *path.next().ok_or_else(invalid)?
+        };
+
+        let mut fn_ = self.old_size - 1;
+        let mut sn = self.new_size - 1;
+        while fn_ & 1 == 1 {
+            fn_ >>= 1;
+            sn >>= 1;
+        }
+
+        let mut fr = first;
+        let mut sr = first;
+        for c in path {
+            if sn == 0 {
+                return Err(invalid());
+            }
+
+            if fn_ & 1 == 1 || fn_ == sn {
+                fr = node_hash(hasher, c, &fr);
+                sr = node_hash(hasher, c, &sr);
+                while fn_ & 1 == 0 && fn_ != 0 {
+                    fn_ >>= 1;
+                    sn >>= 1;
+                }
+            } else {
+                sr = node_hash(hasher, &sr, c);
+            }
+
+            fn_ >>= 1;
+            sn >>= 1;
+        }
+
+        if sn != 0 || fr != old.root || sr != new.root {
+            return Err(invalid());
+        }
+
+        Ok(())
+    }
+}
+
+/// Proof that an attestation is included in the transparency log of its
+/// Notary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransparencyProof {
+    /// Tree head of the log, signed by the Notary.
+    pub tree_head: SignedTreeHead,
+    /// Inclusion proof of the attestation.
+    pub inclusion: InclusionProof,
+}
+
+impl TransparencyProof {
+    /// Verifies the proof, returning the tree head.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `header` - Header of the attestation.
+    /// * `verifying_key` - Verifying key of the attestation, which must have
+    ///   signed the tree head.
+    pub fn verify(
+        &self,
+        provider: &CryptoProvider,
+        header: &Header,
+        verifying_key: &VerifyingKey,
+    ) -> Result<TreeHead, TransparencyError> {
+        if self.tree_head.issuer() != verifying_key {
+            return Err(TransparencyError::new(
+                ErrorKind::Issuer,
+                "tree head is not signed by the key of the attestation",
+            ));
+        }
+
+        let head = self.tree_head.verify(provider)?;
+        self.inclusion.verify(provider, head, header)?;
+
+        Ok(head.clone())
+    }
+}
+
+/// An append-only transparency log of attestations.
+///
+/// The roots of the complete subtrees are kept alongside the leaves, so that
+/// appending takes `O(log n)` hashes and heads and proofs take `O(log² n)`
+/// hashes.
+#[derive(Debug, Clone)]
+pub struct TransparencyLog {
+    hash_alg: HashAlgId,
+    /// Roots of the complete subtrees by height, `nodes[h][i]` being the root
+    /// of the leaves `i * 2^h..(i + 1) * 2^h`. The leaves are `nodes[0]`.
+    nodes: Vec<Vec<Hash>>,
+}
+
+impl TransparencyLog {
+    /// Creates a new empty log.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash_alg` - Hash algorithm of the log.
+    pub fn new(hash_alg: HashAlgId) -> Self {
+        Self {
+            hash_alg,
+            nodes: vec![Vec::new()],
+        }
+    }
+
+    /// Returns the hash algorithm of the log.
+    pub fn hash_alg(&self) -> HashAlgId {
+        self.hash_alg
+    }
+
+    /// Returns the number of attestations in the log.
+    pub fn len(&self) -> u64 {
+        self.nodes[0].len() as u64
+    }
+
+    /// Returns `true` if the log is empty.
+    pub fn is_empty(&self) -> bool {
+        self.nodes[0].is_empty()
+    }
+
+    /// Appends an attestation to the log, returning its index.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `header` - Header of the attestation.
+    pub fn append(
+        &mut self,
+        provider: &CryptoProvider,
+        header: &Header,
+    ) -> Result<u64, TransparencyError> {
+        let hasher = hasher(provider, self.hash_alg)?;
+        let leaf_index = self.len();
+
+        // Complete the subtrees which the leaf is the last leaf of.
+        let mut hash = leaf_hash(hasher, header);
+        let mut index = leaf_index as usize;
+        for height in 0.. {
+            if height == self.nodes.len() {
+                self.nodes.push(Vec::new());
+            }
+            self.nodes[height].push(hash);
+
+            if index & 1 == 0 {
+                break;
+            }
+
+            hash = node_hash(
+                hasher,
+                &self.nodes[height][index - 1],
+                &self.nodes[height][index],
+            );
+            index >>= 1;
+        }
+
+        Ok(leaf_index)
+    }
+
+    /// Truncates the log to the given size, e.g. to roll back an attestation
+    /// which could not be persisted.
+    ///
+    /// Heads and proofs issued for a larger size are no longer consistent with
+    /// the log, so this must only be used for attestations which were not
+    /// published.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - Size of the log.
+    pub fn truncate(&mut self, size: u64) {
+        for (height, nodes) in self.nodes.iter_mut().enumerate() {
+            nodes.truncate((size >> height) as usize);
+        }
+    }
+
+    /// Returns the head of the log.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `timestamp` - Time at which the head is issued, in seconds since the
+    ///   UNIX epoch.
+    pub fn tree_head(
+        &self,
+        provider: &CryptoProvider,
+        timestamp: u64,
+    ) -> Result<TreeHead, TransparencyError> {
+        let hasher = hasher(provider, self.hash_alg)?;
+
+        Ok(TreeHead {
+            hash_alg: self.hash_alg,
+            size: self.len(),
+            root: self.root(hasher, 0, self.len() as usize),
+            timestamp,
+        })
+    }
+
+    /// Returns the proof that the attestation at the index is included in the
+    /// log of the given size.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `leaf_index` - Index of the attestation.
+    /// * `tree_size` - Size of the log.
+    pub fn inclusion_proof(
+        &self,
+        provider: &CryptoProvider,
+        leaf_index: u64,
+        tree_size: u64,
+    ) -> Result<InclusionProof, TransparencyError> {
+        let hasher = hasher(provider, self.hash_alg)?;
+
+        if leaf_index >= tree_size || tree_size > self.len() {
+            return Err(TransparencyError::new(
+                ErrorKind::Log,
+                format!(
+                    "leaf {leaf_index} is not in a log of size {tree_size}, the log has {} leaves",
+                    self.len()
+                ),
+            ));
+        }
+
+        Ok(InclusionProof {
+            leaf_index,
+            tree_size,
+            path: self.inclusion_path(hasher, leaf_index as usize, 0, tree_size as usize),
+        })
+    }
+
+    /// Returns the proof that the log of the new size extends the log of the
+    /// old size.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Cryptography provider.
+    /// * `old_size` - Size of the older log.
+    /// * `new_size` - Size of the newer log.
+    pub fn consistency_proof(
+        &self,
+        provider: &CryptoProvider,
+        old_size: u64,
+        new_size: u64,
+    ) -> Result<ConsistencyProof, TransparencyError> {
+        let hasher = hasher(provider, self.hash_alg)?;
+
+        if old_size > new_size || new_size > self.len() {
+            return Err(TransparencyError::new(
+                ErrorKind::Log,
+                format!(
+                    "no consistency proof from size {old_size} to {new_size}, the log has {} leaves",
+                    self.len()
+                ),
+            ));
+        }
+
+        let path = if old_size == 0 {
+            Vec::new()
+        } else {
+            self.subproof(hasher, old_size as usize, 0, new_size as usize, true)
+        };
+
+        Ok(ConsistencyProof {
+            old_size,
+            new_size,
+            path,
+        })
+    }
+
+    /// Returns the root of the `n` leaves starting at `offset`.
+    ///
+    /// The range must be a subtree of a log, so that its complete subtrees
+    /// are aligned with the ones kept in the log.
+    fn root(&self, hasher: &dyn HashAlgorithm, offset: usize, n: usize) -> Hash {
+        if n == 0 {
+            return hasher.hash(&[]);
+        }
+
+        if n.is_power_of_two() {
+            let height = n.trailing_zeros() as usize;
+            return self.nodes[height][offset >> height];
+        }
+
+        let k = split(n);
+        node_hash(
+            hasher,
+            &self.root(hasher, offset, k),
+            &self.root(hasher, offset + k, n - k),
+        )
+    }
+
+    /// Returns the audit path of the leaf `m` of the subtree of `n` leaves
+    /// starting at `offset`.
+    fn inclusion_path(
+        &self,
+        hasher: &dyn HashAlgorithm,
+        m: usize,
+        offset: usize,
+        n: usize,
+    ) -> Vec<Hash> {
+        if n <= 1 {
+            return Vec::new();
+        }
+
+        let k = split(n);
+        if m < k {
+            let mut path = self.inclusion_path(hasher, m, offset, k);
+            path.push(self.root(hasher, offset + k, n - k));
+            path
+        } else {
+            let mut path = self.inclusion_path(hasher, m - k, offset + k, n - k);
+            path.push(self.root(hasher, offset, k));
+            path
+        }
+    }
+
+    /// Returns the consistency proof of the first `m` leaves of the subtree of
+    /// `n` leaves starting at `offset`.
+    fn subproof(
+        &self,
+        hasher: &dyn HashAlgorithm,
+        m: usize,
+        offset: usize,
+        n: usize,
+        complete: bool,
+    ) -> Vec<Hash> {
+        if m == n {
+            return if complete {
+                Vec::new()
+            } else {
+                vec![self.root(hasher, offset, n)]
+            };
+        }
+
+        let k = split(n);
+        if m <= k {
+            let mut path = self.subproof(hasher, m, offset, k, complete);
+            path.push(self.root(hasher, offset + k, n - k));
+            path
+        } else {
+            let mut path = self.subproof(hasher, m - k, offset + k, n - k, false);
+            path.push(self.root(hasher, offset, k));
+            path
+        }
+    }
+}
+
+fn hasher(
+    provider: &CryptoProvider,
+    alg: HashAlgId,
+) -> Result<&(dyn HashAlgorithm + Send + Sync), TransparencyError> {
+    provider
+        .hash
+        .get(&alg)
+        .map_err(|e| TransparencyError::new(ErrorKind::Provider, e))
+}
+
+fn leaf_hash(hasher: &dyn HashAlgorithm, header: &Header) -> Hash {
+    hasher.hash_prefixed(LEAF_PREFIX, &CanonicalSerialize::serialize(header))
+}
+
+fn node_hash(hasher: &dyn HashAlgorithm, left: &Hash, right: &Hash) -> Hash {
+    let mut data = Vec::from(*left);
+    data.extend(Vec::from(*right));
+    hasher.hash_prefixed(NODE_PREFIX, &data)
+}
+
+/// Returns the largest power of two smaller than `n`, which must be at least 2.
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// Error for the [transparency log](crate::transparency).
+#[derive(Debug, thiserror::Error)]
+pub struct TransparencyError {
+    kind: ErrorKind,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl TransparencyError {
+    fn new<E>(kind: ErrorKind, source: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self {
+            kind,
+            source: Some(source.into()),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Provider,
+    Issuer,
+    Signature,
+    Proof,
+    Log,
+}
+
+impl fmt::Display for TransparencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("transparency error: ")?;
+
+        match self.kind {
+            ErrorKind::Provider => f.write_str("provider error")?,
+            ErrorKind::Issuer => f.write_str("issuer error")?,
+            ErrorKind::Signature => f.write_str("signature error")?,
+            ErrorKind::Proof => f.write_str("proof error")?,
+            ErrorKind::Log => f.write_str("log error")?,
+        }
+
+        if let Some(source) = &self.source {
+            write!(f, " caused by: {}", source)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attestation::{Uid, VERSION},
+        hash::TypedHash,
+        signing::Secp256k1Signer,
+    };
+
+    fn header(i: u8) -> Header {
+        Header {
+            id: Uid([i; 16]),
+            version: VERSION,
+            root: TypedHash {
+                alg: HashAlgId::SHA256,
+                value: Hash::default(),
+            },
+        }
+    }
+
+    fn log(provider: &CryptoProvider, size: u8) -> TransparencyLog {
+        let mut log = TransparencyLog::new(HashAlgId::SHA256);
+        for i in 0..size {
+            assert_eq!(log.append(provider, &header(i)).unwrap(), i as u64);
+        }
+        log
+    }
+
+    /// Returns the head of a log of the given size.
+    fn head(provider: &CryptoProvider, size: u8) -> TreeHead {
+        log(provider, size).tree_head(provider, 0).unwrap()
+    }
+
+    /// Computes the root of the leaves as specified in RFC 9162.
+    fn expected_root(hasher: &dyn HashAlgorithm, leaves: &[Hash]) -> Hash {
+        match leaves {
+            [] => hasher.hash(&[]),
+            [leaf] => *leaf,
+            _ => {
+                let k = split(leaves.len());
+                node_hash(
+                    hasher,
+                    &expected_root(hasher, &leaves[..k]),
+                    &expected_root(hasher, &leaves[k..]),
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_root() {
+        let provider = CryptoProvider::default();
+        let head = log(&provider, 0).tree_head(&provider, 0).unwrap();
+
+        // SHA-256 of the empty string.
+        assert_eq!(
+            Vec::from(head.root),
+            hex::decode("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_root() {
+        let provider = CryptoProvider::default();
+        let hasher = hasher(&provider, HashAlgId::SHA256).unwrap();
+
+        for size in 0..=33u8 {
+            let leaves = (0..size)
+                .map(|i| leaf_hash(hasher, &header(i)))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                head(&provider, size).root,
+                expected_root(hasher, &leaves),
+                "size {size}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_inclusion_proof() {
+        let provider = CryptoProvider::default();
+        let log = log(&provider, 11);
+
+        for size in 1..=11u64 {
+            let head = head(&provider, size as u8);
+
+            for i in 0..size {
+                let proof = log.inclusion_proof(&provider, i, size).unwrap();
+                proof.verify(&provider, &head, &header(i as u8)).unwrap();
+
+                // Another attestation is not included at the index.
+                assert!(proof
+                    .verify(&provider, &head, &header(i as u8 + 1))
+                    .is_err());
+            }
+        }
+
+        assert!(log.inclusion_proof(&provider, 11, 11).is_err());
+        assert!(log.inclusion_proof(&provider, 0, 12).is_err());
+    }
+
+    #[test]
+    fn test_truncate() {
+        let provider = CryptoProvider::default();
+        let mut log = log(&provider, 5);
+
+        log.truncate(3);
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.tree_head(&provider, 0).unwrap(), head(&provider, 3));
+
+        // Truncating to a larger size does nothing.
+        log.truncate(5);
+        assert_eq!(log.len(), 3);
+
+        // The log can be appended to again.
+        for i in 3..8 {
+            log.append(&provider, &header(i)).unwrap();
+        }
+        assert_eq!(log.tree_head(&provider, 0).unwrap(), head(&provider, 8));
+    }
+
+    #[test]
+    fn test_consistency_proof() {
+        let provider = CryptoProvider::default();
+        let log = log(&provider, 11);
+        let heads = (0..=11)
+            .map(|size| head(&provider, size))
+            .collect::<Vec<_>>();
+
+        for old in 0..=11 {
+            for new in old..=11 {
+                let proof = log
+                    .consistency_proof(&provider, old as u64, new as u64)
+                    .unwrap();
+                proof.verify(&provider, &heads[old], &heads[new]).unwrap();
+            }
+        }
+
+        // A log which does not extend the old one.
+        let mut forked = TransparencyLog::new(HashAlgId::SHA256);
+        for i in [0, 1, 2, 42, 4, 5, 6] {
+            forked.append(&provider, &header(i)).unwrap();
+        }
+        let forked_head = forked.tree_head(&provider, 0).unwrap();
+        let proof = forked.consistency_proof(&provider, 5, 7).unwrap();
+        assert!(proof.verify(&provider, &heads[5], &forked_head).is_err());
+
+        assert!(log.consistency_proof(&provider, 5, 4).is_err());
+        assert!(log.consistency_proof(&provider, 5, 12).is_err());
+    }
+
+    #[test]
+    fn test_transparency_proof() {
+        let provider = CryptoProvider::default();
+        let signer = Secp256k1Signer::new(&[42u8; 32]).unwrap();
+        let log = log(&provider, 5);
+
+        let proof = TransparencyProof {
+            tree_head: log
+                .tree_head(&provider, 1700000000)
+                .unwrap()
+                .sign(&signer)
+                .unwrap(),
+            inclusion: log.inclusion_proof(&provider, 3, 5).unwrap(),
+        };
+
+        let head = proof
+            .verify(&provider, &header(3), &signer.verifying_key())
+            .unwrap();
+        assert_eq!(head.size, 5);
+
+        // The tree head must be signed by the key of the attestation.
+        let other_signer = Secp256k1Signer::new(&[1u8; 32]).unwrap();
+        let err = proof
+            .verify(&provider, &header(3), &other_signer.verifying_key())
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Issuer));
+
+        // Tampering with the tree head invalidates the signature.
+        let mut tampered = proof.clone();
+        tampered.tree_head.head.timestamp += 1;
+        let err = tampered
+            .verify(&provider, &header(3), &signer.verifying_key())
+            .unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::Signature));
+    }
+}
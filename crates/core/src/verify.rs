@@ -0,0 +1,527 @@
+//! Verifier policy.
+//!
+//! A [`VerifyConfig`] describes the requirements a verifier enforces on the
+//! information disclosed by the prover.
+
+use bytes::Bytes;
+use rangeset::ToRangeSet;
+use spansy::{http::Requests, Spanned};
+
+use crate::{
+    connection::{ServerName, TranscriptLength},
+    transcript::{predicate::Predicate, Direction, Idx, PartialTranscript, TranscriptCommitment},
+};
+
+/// Configuration to verify information from the prover.
+///
+/// The configuration is a policy which the verifier enforces, rejecting the
+/// prover's payload if any requirement is not met. The default configuration
+/// has no requirements.
+#[derive(Debug, Default, Clone)]
+pub struct VerifyConfig {
+    server_identity: bool,
+    server_names: Vec<ServerName>,
+    reveal_sent: Idx,
+    reveal_recv: Idx,
+    sent_len: (Option<usize>, Option<usize>),
+    recv_len: (Option<usize>, Option<usize>),
+    http_paths: Vec<String>,
+    predicates: Vec<Predicate>,
+}
+
+impl VerifyConfig {
+    /// Creates a new builder.
+    pub fn builder() -> VerifyConfigBuilder {
+        VerifyConfigBuilder::new()
+    }
+
+    /// Returns `true` if the server identity must be proven.
+    pub fn server_identity(&self) -> bool {
+        self.server_identity || !self.server_names.is_empty()
+    }
+
+    /// Returns the accepted server names, any name is accepted if empty.
+    pub fn server_names(&self) -> &[ServerName] {
+        &self.server_names
+    }
+
+    /// Returns the ranges of the sent data which must be revealed.
+    pub fn reveal_sent(&self) -> &Idx {
+        &self.reveal_sent
+    }
+
+    /// Returns the ranges of the received data which must be revealed.
+    pub fn reveal_recv(&self) -> &Idx {
+        &self.reveal_recv
+    }
+
+    /// Returns the HTTP paths which must be requested.
+    pub fn http_paths(&self) -> &[String] {
+        &self.http_paths
+    }
+
+    /// Returns the predicates which must be proven.
+    pub fn predicates(&self) -> &[Predicate] {
+        &self.predicates
+    }
+
+    /// Checks the server name proven by the prover, if any.
+    pub fn check_server_name(&self, server_name: Option<&ServerName>) -> Result<(), PolicyError> {
+        match server_name {
+            None if self.server_identity() => Err(PolicyError::MissingServerIdentity),
+            Some(name) if !self.server_names.is_empty() && !self.server_names.contains(name) => {
+                Err(PolicyError::ServerName { name: name.clone() })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the length of the transcript.
+    pub fn check_transcript_length(&self, length: &TranscriptLength) -> Result<(), PolicyError> {
+        for (direction, len, (min, max)) in [
+            (Direction::Sent, length.sent as usize, self.sent_len),
+            (Direction::Received, length.received as usize, self.recv_len),
+        ] {
+            if min.is_some_and(|min| len < min) || max.is_some_and(|max| len > max) {
+                return Err(PolicyError::TranscriptLength {
+                    direction,
+                    len,
+                    min,
+                    max,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the data revealed by the prover, if any.
+    pub fn check_transcript(
+        &self,
+        transcript: Option<&PartialTranscript>,
+    ) -> Result<(), PolicyError> {
+        let empty = Idx::empty();
+        let (sent_authed, recv_authed) = transcript
+            .map(|transcript| (transcript.sent_authed(), transcript.received_authed()))
+            .unwrap_or((&empty, &empty));
+
+        for (direction, required, authed) in [
+            (Direction::Sent, &self.reveal_sent, sent_authed),
+            (Direction::Received, &self.reveal_recv, recv_authed),
+        ] {
+            if !required.is_subset(authed) {
+                return Err(PolicyError::MissingReveal {
+                    direction,
+                    idx: required.difference(authed),
+                });
+            }
+        }
+
+        if !self.http_paths.is_empty() {
+            let paths = transcript
+                .map(|transcript| {
+                    http_request_paths(transcript.sent_unsafe(), transcript.sent_authed())
+                })
+                .unwrap_or_default();
+
+            for path in &self.http_paths {
+                if !paths.contains(path) {
+                    return Err(PolicyError::MissingHttpPath { path: path.clone() });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the required predicates were proven.
+    pub fn check_predicates<'a>(
+        &self,
+        commitments: impl IntoIterator<Item = &'a TranscriptCommitment>,
+    ) -> Result<(), PolicyError> {
+        let proven: Vec<&Predicate> = commitments
+            .into_iter()
+            .filter_map(|commitment| match commitment {
+                TranscriptCommitment::Predicate(predicate) => Some(predicate),
+                _ => None,
+            })
+            .collect();
+
+        for predicate in &self.predicates {
+            if !proven.contains(&predicate) {
+                return Err(PolicyError::MissingPredicate {
+                    predicate: predicate.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the paths of the HTTP requests whose request line is authenticated.
+///
+/// Requests are parsed in order, so that a request body can not be mistaken
+/// for a request. Parsing stops at the first request which can not be parsed,
+/// e.g. because one of its headers was not revealed. Only the request line of
+/// that request is read, as the start of the next request is unknown.
+fn http_request_paths(data: &[u8], authed: &Idx) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut offset = 0;
+    let mut requests = Requests::new(Bytes::copy_from_slice(data));
+    while offset < data.len() {
+        let Some(Ok(request)) = requests.next() else {
+            paths.extend(request_line_path(data, offset, authed));
+            break;
+        };
+
+        if Idx::new(request.request.to_range_set()).is_subset(authed) {
+            paths.push(path(request.request.target.as_str()));
+        }
+        offset += request.span().as_bytes().len();
+    }
+
+    paths
+}
+
+/// Returns the path of the request line starting at the offset, if the line
+/// is authenticated up to and including its CRLF.
+fn request_line_path(data: &[u8], offset: usize, authed: &Idx) -> Option<String> {
+    let len = data[offset..]
+        .windows(2)
+        .position(|window| window == b"\r\n")?;
+    if !Idx::new(offset..offset + len + 2).is_subset(authed) {
+        return None;
+    }
+
+    let line = std::str::from_utf8(&data[offset..offset + len]).ok()?;
+    let mut parts = line.split(' ');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version), None)
+            if !method.is_empty() && !target.is_empty() && version.starts_with("HTTP/") =>
+        {
+            Some(path(target))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the path of a request target, without the query string.
+fn path(target: &str) -> String {
+    target.split('?').next().unwrap_or(target).to_string()
+}
+
+/// Builder for [`VerifyConfig`].
+#[derive(Debug, Default)]
+pub struct VerifyConfigBuilder {
+    config: VerifyConfig,
+}
+
+impl VerifyConfigBuilder {
+    /// Creates a new builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires the prover to prove the server identity.
+    pub fn server_identity(&mut self) -> &mut Self {
+        self.config.server_identity = true;
+        self
+    }
+
+    /// Adds an accepted server name.
+    ///
+    /// If any names are added, the prover must prove the server identity with
+    /// one of them.
+    pub fn server_name(&mut self, name: ServerName) -> &mut Self {
+        self.config.server_names.push(name);
+        self
+    }
+
+    /// Requires the given ranges of the transcript to be revealed.
+    pub fn reveal(&mut self, direction: Direction, ranges: &dyn ToRangeSet<usize>) -> &mut Self {
+        let idx = Idx::new(ranges.to_range_set());
+        match direction {
+            Direction::Sent => self.config.reveal_sent.union_mut(&idx),
+            Direction::Received => self.config.reveal_recv.union_mut(&idx),
+        }
+        self
+    }
+
+    /// Requires the given ranges of the sent data to be revealed.
+    pub fn reveal_sent(&mut self, ranges: &dyn ToRangeSet<usize>) -> &mut Self {
+        self.reveal(Direction::Sent, ranges)
+    }
+
+    /// Requires the given ranges of the received data to be revealed.
+    pub fn reveal_recv(&mut self, ranges: &dyn ToRangeSet<usize>) -> &mut Self {
+        self.reveal(Direction::Received, ranges)
+    }
+
+    /// Sets the minimum length of the sent data.
+    pub fn min_sent(&mut self, len: usize) -> &mut Self {
+        self.config.sent_len.0 = Some(len);
+        self
+    }
+
+    /// Sets the maximum length of the sent data.
+    pub fn max_sent(&mut self, len: usize) -> &mut Self {
+        self.config.sent_len.1 = Some(len);
+        self
+    }
+
+    /// Sets the minimum length of the received data.
+    pub fn min_recv(&mut self, len: usize) -> &mut Self {
+        self.config.recv_len.0 = Some(len);
+        self
+    }
+
+    /// Sets the maximum length of the received data.
+    pub fn max_recv(&mut self, len: usize) -> &mut Self {
+        self.config.recv_len.1 = Some(len);
+        self
+    }
+
+    /// Requires a request to the given HTTP path to be revealed.
+    ///
+    /// The query string of the request target is ignored.
+    pub fn http_path(&mut self, path: impl Into<String>) -> &mut Self {
+        self.config.http_paths.push(path.into());
+        self
+    }
+
+    /// Requires the given predicate to be proven.
+    pub fn predicate(&mut self, predicate: Predicate) -> &mut Self {
+        self.config.predicates.push(predicate);
+        self
+    }
+
+    /// Builds the configuration.
+    pub fn build(self) -> Result<VerifyConfig, VerifyConfigBuilderError> {
+        for (direction, (min, max)) in [
+            (Direction::Sent, self.config.sent_len),
+            (Direction::Received, self.config.recv_len),
+        ] {
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(VerifyConfigBuilderErrorRepr::InvalidLength {
+                        direction,
+                        min,
+                        max,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        Ok(self.config)
+    }
+}
+
+/// Error for [`VerifyConfigBuilder`].
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct VerifyConfigBuilderError(#[from] VerifyConfigBuilderErrorRepr);
+
+#[derive(Debug, thiserror::Error)]
+enum VerifyConfigBuilderErrorRepr {
+    #[error("minimum length is greater than maximum length ({direction}): {min} > {max}")]
+    InvalidLength {
+        direction: Direction,
+        min: usize,
+        max: usize,
+    },
+}
+
+/// A requirement of a [`VerifyConfig`] which was not met.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum PolicyError {
+    /// The server identity was not proven.
+    #[error("server identity was not proven")]
+    MissingServerIdentity,
+    /// The server name is not accepted.
+    #[error("server name is not accepted: {name}")]
+    ServerName {
+        /// The proven server name.
+        name: ServerName,
+    },
+    /// The transcript length is out of bounds.
+    #[error("transcript length is out of bounds ({direction}): {len}, min: {min:?}, max: {max:?}")]
+    TranscriptLength {
+        /// Direction of the transcript.
+        direction: Direction,
+        /// Length of the transcript.
+        len: usize,
+        /// Minimum length.
+        min: Option<usize>,
+        /// Maximum length.
+        max: Option<usize>,
+    },
+    /// Required ranges were not revealed.
+    #[error("required ranges were not revealed ({direction}): {idx:?}")]
+    MissingReveal {
+        /// Direction of the transcript.
+        direction: Direction,
+        /// The ranges which were not revealed.
+        idx: Idx,
+    },
+    /// No request to the HTTP path was revealed.
+    #[error("no request to the HTTP path was revealed: {path}")]
+    MissingHttpPath {
+        /// The HTTP path.
+        path: String,
+    },
+    /// A required predicate was not proven.
+    #[error("required predicate was not proven: {predicate}")]
+    MissingPredicate {
+        /// The predicate.
+        predicate: Predicate,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Transcript;
+
+    fn partial(sent: &[u8], sent_idx: Idx) -> PartialTranscript {
+        Transcript::new(sent, b"HTTP/1.1 200 OK\r\n\r\n").to_partial(sent_idx, Idx::empty())
+    }
+
+    #[test]
+    fn test_verify_config_default_accepts_all() {
+        let config = VerifyConfig::default();
+
+        assert!(config.check_server_name(None).is_ok());
+        assert!(config.check_transcript(None).is_ok());
+        assert!(config
+            .check_transcript_length(&TranscriptLength {
+                sent: 100,
+                received: 100
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn test_verify_config_server_name() {
+        let mut builder = VerifyConfig::builder();
+        builder.server_name(ServerName::from("example.com"));
+        let config = builder.build().unwrap();
+
+        assert_eq!(
+            config.check_server_name(None),
+            Err(PolicyError::MissingServerIdentity)
+        );
+        assert_eq!(
+            config.check_server_name(Some(&ServerName::from("evil.com"))),
+            Err(PolicyError::ServerName {
+                name: ServerName::from("evil.com")
+            })
+        );
+        assert!(config
+            .check_server_name(Some(&ServerName::from("example.com")))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_verify_config_transcript_length() {
+        let mut builder = VerifyConfig::builder();
+        builder.min_sent(10).max_recv(100);
+        let config = builder.build().unwrap();
+
+        assert!(matches!(
+            config.check_transcript_length(&TranscriptLength {
+                sent: 5,
+                received: 50
+            }),
+            Err(PolicyError::TranscriptLength {
+                direction: Direction::Sent,
+                ..
+            })
+        ));
+        assert!(matches!(
+            config.check_transcript_length(&TranscriptLength {
+                sent: 10,
+                received: 101
+            }),
+            Err(PolicyError::TranscriptLength {
+                direction: Direction::Received,
+                ..
+            })
+        ));
+
+        let mut builder = VerifyConfig::builder();
+        builder.min_sent(10).max_sent(5);
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn test_verify_config_reveal_and_http_path() {
+        let sent = b"GET /api/balance?id=1 HTTP/1.1\r\nHost: example.com\r\n\r\n";
+
+        let mut builder = VerifyConfig::builder();
+        builder.reveal_sent(&(0..4)).http_path("/api/balance");
+        let config = builder.build().unwrap();
+
+        assert!(config
+            .check_transcript(Some(&partial(sent, Idx::new(0..sent.len()))))
+            .is_ok());
+        assert_eq!(
+            config.check_transcript(Some(&partial(sent, Idx::new(2..sent.len())))),
+            Err(PolicyError::MissingReveal {
+                direction: Direction::Sent,
+                idx: Idx::new(0..2),
+            })
+        );
+
+        // The request line is only partially revealed.
+        let mut builder = VerifyConfig::builder();
+        builder.http_path("/api/balance");
+        let config = builder.build().unwrap();
+
+        assert_eq!(
+            config.check_transcript(Some(&partial(sent, Idx::new(0..20)))),
+            Err(PolicyError::MissingHttpPath {
+                path: "/api/balance".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_config_http_path_redacted_header() {
+        let sent = b"GET /api/balance?id=1 HTTP/1.1\r\nAuthorization: Bearer secret\r\nHost: example.com\r\n\r\n";
+        let token = sent.windows(6).position(|w| w == b"secret").unwrap();
+
+        let mut builder = VerifyConfig::builder();
+        builder.http_path("/api/balance");
+        let config = builder.build().unwrap();
+
+        // The request line is revealed, the token is not.
+        let mut idx = Idx::new(0..token);
+        idx.union_mut(&Idx::new(token + 6..sent.len()));
+        assert!(config.check_transcript(Some(&partial(sent, idx))).is_ok());
+
+        // Only the request line is revealed.
+        assert!(config
+            .check_transcript(Some(&partial(sent, Idx::new(0..32))))
+            .is_ok());
+
+        // The CRLF of the request line is not revealed.
+        assert_eq!(
+            config.check_transcript(Some(&partial(sent, Idx::new(0..30)))),
+            Err(PolicyError::MissingHttpPath {
+                path: "/api/balance".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_http_request_paths_skips_body() {
+        let sent = b"POST /a HTTP/1.1\r\nContent-Length: 19\r\n\r\nGET /b HTTP/1.1\r\n\r\nGET /c HTTP/1.1\r\n\r\n";
+        let paths = http_request_paths(sent, &Idx::new(0..sent.len()));
+
+        // The body of the first request looks like a request line.
+        assert_eq!(paths, vec!["/a", "/c"]);
+    }
+}
@@ -7,18 +7,23 @@ use http_body_util::{BodyExt as _, Either, Empty, Full};
 use hyper::{
     body::{Bytes, Incoming},
     client::conn::http1::Parts,
+    header::AUTHORIZATION,
     Request, Response, StatusCode,
 };
 use hyper_util::rt::TokioIo;
 use notary_common::{
-    ClientType, NotarizationSessionRequest, NotarizationSessionResponse, X_API_KEY_HEADER,
+    ClientType, InclusionProofResponse, NotarizationSessionRejection, NotarizationSessionRequest,
+    NotarizationSessionResponse, X_API_KEY_HEADER,
 };
+use serde::de::DeserializeOwned;
 use std::{
     io::Error as IoError,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
 };
+use tlsn_common::config::{NetworkSetting, ProtocolConfig};
+use tlsn_core::{attestation::Attestation, transparency::TransparencyProof, CryptoProvider};
 use tokio::{
     io::{AsyncRead, AsyncWrite, ReadBuf},
     net::TcpStream,
@@ -34,12 +39,34 @@ use tracing::{debug, error};
 use crate::error::{ClientError, ErrorKind};
 
 /// Parameters used to configure notarization.
+///
+/// These are sent to the notary server as a [`ProtocolConfig`], which is
+/// checked against the server's limits before the notarization starts. The
+/// prover must use the same configuration, see
+/// [`NotarizationRequest::protocol_config`].
 #[derive(Debug, Clone, derive_builder::Builder)]
 pub struct NotarizationRequest {
     /// Maximum number of bytes that can be sent.
     max_sent_data: usize,
+    /// Maximum number of application data records that can be sent.
+    #[builder(setter(strip_option), default)]
+    max_sent_records: Option<usize>,
+    /// Maximum number of bytes that can be decrypted online, i.e. while the
+    /// MPC-TLS connection is active.
+    #[builder(setter(strip_option), default)]
+    max_recv_data_online: Option<usize>,
     /// Maximum number of bytes that can be received.
     max_recv_data: usize,
+    /// Maximum number of application data records that can be received.
+    #[builder(setter(strip_option), default)]
+    max_recv_records: Option<usize>,
+    /// Whether the `deferred decryption` feature is toggled on from the start
+    /// of the MPC-TLS connection.
+    #[builder(setter(strip_option), default)]
+    defer_decryption_from_start: Option<bool>,
+    /// Network settings.
+    #[builder(setter(strip_option), default)]
+    network: Option<NetworkSetting>,
 }
 
 impl NotarizationRequest {
@@ -47,6 +74,35 @@ impl NotarizationRequest {
     pub fn builder() -> NotarizationRequestBuilder {
         NotarizationRequestBuilder::default()
     }
+
+    /// Returns the protocol configuration of this request, which should be
+    /// used by the prover.
+    pub fn protocol_config(&self) -> Result<ProtocolConfig, ClientError> {
+        let mut builder = ProtocolConfig::builder();
+        builder
+            .max_sent_data(self.max_sent_data)
+            .max_recv_data(self.max_recv_data);
+
+        if let Some(max_sent_records) = self.max_sent_records {
+            builder.max_sent_records(max_sent_records);
+        }
+        if let Some(max_recv_data_online) = self.max_recv_data_online {
+            builder.max_recv_data_online(max_recv_data_online);
+        }
+        if let Some(max_recv_records) = self.max_recv_records {
+            builder.max_recv_records(max_recv_records);
+        }
+        if let Some(defer_decryption_from_start) = self.defer_decryption_from_start {
+            builder.defer_decryption_from_start(defer_decryption_from_start);
+        }
+        if let Some(network) = self.network {
+            builder.network(network);
+        }
+
+        builder
+            .build()
+            .map_err(|err| ClientError::new(ErrorKind::Builder, Some(Box::new(err))))
+    }
 }
 
 /// An accepted notarization request.
@@ -137,6 +193,10 @@ pub struct NotaryClient {
     /// in notary server.
     #[builder(setter(into, strip_option), default)]
     api_key: Option<String>,
+    /// JWT used to call notary server endpoints if JWT authorization is enabled
+    /// in notary server, it is sent as a bearer token.
+    #[builder(setter(into, strip_option), default)]
+    jwt: Option<String>,
     /// The duration of notarization request timeout in seconds.
     #[builder(default = "60")]
     request_timeout: usize,
@@ -173,6 +233,61 @@ impl NotaryClient {
         &self,
         notarization_request: NotarizationRequest,
     ) -> Result<Accepted, ClientError> {
+        let notary_socket = self.connect().await?;
+
+        self.send_request(notary_socket, notarization_request)
+            .await
+            .map(|(connection, session_id)| Accepted {
+                id: session_id,
+                io: connection,
+            })
+    }
+
+    /// Fetches the proof that the attestation is in the transparency log of the
+    /// notary server, which can be included in a presentation with
+    /// [`PresentationBuilder::transparency_proof`](tlsn_core::presentation::PresentationBuilder::transparency_proof).
+    ///
+    /// The proof is verified before it is returned.
+    pub async fn transparency_proof(
+        &self,
+        attestation: &Attestation,
+    ) -> Result<TransparencyProof, ClientError> {
+        let InclusionProofResponse { proof, tree_heads } = self
+            .get(&format!(
+                "/transparency/inclusion?id={}",
+                attestation.header.id
+            ))
+            .await?;
+
+        let verifying_key = attestation.body.verifying_key();
+        let tree_head = tree_heads
+            .into_iter()
+            .find(|tree_head| tree_head.issuer() == verifying_key)
+            .ok_or_else(|| {
+                ClientError::new(
+                    ErrorKind::Transparency,
+                    Some("No tree head is signed by the key of the attestation".into()),
+                )
+            })?;
+
+        let proof = TransparencyProof {
+            tree_head,
+            inclusion: proof,
+        };
+
+        proof
+            .verify(
+                &CryptoProvider::default(),
+                &attestation.header,
+                verifying_key,
+            )
+            .map_err(|err| ClientError::new(ErrorKind::Transparency, Some(Box::new(err))))?;
+
+        Ok(proof)
+    }
+
+    /// Connects to the notary server, using TLS if it is turned on.
+    async fn connect(&self) -> Result<NotaryConnection, ClientError> {
         if self.tls {
             debug!("Setting up tls connection...");
 
@@ -202,12 +317,7 @@ impl NotaryClient {
                     ClientError::new(ErrorKind::TlsSetup, Some(Box::new(err)))
                 })?;
 
-            self.send_request(notary_tls_socket, notarization_request)
-                .await
-                .map(|(connection, session_id)| Accepted {
-                    id: session_id,
-                    io: NotaryConnection::Tls(connection),
-                })
+            Ok(NotaryConnection::Tls(notary_tls_socket))
         } else {
             debug!("Setting up tcp connection...");
 
@@ -215,13 +325,90 @@ impl NotaryClient {
                 .await
                 .map_err(|err| ClientError::new(ErrorKind::Connection, Some(Box::new(err))))?;
 
-            self.send_request(notary_socket, notarization_request)
+            Ok(NotaryConnection::Tcp(notary_socket))
+        }
+    }
+
+    /// Sends a GET request to an endpoint of the notary server, returning the
+    /// parsed JSON response.
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        let http_scheme = if self.tls { "https" } else { "http" };
+        let path_prefix = if self.path_prefix.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", self.path_prefix)
+        };
+
+        let (mut request_sender, connection) =
+            hyper::client::conn::http1::handshake(TokioIo::new(self.connect().await?))
                 .await
-                .map(|(connection, session_id)| Accepted {
-                    id: session_id,
-                    io: NotaryConnection::Tcp(connection),
-                })
+                .map_err(|err| {
+                    error!("Failed to attach http client to notary socket");
+                    ClientError::new(ErrorKind::Connection, Some(Box::new(err)))
+                })?;
+
+        let mut request_builder = Request::builder()
+            .uri(format!(
+                "{http_scheme}://{}:{}{}{}",
+                self.host, self.port, path_prefix, path
+            ))
+            .method("GET")
+            .header("Host", &self.host);
+
+        if let Some(api_key) = &self.api_key {
+            request_builder = request_builder.header(X_API_KEY_HEADER, api_key);
         }
+
+        if let Some(jwt) = &self.jwt {
+            request_builder = request_builder.header(AUTHORIZATION, format!("Bearer {jwt}"));
+        }
+
+        let request = request_builder
+            .body(Empty::<Bytes>::new())
+            .map_err(|err| ClientError::new(ErrorKind::Internal, Some(Box::new(err))))?;
+
+        // Drive the connection until the response is received, the connection is
+        // closed once the request sender is dropped.
+        let connection_fut = async {
+            connection
+                .await
+                .map_err(|err| ClientError::new(ErrorKind::Http, Some(Box::new(err))))
+        };
+
+        let response_fut = async move {
+            let response = request_sender
+                .send_request(request)
+                .await
+                .map_err(|err| ClientError::new(ErrorKind::Http, Some(Box::new(err))))?;
+
+            let status = response.status();
+            let payload = response
+                .into_body()
+                .collect()
+                .await
+                .map_err(|err| ClientError::new(ErrorKind::Http, Some(Box::new(err))))?
+                .to_bytes();
+
+            if status != StatusCode::OK {
+                return Err(ClientError::new(
+                    ErrorKind::Http,
+                    Some(
+                        format!(
+                            "Response status of {path} is not OK: {status}, {}",
+                            String::from_utf8_lossy(&payload)
+                        )
+                        .into(),
+                    ),
+                ));
+            }
+
+            serde_json::from_slice(&payload)
+                .map_err(|err| ClientError::new(ErrorKind::Internal, Some(Box::new(err))))
+        };
+
+        let (_, response) = futures::try_join!(connection_fut, response_fut)?;
+
+        Ok(response)
     }
 
     /// Sends notarization request to the notary server.
@@ -270,6 +457,7 @@ impl NotaryClient {
                     client_type: ClientType::Tcp,
                     max_sent_data: Some(notarization_request.max_sent_data),
                     max_recv_data: Some(notarization_request.max_recv_data),
+                    protocol_config: Some(notarization_request.protocol_config()?),
                 })
                 .map_err(|err| {
                     error!("Failed to serialise http request for configuration");
@@ -291,6 +479,11 @@ impl NotaryClient {
                     configuration_request_builder.header(X_API_KEY_HEADER, api_key);
             }
 
+            if let Some(jwt) = &self.jwt {
+                configuration_request_builder =
+                    configuration_request_builder.header(AUTHORIZATION, format!("Bearer {jwt}"));
+            }
+
             let configuration_request = configuration_request_builder
                 .body(Either::Left(Full::new(Bytes::from(
                     configuration_request_payload,
@@ -312,6 +505,32 @@ impl NotaryClient {
 
             debug!("Sent configuration request");
 
+            // The server rejects protocol configurations exceeding its limits with a
+            // structured response.
+            if configuration_response.status() == StatusCode::BAD_REQUEST {
+                let payload = configuration_response
+                    .into_body()
+                    .collect()
+                    .await
+                    .map_err(|err| {
+                        error!("Failed to parse configuration response");
+                        ClientError::new(ErrorKind::Http, Some(Box::new(err)))
+                    })?
+                    .to_bytes();
+
+                let source: Box<dyn std::error::Error + Send + Sync> =
+                    match serde_json::from_slice::<NotarizationSessionRejection>(&payload) {
+                        Ok(rejection) => Box::new(rejection),
+                        Err(_) => format!(
+                            "Configuration request is invalid: {}",
+                            String::from_utf8_lossy(&payload)
+                        )
+                        .into(),
+                    };
+
+                return Err(ClientError::new(ErrorKind::Configuration, Some(source)));
+            }
+
             if configuration_response.status() != StatusCode::OK {
                 return Err(ClientError::new(
                     ErrorKind::Configuration,
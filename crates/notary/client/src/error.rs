@@ -4,6 +4,7 @@
 //! notarization requests.
 
 use derive_builder::UninitializedFieldError;
+use notary_common::NotarizationSessionRejection;
 use std::{error::Error, fmt};
 
 #[derive(Debug)]
@@ -15,6 +16,7 @@ pub(crate) enum ErrorKind {
     TlsSetup,
     Http,
     Configuration,
+    Transparency,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -29,6 +31,14 @@ impl ClientError {
     pub(crate) fn new(kind: ErrorKind, source: Option<Box<dyn Error + Send + Sync>>) -> Self {
         Self { kind, source }
     }
+
+    /// Returns the rejection of the requested protocol configuration, if the
+    /// notary server rejected it.
+    pub fn rejection(&self) -> Option<&NotarizationSessionRejection> {
+        self.source
+            .as_ref()
+            .and_then(|source| source.downcast_ref::<NotarizationSessionRejection>())
+    }
 }
 
 impl fmt::Display for ClientError {
@@ -1,7 +1,8 @@
 //! Notary client library.
 //!
 //! A notary client's purpose is to establish a connection to the notary server
-//! via TCP or TLS, and to configure and request notarization.
+//! via TCP or TLS, to configure and request notarization, and to fetch proofs
+//! from the transparency log of the notary server.
 //! Note that the actual notarization is not performed by the notary client but
 //! by the prover of the TLSNotary protocol.
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use tlsn_common::config::{LimitExceeded, ProtocolConfig};
+use tlsn_core::transparency::{ConsistencyProof, InclusionProof, SignedTreeHead};
 
 /// Custom HTTP header used for specifying a whitelisted API key.
 pub const X_API_KEY_HEADER: &str = "X-API-Key";
@@ -14,7 +17,7 @@ pub enum ClientType {
 }
 
 /// Request object of the /session API.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NotarizationSessionRequest {
     pub client_type: ClientType,
@@ -22,6 +25,10 @@ pub struct NotarizationSessionRequest {
     pub max_sent_data: Option<usize>,
     /// Maximum data that can be received by the prover.
     pub max_recv_data: Option<usize>,
+    /// Protocol configuration that the prover will use, takes precedence over
+    /// `max_sent_data` and `max_recv_data` if set.
+    #[serde(default)]
+    pub protocol_config: Option<ProtocolConfig>,
 }
 
 /// Response object of the /session API.
@@ -31,4 +38,54 @@ pub struct NotarizationSessionResponse {
     /// Unique session id that is generated by the notary and shared to the
     /// prover.
     pub session_id: String,
+    /// Protocol configuration accepted by the notary.
+    #[serde(default)]
+    pub protocol_config: Option<ProtocolConfig>,
+}
+
+/// Response object of the /session API when the notary rejects the protocol
+/// configuration requested by the prover.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotarizationSessionRejection {
+    /// Reason of the rejection.
+    pub message: String,
+    /// The notary's limit which the requested configuration exceeds, if any.
+    pub limit: Option<LimitExceeded>,
+}
+
+impl fmt::Display for NotarizationSessionRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "notarization session rejected: {}", self.message)
+    }
+}
+
+impl std::error::Error for NotarizationSessionRejection {}
+
+/// Response object of the /transparency/sth API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeHeadsResponse {
+    /// Current head of the transparency log, signed by each published key of
+    /// the notary.
+    pub tree_heads: Vec<SignedTreeHead>,
+}
+
+/// Response object of the /transparency/inclusion API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionProofResponse {
+    /// Proof that the attestation is included in the log of the tree heads.
+    pub proof: InclusionProof,
+    /// Head of the transparency log, signed by each published key of the
+    /// notary.
+    pub tree_heads: Vec<SignedTreeHead>,
+}
+
+/// Response object of the /transparency/consistency API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyProofResponse {
+    /// Proof that the newer log extends the older log.
+    pub proof: ConsistencyProof,
 }
@@ -1,3 +1,5 @@
+pub mod jwt;
+
 use eyre::{eyre, Result};
 use notify::{
     event::ModifyKind, Error, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
@@ -10,7 +12,7 @@ use std::{
 };
 use tracing::{debug, error, info};
 
-use crate::{util::parse_csv_file, NotaryServerProperties};
+use crate::{config::AuthorizationMode, util::parse_csv_file, NotaryServerProperties};
 
 /// Structure of each whitelisted record of the API key whitelist for
 /// authorization purpose
@@ -38,21 +40,22 @@ pub fn authorization_whitelist_vec_into_hashmap(
 pub fn load_authorization_whitelist(
     config: &NotaryServerProperties,
 ) -> Result<Option<HashMap<String, AuthorizationWhitelistRecord>>> {
-    let authorization_whitelist = if !config.auth.enabled {
-        debug!("Skipping authorization as it is turned off.");
-        None
-    } else {
-        // Check if whitelist_csv_path is Some and convert to &str
-        let whitelist_csv_path = config.auth.whitelist_path.as_deref().ok_or_else(|| {
-            eyre!("Authorization whitelist csv path is not provided in the config")
-        })?;
-        // Load the csv
-        let whitelist_csv = parse_csv_file::<AuthorizationWhitelistRecord>(whitelist_csv_path)
-            .map_err(|err| eyre!("Failed to parse authorization whitelist csv: {:?}", err))?;
-        // Convert the whitelist record into hashmap for faster lookup
-        let whitelist_hashmap = authorization_whitelist_vec_into_hashmap(whitelist_csv);
-        Some(whitelist_hashmap)
-    };
+    let authorization_whitelist =
+        if !config.auth.enabled || config.auth.mode != AuthorizationMode::Whitelist {
+            debug!("Skipping API key whitelist authorization as it is turned off.");
+            None
+        } else {
+            // Check if whitelist_csv_path is Some and convert to &str
+            let whitelist_csv_path = config.auth.whitelist_path.as_deref().ok_or_else(|| {
+                eyre!("Authorization whitelist csv path is not provided in the config")
+            })?;
+            // Load the csv
+            let whitelist_csv = parse_csv_file::<AuthorizationWhitelistRecord>(whitelist_csv_path)
+                .map_err(|err| eyre!("Failed to parse authorization whitelist csv: {:?}", err))?;
+            // Convert the whitelist record into hashmap for faster lookup
+            let whitelist_hashmap = authorization_whitelist_vec_into_hashmap(whitelist_csv);
+            Some(whitelist_hashmap)
+        };
     Ok(authorization_whitelist)
 }
 
@@ -141,6 +144,7 @@ mod test {
             auth: AuthorizationProperties {
                 enabled: true,
                 whitelist_path: Some(whitelist_csv_path.clone()),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -0,0 +1,371 @@
+use eyre::{eyre, Result};
+use jsonwebtoken::{
+    decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, TokenData, Validation,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+use tracing::debug;
+
+use crate::{
+    config::{AuthorizationMode, JwtAuthorizationProperties, JwtClaim, NotarizationProperties},
+    NotaryServerProperties,
+};
+
+/// Claims of a validated JWT which are relevant to the notary server
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct JwtClaims {
+    /// Subject of the JWT, i.e. the user
+    pub sub: Option<String>,
+    /// Per-user limit for maximum bytes that can be sent
+    pub max_sent_data: Option<usize>,
+    /// Per-user limit for maximum bytes that can be received
+    pub max_recv_data: Option<usize>,
+}
+
+impl JwtClaims {
+    /// Apply the per-user limits to the global notarization config. The
+    /// per-user limits can only lower the global limits
+    pub fn limit(&self, config: &NotarizationProperties) -> NotarizationProperties {
+        let mut config = config.clone();
+        if let Some(max_sent_data) = self.max_sent_data {
+            config.max_sent_data = config.max_sent_data.min(max_sent_data);
+        }
+        if let Some(max_recv_data) = self.max_recv_data {
+            config.max_recv_data = config.max_recv_data.min(max_recv_data);
+        }
+        config
+    }
+}
+
+/// Validator of the JWTs attached to requests
+pub struct JwtValidator {
+    /// Decoding keys with their optional key id
+    keys: Vec<(Option<String>, DecodingKey)>,
+    validation: Validation,
+    claims: Vec<JwtClaim>,
+}
+
+impl fmt::Debug for JwtValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtValidator")
+            .field("keys", &self.keys.len())
+            .field("validation", &self.validation)
+            .field("claims", &self.claims)
+            .finish()
+    }
+}
+
+impl JwtValidator {
+    pub fn new(config: &JwtAuthorizationProperties) -> Result<Self> {
+        let keys = match (&config.jwks_path, &config.public_key_path) {
+            (Some(path), _) => {
+                let jwks: JwkSet = serde_json::from_str(&std::fs::read_to_string(path)?)
+                    .map_err(|err| eyre!("Failed to parse JWT key set: {err}"))?;
+                jwks.keys
+                    .iter()
+                    .map(|jwk| {
+                        DecodingKey::from_jwk(jwk)
+                            .map(|key| (jwk.common.key_id.clone(), key))
+                            .map_err(|err| eyre!("Failed to load JWT key from key set: {err}"))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+            (None, Some(path)) => {
+                let pem = std::fs::read(path)?;
+                vec![(None, decoding_key_from_pem(config.algorithm, &pem)?)]
+            }
+            (None, None) => {
+                return Err(eyre!(
+                    "Either JWT public key path or key set path must be provided in the config"
+                ))
+            }
+        };
+        if keys.is_empty() {
+            return Err(eyre!("JWT key set does not contain any key"));
+        }
+
+        let mut validation = Validation::new(config.algorithm);
+        if let Some(issuer) = &config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = &config.audience {
+            validation.set_audience(&[audience]);
+        } else {
+            validation.validate_aud = false;
+        }
+
+        Ok(Self {
+            keys,
+            validation,
+            claims: config.claims.clone(),
+        })
+    }
+
+    /// Validate the JWT, returns its claims if it is valid
+    pub fn validate(&self, token: &str) -> Result<JwtClaims> {
+        let header = decode_header(token)?;
+
+        // Select the key by its id if the JWT specifies one, otherwise try all keys
+        let mut keys = self
+            .keys
+            .iter()
+            .filter(|(kid, _)| header.kid.is_none() || *kid == header.kid)
+            .peekable();
+        if keys.peek().is_none() {
+            return Err(eyre!("Unknown JWT key id {:?}", header.kid));
+        }
+
+        let mut result = Err(eyre!("JWT is not signed by any key"));
+        for (_, key) in keys {
+            result = decode::<Map<String, Value>>(token, key, &self.validation)
+                .map_err(|err| eyre!("Invalid JWT: {err}"));
+            if result.is_ok() {
+                break;
+            }
+        }
+        let TokenData { claims, .. } = result?;
+
+        for required in &self.claims {
+            check_claim(&claims, required)?;
+        }
+
+        serde_json::from_value(Value::Object(claims))
+            .map_err(|err| eyre!("Malformed JWT claims: {err}"))
+    }
+}
+
+/// Load the JWT validator if JWT authorization is enabled
+pub fn load_jwt_validator(config: &NotaryServerProperties) -> Result<Option<JwtValidator>> {
+    if !config.auth.enabled || config.auth.mode != AuthorizationMode::Jwt {
+        debug!("Skipping JWT authorization as it is turned off.");
+        return Ok(None);
+    }
+    let jwt_config = config
+        .auth
+        .jwt
+        .as_ref()
+        .ok_or_else(|| eyre!("JWT setting is not provided in the config"))?;
+    Ok(Some(JwtValidator::new(jwt_config)?))
+}
+
+fn decoding_key_from_pem(algorithm: Algorithm, pem: &[u8]) -> Result<DecodingKey> {
+    let key = match algorithm {
+        Algorithm::RS256
+        | Algorithm::RS384
+        | Algorithm::RS512
+        | Algorithm::PS256
+        | Algorithm::PS384
+        | Algorithm::PS512 => DecodingKey::from_rsa_pem(pem),
+        Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(pem),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(pem),
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+            return Err(eyre!(
+                "JWT algorithm {algorithm:?} is not supported as it requires a shared secret"
+            ))
+        }
+    };
+    key.map_err(|err| eyre!("Failed to load JWT public key: {err}"))
+}
+
+/// Check that the required claim is present with one of the accepted values
+fn check_claim(claims: &Map<String, Value>, required: &JwtClaim) -> Result<()> {
+    let value = claims
+        .get(&required.name)
+        .ok_or_else(|| eyre!("Missing JWT claim {}", required.name))?;
+    if required.values.is_empty() {
+        return Ok(());
+    }
+
+    let accepted = |value: &Value| {
+        value
+            .as_str()
+            .is_some_and(|value| required.values.iter().any(|accepted| accepted == value))
+    };
+    let is_accepted = match value {
+        Value::Array(values) => values.iter().any(accepted),
+        value => accepted(value),
+    };
+    if is_accepted {
+        Ok(())
+    } else {
+        Err(eyre!("Unaccepted value of JWT claim {}", required.name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use p256::{
+        elliptic_curve::sec1::ToEncodedPoint,
+        pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding},
+        SecretKey,
+    };
+    use serde_json::json;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn secret_key(seed: u8) -> SecretKey {
+        SecretKey::from_slice(&[seed; 32]).unwrap()
+    }
+
+    fn token(key: &SecretKey, kid: Option<&str>, claims: Value) -> String {
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = kid.map(str::to_string);
+        let pem = key.to_pkcs8_pem(LineEnding::LF).unwrap();
+        encode(
+            &header,
+            &claims,
+            &EncodingKey::from_ec_pem(pem.as_bytes()).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn exp() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 600
+    }
+
+    fn validator(key: &SecretKey) -> JwtValidator {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("key.pem");
+        let pem = key.public_key().to_public_key_pem(LineEnding::LF).unwrap();
+        std::fs::write(&path, pem).unwrap();
+
+        JwtValidator::new(&JwtAuthorizationProperties {
+            algorithm: Algorithm::ES256,
+            public_key_path: Some(path.to_str().unwrap().to_string()),
+            jwks_path: None,
+            issuer: Some("https://issuer.example".to_string()),
+            audience: Some("notary".to_string()),
+            claims: vec![JwtClaim {
+                name: "roles".to_string(),
+                values: vec!["prover".to_string()],
+            }],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_jwt() {
+        let key = secret_key(1);
+        let validator = validator(&key);
+
+        let claims = validator
+            .validate(&token(
+                &key,
+                None,
+                json!({
+                    "sub": "alice",
+                    "iss": "https://issuer.example",
+                    "aud": "notary",
+                    "exp": exp(),
+                    "roles": ["admin", "prover"],
+                    "max_sent_data": 1024,
+                }),
+            ))
+            .unwrap();
+
+        assert_eq!(claims.sub.as_deref(), Some("alice"));
+        assert_eq!(claims.max_sent_data, Some(1024));
+        assert_eq!(claims.max_recv_data, None);
+
+        let config = claims.limit(&NotarizationProperties::default());
+        assert_eq!(config.max_sent_data, 1024);
+        assert_eq!(
+            config.max_recv_data,
+            NotarizationProperties::default().max_recv_data
+        );
+    }
+
+    #[test]
+    fn test_invalid_jwt() {
+        let key = secret_key(1);
+        let validator = validator(&key);
+        let claims = json!({
+            "iss": "https://issuer.example",
+            "aud": "notary",
+            "exp": exp(),
+            "roles": "prover",
+        });
+        assert!(validator
+            .validate(&token(&key, None, claims.clone()))
+            .is_ok());
+
+        // Signed by another key
+        assert!(validator
+            .validate(&token(&secret_key(2), None, claims.clone()))
+            .is_err());
+
+        // Wrong issuer, audience or required claim, or expired
+        for (name, value) in [
+            ("iss", json!("https://other.example")),
+            ("aud", json!("other")),
+            ("roles", json!(["admin"])),
+            ("exp", json!(1)),
+        ] {
+            let mut claims = claims.clone();
+            claims[name] = value;
+            assert!(validator.validate(&token(&key, None, claims)).is_err());
+        }
+
+        // Missing required claim
+        let mut claims = claims.clone();
+        claims.as_object_mut().unwrap().remove("roles");
+        assert!(validator.validate(&token(&key, None, claims)).is_err());
+    }
+
+    #[test]
+    fn test_jwks() {
+        let keys = [secret_key(1), secret_key(2)];
+        let jwks = json!({
+            "keys": keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| {
+                    let point = key.public_key().to_encoded_point(false);
+                    json!({
+                        "kty": "EC",
+                        "crv": "P-256",
+                        "kid": format!("key-{i}"),
+                        "x": URL_SAFE_NO_PAD.encode(point.x().unwrap()),
+                        "y": URL_SAFE_NO_PAD.encode(point.y().unwrap()),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("jwks.json");
+        std::fs::write(&path, jwks.to_string()).unwrap();
+
+        let validator = JwtValidator::new(&JwtAuthorizationProperties {
+            algorithm: Algorithm::ES256,
+            public_key_path: None,
+            jwks_path: Some(path.to_str().unwrap().to_string()),
+            issuer: None,
+            audience: None,
+            claims: vec![],
+        })
+        .unwrap();
+
+        let claims = json!({ "exp": exp() });
+        assert!(validator
+            .validate(&token(&keys[1], Some("key-1"), claims.clone()))
+            .is_ok());
+        assert!(validator
+            .validate(&token(&keys[1], None, claims.clone()))
+            .is_ok());
+        assert!(validator
+            .validate(&token(&keys[1], Some("key-0"), claims.clone()))
+            .is_err());
+        assert!(validator
+            .validate(&token(&keys[1], Some("unknown"), claims))
+            .is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! Stand-in for an external signer, e.g. a KMS or HSM, which signs
+//! attestations for the notary server with a local key using the remote signer
+//! protocol of `tlsn_core::signing::remote`. Intended for tests and local
+//! development only.
+
+use eyre::{eyre, Result};
+use notary_server::AttestationKey;
+use pkcs8::DecodePrivateKey;
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    sync::Arc,
+    thread,
+};
+use structopt::StructOpt;
+use tlsn_core::signing::{remote::serve_remote_signer, RemoteSignerEndpoint, Signer};
+use tracing::{error, info};
+
+#[derive(Clone, Debug, StructOpt)]
+#[structopt(name = "Stand-in Signer")]
+struct CliFields {
+    /// File path of the private key (in PEM format) used for signing
+    #[structopt(long)]
+    key: String,
+    /// Endpoint to listen on, i.e. tcp://<host>:<port> or unix://<socket path>
+    #[structopt(long, default_value = "tcp://127.0.0.1:7049")]
+    listen: String,
+}
+
+fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let cli_fields = CliFields::from_args();
+
+    let pem = std::fs::read_to_string(&cli_fields.key)?;
+    let key = AttestationKey::from_pkcs8_pem(&pem)
+        .map_err(|err| eyre!("Failed to load signing key: {err}"))?;
+    let signer: Arc<dyn Signer + Send + Sync> = Arc::from(key.into_signer());
+
+    let endpoint: RemoteSignerEndpoint = cli_fields.listen.parse()?;
+    info!("Listening for signing requests on {endpoint}");
+
+    match endpoint {
+        RemoteSignerEndpoint::Tcp(addr) => {
+            for stream in TcpListener::bind(addr)?.incoming() {
+                spawn_handler(stream?, signer.clone());
+            }
+        }
+        #[cfg(unix)]
+        RemoteSignerEndpoint::Unix(path) => {
+            // Remove the socket left over by a previous run
+            let _ = std::fs::remove_file(&path);
+            for stream in std::os::unix::net::UnixListener::bind(path)?.incoming() {
+                spawn_handler(stream?, signer.clone());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn spawn_handler<S: Read + Write + Send + 'static>(
+    mut stream: S,
+    signer: Arc<dyn Signer + Send + Sync>,
+) {
+    thread::spawn(move || {
+        if let Err(err) = serve_remote_signer(&mut stream, signer.as_ref()) {
+            error!("Failed to serve signing request: {err}");
+        }
+    });
+}
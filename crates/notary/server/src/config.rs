@@ -1,7 +1,9 @@
 use config::{Config, Environment};
 use eyre::{eyre, Result};
+use jsonwebtoken::Algorithm;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use tlsn_common::config::NetworkSetting;
 
 use crate::{parse_config_file, util::prepend_file_path, CliFields};
 
@@ -23,6 +25,15 @@ pub struct NotaryServerProperties {
     pub log: LogProperties,
     /// Setting for authorization
     pub auth: AuthorizationProperties,
+    /// Setting for notarization sessions
+    #[serde(default)]
+    pub session: SessionProperties,
+    /// Setting for rate limiting of provers
+    #[serde(default)]
+    pub rate_limit: RateLimitProperties,
+    /// Setting for the transparency log of attestations
+    #[serde(default)]
+    pub transparency: TransparencyProperties,
 }
 
 impl NotaryServerProperties {
@@ -45,6 +56,15 @@ impl NotaryServerProperties {
             if let Some(path) = &config.notarization.private_key_path {
                 config.notarization.private_key_path = Some(prepend_file_path(path, &parent_dir)?);
             }
+            // Prepend notarization key directory.
+            if let Some(path) = &config.notarization.key_directory {
+                config.notarization.key_directory = Some(prepend_file_path(path, &parent_dir)?);
+            }
+            // Prepend revocation list path.
+            if let Some(path) = &config.notarization.revocation_list_path {
+                config.notarization.revocation_list_path =
+                    Some(prepend_file_path(path, &parent_dir)?);
+            }
             // Prepend TLS key paths.
             if let Some(path) = &config.tls.private_key_path {
                 config.tls.private_key_path = Some(prepend_file_path(path, &parent_dir)?);
@@ -56,6 +76,23 @@ impl NotaryServerProperties {
             if let Some(path) = &config.auth.whitelist_path {
                 config.auth.whitelist_path = Some(prepend_file_path(path, &parent_dir)?);
             }
+            // Prepend JWT key paths.
+            if let Some(jwt) = &mut config.auth.jwt {
+                if let Some(path) = &jwt.public_key_path {
+                    jwt.public_key_path = Some(prepend_file_path(path, &parent_dir)?);
+                }
+                if let Some(path) = &jwt.jwks_path {
+                    jwt.jwks_path = Some(prepend_file_path(path, &parent_dir)?);
+                }
+            }
+            // Prepend session store path.
+            if let Some(path) = &config.session.store_path {
+                config.session.store_path = Some(prepend_file_path(path, &parent_dir)?);
+            }
+            // Prepend transparency log path.
+            if let Some(path) = &config.transparency.log_path {
+                config.transparency.log_path = Some(prepend_file_path(path, &parent_dir)?);
+            }
 
             Ok(config)
         } else {
@@ -85,16 +122,47 @@ pub struct NotarizationProperties {
     pub max_sent_data: usize,
     /// Global limit for maximum number of bytes that can be received
     pub max_recv_data: usize,
+    /// Global limit for maximum number of bytes that can be decrypted online,
+    /// i.e. while the MPC-TLS connection is active
+    pub max_recv_data_online: Option<usize>,
+    /// Global limit for maximum number of application data records that can
+    /// be sent
+    pub max_sent_records: Option<usize>,
+    /// Global limit for maximum number of application data records that can
+    /// be received
+    pub max_recv_records: Option<usize>,
+    /// Setting of the deferred decryption from the start of the MPC-TLS
+    /// connection required from provers, any setting is accepted if not set
+    pub defer_decryption_from_start: Option<bool>,
+    /// Network setting required from provers, any setting is accepted if not
+    /// set
+    pub network: Option<NetworkSetting>,
     /// Number of seconds before notarization timeouts to prevent unreleased
     /// memory
     pub timeout: u64,
     /// File path of private key (in PEM format) used to sign the notarization
     pub private_key_path: Option<String>,
-    /// Signature algorithm used to generate a random private key when
-    /// private_key_path is not set
+    /// Directory of the keys used to sign the notarization, which are listed
+    /// with their validity windows in its keys.yaml manifest. Takes precedence
+    /// over private_key_path, and is hot reloaded when modified
+    pub key_directory: Option<String>,
+    /// Endpoint of an external signer (tcp://<host>:<port> or
+    /// unix://<socket path>) used to sign the notarization, so that the key
+    /// can be kept in a KMS or HSM. Takes precedence over private_key_path
+    pub remote_signer: Option<String>,
+    /// Signature algorithm requested from the remote signer, or used to
+    /// generate a random private key when no key is configured
     pub signature_algorithm: String,
     /// Flag to allow any custom extensions from the prover.
     pub allow_extensions: bool,
+    /// Number of seconds after the notarization during which attestations are
+    /// valid, valid indefinitely if not set
+    #[serde(default)]
+    pub validity: Option<u64>,
+    /// File path of the ids (hex encoded, one per line) of the attestations
+    /// revoked by the notary, which are published by the /revocations API
+    #[serde(default)]
+    pub revocation_list_path: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -134,8 +202,94 @@ pub struct LogProperties {
 pub struct AuthorizationProperties {
     /// Flag to turn on or off auth middleware
     pub enabled: bool,
+    /// Mode of authorization, i.e. either API key whitelist or JWT
+    #[serde(default)]
+    pub mode: AuthorizationMode,
     /// File path of the API key whitelist (in CSV format)
     pub whitelist_path: Option<String>,
+    /// Setting for JWT authorization
+    #[serde(default)]
+    pub jwt: Option<JwtAuthorizationProperties>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthorizationMode {
+    /// API key in the X-API-Key header, checked against the whitelist
+    #[default]
+    Whitelist,
+    /// JWT in the Authorization header as a bearer token
+    Jwt,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JwtAuthorizationProperties {
+    /// Algorithm of the JWT signature, e.g. ES256, RS256 or EdDSA
+    pub algorithm: Algorithm,
+    /// File path of the public key (in PEM format) used to verify the JWT
+    pub public_key_path: Option<String>,
+    /// File path of the JSON web key set used to verify the JWT, which is used
+    /// instead of public_key_path to support multiple keys selected by the kid
+    /// header of the JWT
+    pub jwks_path: Option<String>,
+    /// Expected issuer (iss claim) of the JWT, not checked if not set
+    pub issuer: Option<String>,
+    /// Expected audience (aud claim) of the JWT, not checked if not set
+    pub audience: Option<String>,
+    /// Claims that must be present in the JWT
+    #[serde(default)]
+    pub claims: Vec<JwtClaim>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JwtClaim {
+    /// Name of the claim
+    pub name: String,
+    /// Accepted values of the claim, any value is accepted if empty. If the
+    /// claim is an array, it must contain one of the accepted values
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionProperties {
+    /// Number of seconds a session id returned by the /session API remains
+    /// valid for the /notarize API
+    pub ttl: u64,
+    /// Number of seconds between purges of expired session ids, must be non-zero
+    pub reap_interval: u64,
+    /// Directory to store session ids in, which can be shared between multiple
+    /// notary instances. Session ids are stored in memory if not set
+    pub store_path: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TransparencyProperties {
+    /// Flag to append the header of every signed attestation to the
+    /// transparency log
+    pub enabled: bool,
+    /// File path of the log, which is appended to and reloaded on startup. The
+    /// log is kept in memory if not set, which is only suitable for testing
+    pub log_path: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RateLimitProperties {
+    /// Maximum number of /session requests per minute of each client, i.e. API
+    /// key, JWT subject or IP address. Unlimited if not set
+    pub requests_per_minute: Option<u32>,
+    /// Maximum number of /session requests of each client in a burst, defaults
+    /// to requests_per_minute if not set
+    pub burst: Option<u32>,
+    /// Maximum bytes each client can notarize per day (UTC). The
+    /// max_sent_data + max_recv_data of a session are reserved until its
+    /// notarization is over. Unlimited if not set
+    pub daily_bytes_quota: Option<u64>,
+    /// Maximum number of /notarize requests waiting for a permit when the
+    /// concurrency limit is reached. Requests are rejected immediately if 0
+    pub queue_size: usize,
+    /// Maximum number of seconds a /notarize request waits for a permit
+    pub queue_timeout: u64,
 }
 
 impl Default for NotaryServerProperties {
@@ -170,6 +324,9 @@ impl Default for NotaryServerProperties {
             tls: Default::default(),
             log: Default::default(),
             auth: Default::default(),
+            session: Default::default(),
+            rate_limit: Default::default(),
+            transparency: Default::default(),
         }
     }
 }
@@ -179,10 +336,41 @@ impl Default for NotarizationProperties {
         Self {
             max_sent_data: 4096,
             max_recv_data: 16384,
+            max_recv_data_online: None,
+            max_sent_records: None,
+            max_recv_records: None,
+            defer_decryption_from_start: None,
+            network: None,
             timeout: 1800,
             private_key_path: None,
+            key_directory: None,
+            remote_signer: None,
             signature_algorithm: "secp256k1".to_string(),
             allow_extensions: false,
+            validity: None,
+            revocation_list_path: None,
+        }
+    }
+}
+
+impl Default for SessionProperties {
+    fn default() -> Self {
+        Self {
+            ttl: 300,
+            reap_interval: 60,
+            store_path: None,
+        }
+    }
+}
+
+impl Default for RateLimitProperties {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: None,
+            burst: None,
+            daily_bytes_quota: None,
+            queue_size: 0,
+            queue_timeout: 30,
         }
     }
 }
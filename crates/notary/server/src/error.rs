@@ -1,9 +1,15 @@
-use axum::http::StatusCode;
+use axum::{
+    http::{header, StatusCode},
+    Json,
+};
 use axum_core::response::{IntoResponse as AxumCoreIntoResponse, Response};
 use eyre::Report;
+use notary_common::NotarizationSessionRejection;
 use std::error::Error;
 use tlsn_common::config::ProtocolConfigValidatorBuilderError;
 
+use crate::rate_limit::RateLimitExceeded;
+
 use tlsn_verifier::{VerifierConfigBuilderError, VerifierError};
 
 #[derive(Debug, thiserror::Error)]
@@ -18,6 +24,10 @@ pub enum NotaryServerError {
     BadProverRequest(String),
     #[error("Unauthorized request from prover: {0}")]
     UnauthorizedProverRequest(String),
+    #[error("Rejected protocol configuration from prover: {}", .0.message)]
+    RejectedProtocolConfig(NotarizationSessionRejection),
+    #[error("Too many requests from prover: {0}")]
+    RateLimited(RateLimitExceeded),
 }
 
 impl From<VerifierError> for NotaryServerError {
@@ -38,6 +48,21 @@ impl From<ProtocolConfigValidatorBuilderError> for NotaryServerError {
     }
 }
 
+impl NotaryServerError {
+    /// Returns the kind of the error, used as a label of metrics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NotaryServerError::Unexpected(_) => "unexpected",
+            NotaryServerError::Connection(_) => "connection",
+            NotaryServerError::Notarization(_) => "notarization",
+            NotaryServerError::BadProverRequest(_) => "bad_request",
+            NotaryServerError::UnauthorizedProverRequest(_) => "unauthorized",
+            NotaryServerError::RejectedProtocolConfig(_) => "rejected_protocol_config",
+            NotaryServerError::RateLimited(_) => "rate_limited",
+        }
+    }
+}
+
 /// Trait implementation to convert this error into an axum http response
 impl AxumCoreIntoResponse for NotaryServerError {
     fn into_response(self) -> Response {
@@ -45,6 +70,15 @@ impl AxumCoreIntoResponse for NotaryServerError {
             bad_request_error @ NotaryServerError::BadProverRequest(_) => {
                 (StatusCode::BAD_REQUEST, bad_request_error.to_string()).into_response()
             }
+            NotaryServerError::RejectedProtocolConfig(rejection) => {
+                (StatusCode::BAD_REQUEST, Json(rejection)).into_response()
+            }
+            rate_limited_error @ NotaryServerError::RateLimited(exceeded) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, exceeded.retry_after().as_secs())],
+                rate_limited_error.to_string(),
+            )
+                .into_response(),
             unauthorized_request_error @ NotaryServerError::UnauthorizedProverRequest(_) => (
                 StatusCode::UNAUTHORIZED,
                 unauthorized_request_error.to_string(),
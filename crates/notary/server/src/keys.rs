@@ -0,0 +1,541 @@
+use chrono::{DateTime, Utc};
+use eyre::{eyre, Result};
+use notify::{
+    event::ModifyKind, Error, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use pkcs8::DecodePrivateKey;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+use tlsn_core::{
+    attestation::{RevocationList, SignedRevocationList, Uid},
+    signing::{SignatureAlgId, Signer},
+    transparency::{SignedTreeHead, TreeHead},
+    CryptoProvider,
+};
+use tracing::{debug, error, info};
+use zeroize::Zeroize;
+
+use crate::{
+    signing::{verifying_key_pem, AttestationKey},
+    util::{parse_attestation_id, parse_config_file},
+};
+
+/// File name of the manifest in the key directory
+pub const KEY_MANIFEST: &str = "keys.yaml";
+
+/// Manifest of the key directory, listing the keys and their validity windows
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct KeyManifest {
+    pub keys: Vec<KeyManifestEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct KeyManifestEntry {
+    /// Unique identifier of the key
+    pub id: String,
+    /// File path of the private key (in PEM format), relative to the key
+    /// directory
+    pub path: String,
+    /// Status of the key
+    pub status: KeyStatus,
+    /// Start of the validity window, valid from the start if not set
+    pub not_before: Option<DateTime<Utc>>,
+    /// End of the validity window, valid indefinitely if not set
+    pub not_after: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyStatus {
+    /// Used to sign attestations during its validity window
+    Active,
+    /// No longer used to sign attestations, but still published so that
+    /// verifiers can verify attestations signed previously
+    Retiring,
+}
+
+/// Public information of a notary key, as returned by the /keys API
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyInfo {
+    /// Unique identifier of the key
+    pub id: String,
+    /// Signature algorithm identifier, as in the signature of attestations
+    pub alg_id: SignatureAlgId,
+    /// Name of the signature algorithm
+    pub algorithm: String,
+    /// Public key (in PEM format)
+    pub public_key: String,
+    pub status: KeyStatus,
+    pub not_before: Option<DateTime<Utc>>,
+    pub not_after: Option<DateTime<Utc>>,
+}
+
+impl KeyInfo {
+    /// Whether the key is within its validity window at the time
+    fn is_valid_at(&self, time: DateTime<Utc>) -> bool {
+        self.not_before.is_none_or(|not_before| not_before <= time)
+            && self.not_after.is_none_or(|not_after| time < not_after)
+    }
+}
+
+/// A notary key with its crypto provider used for signing
+struct NotaryKey {
+    info: KeyInfo,
+    verifying_key_bytes: Vec<u8>,
+    crypto_provider: Arc<CryptoProvider>,
+}
+
+impl NotaryKey {
+    fn new(info: KeyInfo, signer: Box<dyn Signer + Send + Sync>) -> Self {
+        let verifying_key_bytes = signer.verifying_key().data;
+        let mut provider = CryptoProvider::default();
+        provider.signer.set_signer(signer);
+        Self {
+            info,
+            verifying_key_bytes,
+            crypto_provider: Arc::new(provider),
+        }
+    }
+}
+
+/// Set of notary keys, of which at most one is active at any time
+pub struct KeyRing {
+    keys: RwLock<Vec<NotaryKey>>,
+    /// Number of times the keys were reloaded, so that data signed with every
+    /// key can be signed again when the keys change
+    generation: AtomicU64,
+}
+
+impl std::fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyRing")
+            .field("keys", &self.keys())
+            .finish()
+    }
+}
+
+impl KeyRing {
+    /// Key ring of a single signer which is always active, e.g. a local key or
+    /// a remote signer
+    pub fn single(signer: Box<dyn Signer + Send + Sync>) -> Result<Self> {
+        let info = KeyInfo {
+            id: "default".to_string(),
+            alg_id: signer.alg_id(),
+            algorithm: signer.alg_id().to_string(),
+            public_key: verifying_key_pem(&signer.verifying_key())
+                .map_err(|err| eyre!("Failed to get verifying key in PEM format: {err}"))?,
+            status: KeyStatus::Active,
+            not_before: None,
+            not_after: None,
+        };
+        Ok(Self {
+            keys: RwLock::new(vec![NotaryKey::new(info, signer)]),
+            generation: AtomicU64::new(0),
+        })
+    }
+
+    /// Load the keys listed in the manifest of the key directory
+    pub fn load(dir: &str) -> Result<Self> {
+        Ok(Self {
+            keys: RwLock::new(load_keys(dir)?),
+            generation: AtomicU64::new(0),
+        })
+    }
+
+    /// Reload the keys from the key directory. The current keys are kept if
+    /// the directory is invalid
+    pub fn reload(&self, dir: &str) -> Result<()> {
+        let keys = load_keys(dir)?;
+        *self.keys.write().unwrap() = keys;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Returns the number of times the keys were reloaded
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Returns the public information of all keys
+    pub fn keys(&self) -> Vec<KeyInfo> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .map(|key| key.info.clone())
+            .collect()
+    }
+
+    /// Returns the public information of the key used for signing now
+    pub fn active_key(&self) -> Result<KeyInfo> {
+        self.with_active_key(|key| key.info.clone())
+    }
+
+    /// Returns the verifying key used for signing now in compressed bytes
+    pub fn active_verifying_key_bytes(&self) -> Result<Vec<u8>> {
+        self.with_active_key(|key| key.verifying_key_bytes.clone())
+    }
+
+    /// Returns the crypto provider which signs with the key active now
+    pub fn crypto_provider(&self) -> Result<Arc<CryptoProvider>> {
+        self.with_active_key(|key| key.crypto_provider.clone())
+    }
+
+    /// Signs the list of revoked attestations with every key, so that
+    /// attestations signed by retiring keys can be revoked as well
+    pub fn revocation_lists(&self, revoked: &BTreeSet<Uid>) -> Result<Vec<SignedRevocationList>> {
+        let issued_at = Utc::now().timestamp() as u64;
+        self.sign_with_all_keys(|signer| {
+            let mut list = RevocationList::new(signer.verifying_key(), issued_at);
+            list.revoked = revoked.clone();
+            Ok(list.sign(signer)?)
+        })
+        .map_err(|err| eyre!("Failed to sign revocation list: {err}"))
+    }
+
+    /// Signs the head of the transparency log with every key, so that the
+    /// inclusion of attestations signed by retiring keys can be proven as well
+    pub fn tree_heads(&self, head: &TreeHead) -> Result<Vec<SignedTreeHead>> {
+        self.sign_with_all_keys(|signer| Ok(head.clone().sign(signer)?))
+            .map_err(|err| eyre!("Failed to sign tree head: {err}"))
+    }
+
+    fn sign_with_all_keys<T>(&self, f: impl Fn(&dyn Signer) -> Result<T>) -> Result<Vec<T>> {
+        let keys = self.keys.read().unwrap();
+        keys.iter()
+            .map(|key| {
+                let signer = key.crypto_provider.signer.get(&key.info.alg_id)?;
+                f(signer).map_err(|err| eyre!("key {}: {err}", key.info.id))
+            })
+            .collect()
+    }
+
+    fn with_active_key<T>(&self, f: impl FnOnce(&NotaryKey) -> T) -> Result<T> {
+        let now = Utc::now();
+        let keys = self.keys.read().unwrap();
+        keys.iter()
+            .find(|key| key.info.status == KeyStatus::Active && key.info.is_valid_at(now))
+            .map(f)
+            .ok_or_else(|| eyre!("No notary key is active at {now}"))
+    }
+}
+
+fn load_keys(dir: &str) -> Result<Vec<NotaryKey>> {
+    let dir = Path::new(dir);
+    let manifest_path = dir.join(KEY_MANIFEST);
+    let manifest: KeyManifest = parse_config_file(
+        manifest_path
+            .to_str()
+            .ok_or_else(|| eyre!("Failed to convert path to str"))?,
+    )
+    .map_err(|err| eyre!("Failed to parse key manifest: {err}"))?;
+
+    let mut keys: Vec<NotaryKey> = Vec::with_capacity(manifest.keys.len());
+    for entry in manifest.keys {
+        if keys.iter().any(|key| key.info.id == entry.id) {
+            return Err(eyre!("Duplicate notary key id {}", entry.id));
+        }
+        if let (Some(not_before), Some(not_after)) = (entry.not_before, entry.not_after) {
+            if not_before >= not_after {
+                return Err(eyre!("Empty validity window of notary key {}", entry.id));
+            }
+        }
+
+        let mut pem = std::fs::read_to_string(dir.join(&entry.path))
+            .map_err(|err| eyre!("Failed to read notary key {}: {err}", entry.id))?;
+        let key = AttestationKey::from_pkcs8_pem(&pem)
+            .map_err(|err| eyre!("Failed to load notary key {}: {err}", entry.id))?;
+        pem.zeroize();
+
+        let info = KeyInfo {
+            id: entry.id,
+            alg_id: key.alg_id(),
+            algorithm: key.alg_id().to_string(),
+            public_key: key
+                .verifying_key_pem()
+                .map_err(|err| eyre!("Failed to get verifying key in PEM format: {err}"))?,
+            status: entry.status,
+            not_before: entry.not_before,
+            not_after: entry.not_after,
+        };
+        keys.push(NotaryKey::new(info, key.into_signer()));
+    }
+
+    // Ensure that the key used for signing is unambiguous
+    let active = keys
+        .iter()
+        .filter(|key| key.info.status == KeyStatus::Active)
+        .collect::<Vec<_>>();
+    for (i, a) in active.iter().enumerate() {
+        for b in &active[i + 1..] {
+            if windows_overlap(&a.info, &b.info) {
+                return Err(eyre!(
+                    "Validity windows of active notary keys {} and {} overlap",
+                    a.info.id,
+                    b.info.id
+                ));
+            }
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Load the ids of the revoked attestations, which are hex encoded with one id
+/// per line. Empty lines and lines starting with '#' are ignored
+pub fn load_revoked_attestations(path: &str) -> Result<BTreeSet<Uid>> {
+    let file = std::fs::read_to_string(path)
+        .map_err(|err| eyre!("Failed to read revocation list: {err}"))?;
+
+    file.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_attestation_id)
+        .collect()
+}
+
+fn windows_overlap(a: &KeyInfo, b: &KeyInfo) -> bool {
+    let starts_before_b_ends = match (a.not_before, b.not_after) {
+        (Some(start), Some(end)) => start < end,
+        _ => true,
+    };
+    let starts_before_a_ends = match (b.not_before, a.not_after) {
+        (Some(start), Some(end)) => start < end,
+        _ => true,
+    };
+    starts_before_b_ends && starts_before_a_ends
+}
+
+// Setup a watcher to detect any changes to the key directory, the same way as
+// the authorization whitelist watcher. When the directory is modified, the
+// watcher thread will reload the keys
+pub fn watch_and_reload_keys(dir: String, key_ring: Arc<KeyRing>) -> Result<RecommendedWatcher> {
+    let cloned_dir = dir.clone();
+    let mut watcher = RecommendedWatcher::new(
+        move |event: Result<Event, Error>| match event {
+            Ok(event) => {
+                // Reload keys if a file is created, removed or its data is modified
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_)
+                        | EventKind::Remove(_)
+                        | EventKind::Modify(ModifyKind::Data(_))
+                ) {
+                    debug!("Key directory is modified");
+                    match key_ring.reload(&cloned_dir) {
+                        Ok(()) => info!("Successfully reloaded notary keys!"),
+                        // Ensure that error from reloading doesn't bring the server down
+                        Err(err) => error!("Failed to reload notary keys: {err}"),
+                    }
+                }
+            }
+            Err(err) => {
+                error!("Error occured when watcher detected an event: {err}")
+            }
+        },
+        notify::Config::default(),
+    )
+    .map_err(|err| eyre!("Error occured when setting up watcher for hot reload: {err}"))?;
+
+    watcher
+        .watch(Path::new(&dir), RecursiveMode::Recursive)
+        .map_err(|err| eyre!("Error occured when starting up watcher for hot reload: {err}"))?;
+
+    Ok(watcher)
+}
+
+#[cfg(test)]
+mod test {
+    use pkcs8::EncodePrivateKey;
+    use std::{fs, time::Duration};
+    use tempfile::{tempdir, TempDir};
+    use tlsn_core::signing::{remote::serve_remote_signer, RemoteSigner, RemoteSignerEndpoint};
+
+    use super::*;
+
+    const NOTARY_KEY: &str = "../tests-integration/fixture/notary/notary.key";
+
+    fn key_dir(manifest: &str) -> TempDir {
+        let dir = tempdir().unwrap();
+        fs::copy(NOTARY_KEY, dir.path().join("old.key")).unwrap();
+        fs::write(
+            dir.path().join("new.key"),
+            p256::SecretKey::from_slice(&[1u8; 32])
+                .unwrap()
+                .to_pkcs8_pem(pkcs8::LineEnding::LF)
+                .unwrap()
+                .as_bytes(),
+        )
+        .unwrap();
+        fs::write(dir.path().join(KEY_MANIFEST), manifest).unwrap();
+        dir
+    }
+
+    fn load(dir: &TempDir) -> Result<KeyRing> {
+        KeyRing::load(dir.path().to_str().unwrap())
+    }
+
+    const MANIFEST: &str = r#"
+keys:
+  - id: old
+    path: old.key
+    status: retiring
+    not_before: 2024-01-01T00:00:00Z
+    not_after: 2025-01-01T00:00:00Z
+  - id: new
+    path: new.key
+    status: active
+    not_before: 2025-01-01T00:00:00Z
+"#;
+
+    #[test]
+    fn test_load_keys() {
+        let key_ring = load(&key_dir(MANIFEST)).unwrap();
+
+        let keys = key_ring.keys();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].alg_id, SignatureAlgId::SECP256K1);
+        assert_eq!(keys[0].status, KeyStatus::Retiring);
+        assert_eq!(
+            keys[0].public_key,
+            fs::read_to_string("../tests-integration/fixture/notary/notary.pub").unwrap()
+        );
+        assert_eq!(keys[1].alg_id, SignatureAlgId::SECP256R1);
+
+        let active = key_ring.active_key().unwrap();
+        assert_eq!(active.id, "new");
+        let provider = key_ring.crypto_provider().unwrap();
+        assert_eq!(
+            provider.signer.supported_algs().collect::<Vec<_>>(),
+            vec![SignatureAlgId::SECP256R1]
+        );
+    }
+
+    #[test]
+    fn test_no_active_key() {
+        let manifest = MANIFEST.replace("status: active", "status: retiring");
+        let key_ring = load(&key_dir(&manifest)).unwrap();
+        assert!(key_ring.crypto_provider().is_err());
+
+        // The active key is not valid yet
+        let manifest = MANIFEST.replace(
+            "not_before: 2025-01-01T00:00:00Z",
+            "not_before: 2999-01-01T00:00:00Z",
+        );
+        let key_ring = load(&key_dir(&manifest)).unwrap();
+        assert!(key_ring.crypto_provider().is_err());
+    }
+
+    #[test]
+    fn test_overlapping_active_keys() {
+        let manifest = MANIFEST.replace("status: retiring", "status: active");
+        assert!(load(&key_dir(&manifest)).is_ok());
+
+        let manifest = manifest.replace(
+            "not_after: 2025-01-01T00:00:00Z",
+            "not_after: 2025-01-02T00:00:00Z",
+        );
+        assert!(load(&key_dir(&manifest)).is_err());
+    }
+
+    #[test]
+    fn test_remote_signer_key_ring() {
+        let pem = fs::read_to_string(NOTARY_KEY).unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = RemoteSignerEndpoint::Tcp(listener.local_addr().unwrap().to_string());
+        std::thread::spawn(move || {
+            let signer = AttestationKey::from_pkcs8_pem(&pem).unwrap().into_signer();
+            for stream in listener.incoming().take(2) {
+                serve_remote_signer(&mut stream.unwrap(), signer.as_ref()).unwrap();
+            }
+        });
+
+        let signer = RemoteSigner::connect(endpoint, SignatureAlgId::SECP256K1).unwrap();
+        assert_eq!(
+            signer.sign(b"header").unwrap().alg,
+            SignatureAlgId::SECP256K1
+        );
+        let key_ring = KeyRing::single(Box::new(signer)).unwrap();
+
+        let active = key_ring.active_key().unwrap();
+        assert_eq!(active.alg_id, SignatureAlgId::SECP256K1);
+        assert_eq!(
+            active.public_key,
+            fs::read_to_string("../tests-integration/fixture/notary/notary.pub").unwrap()
+        );
+        assert_eq!(
+            key_ring
+                .crypto_provider()
+                .unwrap()
+                .signer
+                .supported_algs()
+                .collect::<Vec<_>>(),
+            vec![SignatureAlgId::SECP256K1]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_and_reload_keys() {
+        let dir = key_dir(MANIFEST);
+        let key_ring = Arc::new(load(&dir).unwrap());
+        let _watcher =
+            watch_and_reload_keys(dir.path().to_str().unwrap().to_string(), key_ring.clone())
+                .unwrap();
+
+        // Sleep to buy a bit of time for the watcher thread to run
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Rotate back to the old key
+        let manifest = MANIFEST
+            .replace("status: retiring", "status: STATUS")
+            .replace("status: active", "status: retiring")
+            .replace("status: STATUS", "status: active")
+            .replace(
+                "not_after: 2025-01-01T00:00:00Z",
+                "not_after: 2999-01-01T00:00:00Z",
+            );
+        fs::write(dir.path().join(KEY_MANIFEST), manifest).unwrap();
+
+        // Sleep to buy a bit of time for the keys to be hot reloaded
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(key_ring.active_key().unwrap().id, "old");
+    }
+
+    #[test]
+    fn test_revocation_lists() {
+        let dir = key_dir(MANIFEST);
+        let path = dir.path().join("revoked.txt");
+        fs::write(
+            &path,
+            "# revoked attestations\n000102030405060708090a0b0c0d0e0f\n\n",
+        )
+        .unwrap();
+
+        let revoked = load_revoked_attestations(path.to_str().unwrap()).unwrap();
+        let id = Uid(std::array::from_fn(|i| i as u8));
+        assert_eq!(revoked, BTreeSet::from([id.clone()]));
+
+        // Every key signs a list, including the retiring one
+        let key_ring = load(&dir).unwrap();
+        let lists = key_ring.revocation_lists(&revoked).unwrap();
+        assert_eq!(lists.len(), 2);
+
+        let provider = CryptoProvider::default();
+        for list in lists {
+            let list = list.verify(&provider).unwrap();
+            assert!(list.revoked.contains(&id));
+        }
+
+        fs::write(&path, "0001").unwrap();
+        assert!(load_revoked_attestations(path.to_str().unwrap()).is_err());
+    }
+}
@@ -2,22 +2,33 @@ mod auth;
 mod cli;
 mod config;
 mod error;
+mod keys;
+mod metrics;
 mod middleware;
+mod rate_limit;
+mod revocation;
 mod server;
 mod server_tracing;
 mod service;
 mod signing;
+mod store;
 #[cfg(feature = "tee_quote")]
 mod tee;
+mod transparency;
 mod types;
 mod util;
 
 pub use cli::CliFields;
 pub use config::{
-    AuthorizationProperties, LogProperties, NotarizationProperties, NotaryServerProperties,
-    TLSProperties,
+    AuthorizationMode, AuthorizationProperties, JwtAuthorizationProperties, JwtClaim,
+    LogProperties, NotarizationProperties, NotaryServerProperties, RateLimitProperties,
+    SessionProperties, TLSProperties, TransparencyProperties,
 };
 pub use error::NotaryServerError;
+pub use keys::{KeyInfo, KeyStatus};
 pub use server::{read_pem_file, run_server};
 pub use server_tracing::init_tracing;
+pub use signing::AttestationKey;
+pub use store::{FileSessionStore, MemorySessionStore, SessionStore};
+pub use transparency::AttestationLog;
 pub use util::parse_config_file;
@@ -0,0 +1,286 @@
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::{
+    io::Error as IoError,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::error::NotaryServerError;
+
+/// Buckets of the notarization duration histogram in seconds
+const DURATION_BUCKETS: &[f64] = &[
+    1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0,
+];
+
+/// Prometheus metrics of the notary server, exposed by the /metrics API
+#[derive(Debug)]
+pub struct Metrics {
+    registry: Registry,
+    /// Number of accepted prover connections, labelled by whether TLS is used
+    pub connections: IntCounterVec,
+    /// Number of session ids issued by the /session API
+    pub sessions_created: IntCounter,
+    /// Number of /session and /notarize requests rejected, labelled by reason
+    pub sessions_rejected: IntCounterVec,
+    /// Number of notarizations started, labelled by client type
+    pub sessions_started: IntCounterVec,
+    /// Number of notarizations completed, labelled by client type
+    pub sessions_completed: IntCounterVec,
+    /// Number of notarizations failed, labelled by client type and error kind
+    pub sessions_failed: IntCounterVec,
+    /// Duration of notarizations, labelled by client type and outcome
+    pub notarization_duration: HistogramVec,
+    /// Number of bytes sent to provers, labelled by client type
+    pub bytes_sent: IntCounterVec,
+    /// Number of bytes received from provers, labelled by client type
+    pub bytes_received: IntCounterVec,
+    /// Number of notarizations currently running
+    pub active_sessions: IntGauge,
+    /// Maximum number of concurrent notarizations
+    pub concurrency_limit: IntGauge,
+    /// Number of /notarize requests waiting for a permit
+    pub queued_sessions: IntGauge,
+}
+
+impl Metrics {
+    pub fn new(concurrency: usize) -> Self {
+        let registry = Registry::new_custom(Some("notary".to_string()), None)
+            .expect("Prefix of metrics should be valid");
+
+        let metrics = Self {
+            connections: IntCounterVec::new(
+                Opts::new("connections_total", "Number of accepted prover connections"),
+                &["tls"],
+            )
+            .unwrap(),
+            sessions_created: IntCounter::new(
+                "sessions_created_total",
+                "Number of session ids issued by the /session API",
+            )
+            .unwrap(),
+            sessions_rejected: IntCounterVec::new(
+                Opts::new(
+                    "sessions_rejected_total",
+                    "Number of /session and /notarize requests rejected",
+                ),
+                &["reason"],
+            )
+            .unwrap(),
+            sessions_started: IntCounterVec::new(
+                Opts::new("sessions_started_total", "Number of notarizations started"),
+                &["client_type"],
+            )
+            .unwrap(),
+            sessions_completed: IntCounterVec::new(
+                Opts::new(
+                    "sessions_completed_total",
+                    "Number of notarizations completed",
+                ),
+                &["client_type"],
+            )
+            .unwrap(),
+            sessions_failed: IntCounterVec::new(
+                Opts::new("sessions_failed_total", "Number of notarizations failed"),
+                &["client_type", "kind"],
+            )
+            .unwrap(),
+            notarization_duration: HistogramVec::new(
+                HistogramOpts::new("notarization_duration_seconds", "Duration of notarizations")
+                    .buckets(DURATION_BUCKETS.to_vec()),
+                &["client_type", "outcome"],
+            )
+            .unwrap(),
+            bytes_sent: IntCounterVec::new(
+                Opts::new("bytes_sent_total", "Number of bytes sent to provers"),
+                &["client_type"],
+            )
+            .unwrap(),
+            bytes_received: IntCounterVec::new(
+                Opts::new(
+                    "bytes_received_total",
+                    "Number of bytes received from provers",
+                ),
+                &["client_type"],
+            )
+            .unwrap(),
+            active_sessions: IntGauge::new(
+                "active_sessions",
+                "Number of notarizations currently running",
+            )
+            .unwrap(),
+            concurrency_limit: IntGauge::new(
+                "concurrency_limit",
+                "Maximum number of concurrent notarizations",
+            )
+            .unwrap(),
+            queued_sessions: IntGauge::new(
+                "queued_sessions",
+                "Number of /notarize requests waiting for a permit",
+            )
+            .unwrap(),
+            registry,
+        };
+
+        metrics.concurrency_limit.set(concurrency as i64);
+
+        let collectors: [Box<dyn prometheus::core::Collector>; 12] = [
+            Box::new(metrics.connections.clone()),
+            Box::new(metrics.sessions_created.clone()),
+            Box::new(metrics.sessions_rejected.clone()),
+            Box::new(metrics.sessions_started.clone()),
+            Box::new(metrics.sessions_completed.clone()),
+            Box::new(metrics.sessions_failed.clone()),
+            Box::new(metrics.notarization_duration.clone()),
+            Box::new(metrics.bytes_sent.clone()),
+            Box::new(metrics.bytes_received.clone()),
+            Box::new(metrics.active_sessions.clone()),
+            Box::new(metrics.concurrency_limit.clone()),
+            Box::new(metrics.queued_sessions.clone()),
+        ];
+        for collector in collectors {
+            metrics
+                .registry
+                .register(collector)
+                .expect("Metrics should only be registered once");
+        }
+
+        metrics
+    }
+
+    /// Record the outcome of a notarization
+    pub fn observe_notarization(
+        &self,
+        client_type: &str,
+        elapsed: Duration,
+        result: &Result<(), NotaryServerError>,
+    ) {
+        let outcome = match result {
+            Ok(_) => {
+                self.sessions_completed
+                    .with_label_values(&[client_type])
+                    .inc();
+                "completed"
+            }
+            Err(err) => {
+                self.sessions_failed
+                    .with_label_values(&[client_type, err.kind()])
+                    .inc();
+                "failed"
+            }
+        };
+
+        self.notarization_duration
+            .with_label_values(&[client_type, outcome])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Encode all metrics in the Prometheus text format
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        String::from_utf8(buffer).map_err(|err| prometheus::Error::Msg(err.to_string()))
+    }
+}
+
+/// Wrapper of the connection with a prover which counts the bytes sent and
+/// received
+pub struct MeteredStream<T> {
+    inner: T,
+    bytes_sent: IntCounter,
+    bytes_received: IntCounter,
+}
+
+impl<T> MeteredStream<T> {
+    pub fn new(inner: T, metrics: &Metrics, client_type: &str) -> Self {
+        Self {
+            inner,
+            bytes_sent: metrics.bytes_sent.with_label_values(&[client_type]),
+            bytes_received: metrics.bytes_received.with_label_values(&[client_type]),
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for MeteredStream<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), IoError>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            this.bytes_received
+                .inc_by((buf.filled().len() - filled) as u64);
+        }
+        poll
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for MeteredStream<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, IoError>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = poll {
+            this.bytes_sent.inc_by(written as u64);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), IoError>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), IoError>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_metered_stream() {
+        let metrics = Metrics::new(1);
+        let (client, server) = tokio::io::duplex(64);
+        let mut client = MeteredStream::new(client, &metrics, "tcp");
+        let mut server = server;
+
+        client.write_all(b"hello").await.unwrap();
+        server.write_all(b"hi").await.unwrap();
+
+        let mut buf = [0u8; 2];
+        client.read_exact(&mut buf).await.unwrap();
+
+        assert_eq!(metrics.bytes_sent.with_label_values(&["tcp"]).get(), 5);
+        assert_eq!(metrics.bytes_received.with_label_values(&["tcp"]).get(), 2);
+    }
+
+    #[test]
+    fn test_encode() {
+        let metrics = Metrics::new(4);
+        metrics.observe_notarization(
+            "websocket",
+            Duration::from_secs(3),
+            &Err(NotaryServerError::Connection("closed".to_string())),
+        );
+
+        let encoded = metrics.encode().unwrap();
+        assert!(encoded.contains("notary_concurrency_limit 4"));
+        assert!(encoded.contains(
+            r#"notary_sessions_failed_total{client_type="websocket",kind="connection"} 1"#
+        ));
+    }
+}
@@ -1,10 +1,13 @@
-use axum::http::request::Parts;
+use axum::http::{header::AUTHORIZATION, request::Parts};
 use axum_core::extract::{FromRef, FromRequestParts};
 use notary_common::X_API_KEY_HEADER;
 use std::collections::HashMap;
 use tracing::{error, trace};
 
-use crate::{auth::AuthorizationWhitelistRecord, types::NotaryGlobals, NotaryServerError};
+use crate::{
+    auth::AuthorizationWhitelistRecord, rate_limit::ClientId, types::NotaryGlobals,
+    NotaryServerError,
+};
 
 /// Auth middleware to prevent DOS
 pub struct AuthorizationMiddleware;
@@ -18,6 +21,41 @@ where
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let notary_globals = NotaryGlobals::from_ref(state);
+        if let Some(jwt_validator) = notary_globals.jwt_validator {
+            let token = parts
+                .headers
+                .get(AUTHORIZATION)
+                .and_then(|value| std::str::from_utf8(value.as_bytes()).ok())
+                .and_then(|value| value.strip_prefix("Bearer "));
+
+            return match token {
+                Some(token) => match jwt_validator.validate(token) {
+                    Ok(claims) => {
+                        trace!(sub = ?claims.sub, "Request authorized.");
+                        if let Some(sub) = &claims.sub {
+                            parts.extensions.insert(ClientId(format!("jwt:{sub}")));
+                        }
+                        // Make the claims available to the handlers, e.g. to apply per-user
+                        // limits
+                        parts.extensions.insert(claims);
+                        Ok(Self)
+                    }
+                    Err(err) => {
+                        let err_msg = format!("Invalid JWT: {err}");
+                        error!(err_msg);
+                        Err(NotaryServerError::UnauthorizedProverRequest(
+                            "Invalid JWT.".to_string(),
+                        ))
+                    }
+                },
+                None => {
+                    let err_msg = "Missing JWT.".to_string();
+                    error!(err_msg);
+                    Err(NotaryServerError::UnauthorizedProverRequest(err_msg))
+                }
+            };
+        }
+
         let Some(whitelist) = notary_globals.authorization_whitelist else {
             trace!("Skipping authorization as whitelist is not set.");
             return Ok(Self);
@@ -32,6 +70,9 @@ where
                 let whitelist = whitelist.lock().unwrap();
                 if api_key_is_valid(auth_header, &whitelist) {
                     trace!("Request authorized.");
+                    parts
+                        .extensions
+                        .insert(ClientId(format!("api-key:{auth_header}")));
                     Ok(Self)
                 } else {
                     let err_msg = "Invalid API key.".to_string();
@@ -0,0 +1,540 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::RateLimitProperties;
+
+/// Retry-After estimate used before any notarization has completed
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
+/// Weight of the latest notarization duration in the moving average
+const DURATION_SMOOTHING: f64 = 0.2;
+/// Interval between purges of idle clients from the rate limiter
+const PURGE_INTERVAL: Duration = Duration::from_secs(60);
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Identity of a client for rate limiting purpose, i.e. its API key, the
+/// subject of its JWT, or its IP address
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClientId(pub String);
+
+impl fmt::Display for ClientId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Reason why a request of a client is rejected by the rate limiter
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitExceeded {
+    /// Too many /session requests, retry after the duration
+    Requests(Duration),
+    /// Daily byte quota is used up, retry after the duration
+    Quota(Duration),
+}
+
+impl RateLimitExceeded {
+    pub fn retry_after(&self) -> Duration {
+        match self {
+            RateLimitExceeded::Requests(retry_after) | RateLimitExceeded::Quota(retry_after) => {
+                *retry_after
+            }
+        }
+    }
+}
+
+impl fmt::Display for RateLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitExceeded::Requests(_) => f.write_str("Rate limit exceeded."),
+            RateLimitExceeded::Quota(_) => f.write_str("Daily quota exceeded."),
+        }
+    }
+}
+
+/// State of a client in the rate limiter
+#[derive(Debug)]
+struct ClientState {
+    /// Remaining tokens of the token bucket
+    tokens: f64,
+    last_refill: Instant,
+    /// Day since unix epoch of the byte quota usage
+    day: u64,
+    bytes_used: u64,
+}
+
+/// Bytes reserved from the daily quota of a client for a session
+#[derive(Debug)]
+struct Reservation {
+    client: ClientId,
+    /// Day since unix epoch of the quota the bytes were reserved from
+    day: u64,
+    bytes: u64,
+    /// The reservation is refunded if it is not settled before
+    expiry: Instant,
+}
+
+#[derive(Debug)]
+struct RateLimiterState {
+    clients: HashMap<ClientId, ClientState>,
+    /// Reservations by session id
+    reservations: HashMap<String, Reservation>,
+    last_purge: Instant,
+}
+
+/// Per-client token-bucket rate limit of /session requests and daily byte
+/// quota of notarizations
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitProperties,
+    state: Mutex<RateLimiterState>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitProperties) -> Self {
+        Self {
+            config,
+            state: Mutex::new(RateLimiterState {
+                clients: HashMap::new(),
+                reservations: HashMap::new(),
+                last_purge: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a token from the bucket of the client for a /session request
+    pub fn check_request(&self, client: &ClientId) -> Result<(), RateLimitExceeded> {
+        let Some(rate) = self.refill_rate() else {
+            return Ok(());
+        };
+        let now = Instant::now();
+
+        let mut state = self.state.lock().unwrap();
+        let client = self.client(&mut state, client, now);
+        client.tokens = (client.tokens
+            + now.duration_since(client.last_refill).as_secs_f64() * rate)
+            .min(self.capacity());
+        client.last_refill = now;
+
+        if client.tokens >= 1.0 {
+            client.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(RateLimitExceeded::Requests(ceil_secs(
+                (1.0 - client.tokens) / rate,
+            )))
+        }
+    }
+
+    /// Reserve bytes from the daily quota of the client for the notarization
+    /// of a session. The reservation is refunded if the session is not
+    /// started before `ttl`, see [`RateLimiter::start_session`]
+    pub fn reserve_bytes(
+        &self,
+        client: &ClientId,
+        session_id: &str,
+        bytes: u64,
+        ttl: Duration,
+    ) -> Result<(), RateLimitExceeded> {
+        let Some(quota) = self.config.daily_bytes_quota else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time should be after unix epoch")
+            .as_secs();
+        let today = unix_secs / SECONDS_PER_DAY;
+
+        let mut state = self.state.lock().unwrap();
+        refund_expired(&mut state, now);
+        let client_state = self.client(&mut state, client, now);
+        if client_state.day != today {
+            client_state.day = today;
+            client_state.bytes_used = 0;
+        }
+
+        if client_state.bytes_used.saturating_add(bytes) > quota {
+            // The quota is reset at midnight UTC
+            return Err(RateLimitExceeded::Quota(Duration::from_secs(
+                SECONDS_PER_DAY - unix_secs % SECONDS_PER_DAY,
+            )));
+        }
+
+        client_state.bytes_used += bytes;
+        state.reservations.insert(
+            session_id.to_string(),
+            Reservation {
+                client: client.clone(),
+                day: today,
+                bytes,
+                expiry: now + ttl,
+            },
+        );
+        Ok(())
+    }
+
+    /// Keep the reservation of a session whose notarization starts until it is
+    /// settled, or refund it after `timeout` if it never is
+    pub fn start_session(&self, session_id: &str, timeout: Duration) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(reservation) = state.reservations.get_mut(session_id) {
+            reservation.expiry = Instant::now() + timeout;
+        }
+    }
+
+    /// Settle the reservation of a session once its notarization is over,
+    /// refunding the reserved bytes which were not notarized. If `used` is
+    /// None, e.g. the notarization failed, the entire reservation is charged
+    pub fn settle(&self, session_id: &str, used: Option<u64>) {
+        let mut state = self.state.lock().unwrap();
+        let Some(reservation) = state.reservations.remove(session_id) else {
+            return;
+        };
+        if let Some(used) = used {
+            refund(
+                &mut state,
+                &reservation,
+                reservation.bytes.saturating_sub(used),
+            );
+        }
+    }
+
+    /// Tokens refilled per second, None if requests are not rate limited
+    fn refill_rate(&self) -> Option<f64> {
+        self.config
+            .requests_per_minute
+            .map(|requests| requests as f64 / 60.0)
+    }
+
+    fn capacity(&self) -> f64 {
+        self.config
+            .burst
+            .or(self.config.requests_per_minute)
+            .unwrap_or_default() as f64
+    }
+
+    /// Returns the state of the client, periodically removing idle clients to
+    /// bound memory usage
+    fn client<'a>(
+        &self,
+        state: &'a mut RateLimiterState,
+        client: &ClientId,
+        now: Instant,
+    ) -> &'a mut ClientState {
+        if now.duration_since(state.last_purge) >= PURGE_INTERVAL {
+            refund_expired(state, now);
+            let capacity = self.capacity();
+            let rate = self.refill_rate().unwrap_or_default();
+            let today = day_now();
+            state.clients.retain(|_, client| {
+                let refilled = client.tokens
+                    + now.duration_since(client.last_refill).as_secs_f64() * rate
+                    >= capacity;
+                let quota_reset = client.day != today || client.bytes_used == 0;
+                !(refilled && quota_reset)
+            });
+            state.last_purge = now;
+        }
+
+        let capacity = self.capacity();
+        state
+            .clients
+            .entry(client.clone())
+            .or_insert_with(|| ClientState {
+                tokens: capacity,
+                last_refill: now,
+                day: day_now(),
+                bytes_used: 0,
+            })
+    }
+}
+
+/// Refund the reservations of sessions which were never started or settled
+fn refund_expired(state: &mut RateLimiterState, now: Instant) {
+    let expired: Vec<_> = state
+        .reservations
+        .iter()
+        .filter(|(_, reservation)| reservation.expiry <= now)
+        .map(|(session_id, _)| session_id.clone())
+        .collect();
+    for session_id in expired {
+        let reservation = state
+            .reservations
+            .remove(&session_id)
+            .expect("Reservation should exist");
+        refund(state, &reservation, reservation.bytes);
+    }
+}
+
+/// Return bytes to the quota of the client, unless the quota was reset since
+/// they were reserved
+fn refund(state: &mut RateLimiterState, reservation: &Reservation, bytes: u64) {
+    if let Some(client) = state.clients.get_mut(&reservation.client) {
+        if client.day == reservation.day {
+            client.bytes_used = client.bytes_used.saturating_sub(bytes);
+        }
+    }
+}
+
+/// Bounded FIFO queue of /notarize requests waiting for one of the
+/// notarization permits
+#[derive(Debug)]
+pub struct NotarizationQueue {
+    semaphore: Arc<Semaphore>,
+    concurrency: usize,
+    /// Maximum number of requests waiting for a permit
+    capacity: usize,
+    /// Maximum duration a request waits for a permit
+    timeout: Duration,
+    waiting: AtomicUsize,
+    /// Moving average of notarization durations
+    average_duration: Mutex<Option<Duration>>,
+}
+
+impl NotarizationQueue {
+    pub fn new(concurrency: usize, config: &RateLimitProperties) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(concurrency)),
+            concurrency,
+            capacity: config.queue_size,
+            timeout: Duration::from_secs(config.queue_timeout),
+            waiting: AtomicUsize::new(0),
+            average_duration: Mutex::new(None),
+        }
+    }
+
+    /// Number of permits which are not in use
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Number of requests waiting for a permit
+    pub fn waiting(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
+    }
+
+    /// Acquire a permit for notarization, waiting in the queue if all permits
+    /// are in use. Returns the estimated duration after which the request
+    /// should be retried if the queue is full or the wait timed out
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit, Duration> {
+        if let Ok(permit) = self.semaphore.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+
+        // Join the queue if it is not full
+        if self
+            .waiting
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |waiting| {
+                (waiting < self.capacity).then_some(waiting + 1)
+            })
+            .is_err()
+        {
+            return Err(self.retry_after());
+        }
+        // Leave the queue even if the request is dropped while waiting
+        let _guard = WaitingGuard(&self.waiting);
+
+        // Tokio's semaphore hands out permits in FIFO order
+        match tokio::time::timeout(self.timeout, self.semaphore.clone().acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            _ => Err(self.retry_after()),
+        }
+    }
+
+    /// Record the duration of a completed notarization
+    pub fn record(&self, elapsed: Duration) {
+        let mut average = self.average_duration.lock().unwrap();
+        *average = Some(match *average {
+            Some(average) => {
+                average.mul_f64(1.0 - DURATION_SMOOTHING) + elapsed.mul_f64(DURATION_SMOOTHING)
+            }
+            None => elapsed,
+        });
+    }
+
+    /// Estimate when a permit will be available for a new request from the
+    /// average notarization duration and the number of waiting requests
+    pub fn retry_after(&self) -> Duration {
+        let Some(average) = *self.average_duration.lock().unwrap() else {
+            return DEFAULT_RETRY_AFTER;
+        };
+        let ahead = self.waiting() + 1;
+        ceil_secs(average.as_secs_f64() * ahead as f64 / self.concurrency.max(1) as f64)
+    }
+}
+
+struct WaitingGuard<'a>(&'a AtomicUsize);
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Round up to whole seconds, which is the granularity of Retry-After, with a
+/// minimum of one second
+fn ceil_secs(secs: f64) -> Duration {
+    Duration::from_secs((secs.ceil() as u64).max(1))
+}
+
+fn day_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time should be after unix epoch")
+        .as_secs()
+        / SECONDS_PER_DAY
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn client(id: &str) -> ClientId {
+        ClientId(id.to_string())
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let limiter = RateLimiter::new(RateLimitProperties {
+            requests_per_minute: Some(6),
+            burst: Some(2),
+            ..Default::default()
+        });
+
+        assert!(limiter.check_request(&client("alice")).is_ok());
+        assert!(limiter.check_request(&client("alice")).is_ok());
+        let err = limiter.check_request(&client("alice")).unwrap_err();
+        // One token is refilled every 10 seconds
+        assert!(matches!(err, RateLimitExceeded::Requests(_)));
+        assert!(err.retry_after() <= Duration::from_secs(10));
+        assert!(err.retry_after() >= Duration::from_secs(9));
+
+        // Other clients have their own bucket
+        assert!(limiter.check_request(&client("bob")).is_ok());
+    }
+
+    #[test]
+    fn test_daily_quota() {
+        let limiter = RateLimiter::new(RateLimitProperties {
+            daily_bytes_quota: Some(100),
+            ..Default::default()
+        });
+
+        assert!(limiter
+            .reserve_bytes(&client("alice"), "1", 60, TTL)
+            .is_ok());
+        let err = limiter
+            .reserve_bytes(&client("alice"), "2", 60, TTL)
+            .unwrap_err();
+        assert!(matches!(err, RateLimitExceeded::Quota(_)));
+        assert!(err.retry_after() <= Duration::from_secs(SECONDS_PER_DAY));
+        assert!(limiter
+            .reserve_bytes(&client("alice"), "3", 40, TTL)
+            .is_ok());
+        assert!(limiter.reserve_bytes(&client("bob"), "4", 100, TTL).is_ok());
+    }
+
+    #[test]
+    fn test_quota_refund() {
+        let limiter = RateLimiter::new(RateLimitProperties {
+            daily_bytes_quota: Some(100),
+            ..Default::default()
+        });
+
+        // Only the notarized bytes are charged
+        limiter
+            .reserve_bytes(&client("alice"), "1", 100, TTL)
+            .unwrap();
+        limiter.start_session("1", TTL);
+        limiter.settle("1", Some(30));
+        assert!(limiter
+            .reserve_bytes(&client("alice"), "2", 70, TTL)
+            .is_ok());
+
+        // Failed notarizations are charged the entire reservation
+        limiter.settle("2", None);
+        assert!(limiter
+            .reserve_bytes(&client("alice"), "3", 1, TTL)
+            .is_err());
+
+        // Sessions which are never started are refunded once they expire
+        limiter
+            .reserve_bytes(&client("bob"), "4", 100, Duration::ZERO)
+            .unwrap();
+        assert!(limiter.reserve_bytes(&client("bob"), "5", 100, TTL).is_ok());
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let limiter = RateLimiter::new(RateLimitProperties::default());
+        for _ in 0..100 {
+            assert!(limiter.check_request(&client("alice")).is_ok());
+            assert!(limiter
+                .reserve_bytes(&client("alice"), "session", u64::MAX, TTL)
+                .is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_queue() {
+        let queue = Arc::new(NotarizationQueue::new(
+            1,
+            &RateLimitProperties {
+                queue_size: 1,
+                queue_timeout: 5,
+                ..Default::default()
+            },
+        ));
+
+        let permit = queue.acquire().await.unwrap();
+
+        // The second request waits in the queue
+        let waiting = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire().await }
+        });
+        while queue.waiting() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // The queue is full
+        assert_eq!(queue.acquire().await.unwrap_err(), DEFAULT_RETRY_AFTER);
+
+        // The waiting request gets the permit once it is released
+        queue.record(Duration::from_secs(10));
+        drop(permit);
+        let permit = waiting.await.unwrap().unwrap();
+        assert_eq!(queue.waiting(), 0);
+        assert_eq!(queue.available_permits(), 0);
+
+        // Nobody is waiting, so the next permit is expected after one average duration
+        assert_eq!(queue.retry_after(), Duration::from_secs(10));
+        drop(permit);
+        assert_eq!(queue.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_queue_timeout() {
+        let queue = NotarizationQueue::new(
+            1,
+            &RateLimitProperties {
+                queue_size: 1,
+                queue_timeout: 0,
+                ..Default::default()
+            },
+        );
+
+        let _permit = queue.acquire().await.unwrap();
+        assert!(queue.acquire().await.is_err());
+        assert_eq!(queue.waiting(), 0);
+    }
+}
@@ -0,0 +1,229 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use eyre::{eyre, Result};
+use notify::{
+    event::ModifyKind, Error, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, RwLock},
+};
+use tracing::{debug, error, info};
+
+use crate::{
+    error::NotaryServerError,
+    keys::{load_revoked_attestations, KeyRing},
+    types::{NotaryGlobals, RevocationsResponse},
+};
+
+/// Lists of the revoked attestations signed by each published key. The lists
+/// are only signed when the revoked attestations or the keys change, and are
+/// otherwise served from the cache
+pub struct RevocationLists {
+    /// Path of the file listing the revoked attestations, if any
+    path: Option<String>,
+    key_ring: Arc<KeyRing>,
+    cache: RwLock<Cache>,
+    /// Held while signing, so that the lists are signed once per change
+    signing: Mutex<()>,
+}
+
+/// Signed lists, as the JSON encoded response of the /revocations API
+struct Cache {
+    /// Generation of the key ring which signed the lists
+    generation: u64,
+    body: Bytes,
+}
+
+impl std::fmt::Debug for RevocationLists {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RevocationLists")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl RevocationLists {
+    /// Sign the lists of the attestations revoked in the file, or empty lists
+    /// if no path is given. This blocks on the file and the signers
+    pub fn new(path: Option<String>, key_ring: Arc<KeyRing>) -> Result<Self> {
+        let cache = sign_lists(path.as_deref(), &key_ring)?;
+        Ok(Self {
+            path,
+            key_ring,
+            cache: RwLock::new(cache),
+            signing: Mutex::new(()),
+        })
+    }
+
+    /// Sign the lists again, e.g. when the file is modified. The current lists
+    /// are kept if signing fails. This blocks on the file and the signers, so
+    /// it should be run in the blocking thread pool
+    pub fn refresh(&self) -> Result<()> {
+        let _signing = self.signing.lock().unwrap();
+        self.sign()
+    }
+
+    /// Sign the lists again if the keys were reloaded since they were signed
+    pub fn refresh_if_outdated(&self) -> Result<()> {
+        let _signing = self.signing.lock().unwrap();
+        if self.is_outdated() {
+            self.sign()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the JSON encoded response of the /revocations API
+    pub fn body(&self) -> Bytes {
+        self.cache.read().unwrap().body.clone()
+    }
+
+    fn is_outdated(&self) -> bool {
+        self.cache.read().unwrap().generation != self.key_ring.generation()
+    }
+
+    fn sign(&self) -> Result<()> {
+        let cache = sign_lists(self.path.as_deref(), &self.key_ring)?;
+        *self.cache.write().unwrap() = cache;
+        Ok(())
+    }
+}
+
+fn sign_lists(path: Option<&str>, key_ring: &KeyRing) -> Result<Cache> {
+    // Read the generation first, so that keys reloaded while signing are
+    // detected by the next request
+    let generation = key_ring.generation();
+    let revoked = match path {
+        Some(path) => load_revoked_attestations(path)?,
+        None => Default::default(),
+    };
+    let lists = key_ring.revocation_lists(&revoked)?;
+    let body = serde_json::to_vec(&RevocationsResponse { lists })?;
+    Ok(Cache {
+        generation,
+        body: body.into(),
+    })
+}
+
+// Setup a watcher to detect any changes to the revocation list file, the same
+// way as the authorization whitelist watcher. When the file is modified, the
+// watcher thread will sign the lists again
+pub fn watch_and_refresh_revocation_lists(
+    path: String,
+    lists: Arc<RevocationLists>,
+) -> Result<RecommendedWatcher> {
+    let mut watcher = RecommendedWatcher::new(
+        move |event: Result<Event, Error>| match event {
+            Ok(event) => {
+                // Only sign again if it's an event that modified the file data
+                if let EventKind::Modify(ModifyKind::Data(_)) = event.kind {
+                    debug!("Revocation list is modified");
+                    match lists.refresh() {
+                        Ok(()) => info!("Successfully signed revocation lists!"),
+                        // Ensure that error from signing doesn't bring the server down
+                        Err(err) => error!("Failed to sign revocation lists: {err}"),
+                    }
+                }
+            }
+            Err(err) => {
+                error!("Error occured when watcher detected an event: {err}")
+            }
+        },
+        notify::Config::default(),
+    )
+    .map_err(|err| eyre!("Error occured when setting up watcher for hot reload: {err}"))?;
+
+    watcher
+        .watch(Path::new(&path), RecursiveMode::NonRecursive)
+        .map_err(|err| eyre!("Error occured when starting up watcher for hot reload: {err}"))?;
+
+    Ok(watcher)
+}
+
+/// Handler of the /revocations API, returns the lists of the revoked
+/// attestations signed by each published key
+pub async fn revocations(State(notary_globals): State<NotaryGlobals>) -> Response {
+    let lists = notary_globals.revocation_lists.clone();
+
+    // Sign with the new keys in the blocking thread pool, as the signers may
+    // block, e.g. when signing with a remote signer
+    if lists.is_outdated() {
+        let result = tokio::task::spawn_blocking({
+            let lists = lists.clone();
+            move || lists.refresh_if_outdated()
+        })
+        .await
+        .map_err(|err| eyre!("Revocation list task failed: {err}"))
+        .and_then(|result| result);
+        if let Err(err) = result {
+            error!("Failed to sign revocation lists: {err}");
+            return NotaryServerError::Unexpected(err).into_response();
+        }
+    }
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/json")],
+        lists.body(),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use tempfile::tempdir;
+    use tlsn_core::{
+        attestation::Uid,
+        signing::{Secp256k1Signer, Signer},
+        CryptoProvider,
+    };
+
+    use super::*;
+
+    fn revoked(lists: &RevocationLists) -> Vec<Uid> {
+        let response: RevocationsResponse = serde_json::from_slice(&lists.body()).unwrap();
+        let provider = CryptoProvider::default();
+        response
+            .lists
+            .into_iter()
+            .flat_map(|list| list.verify(&provider).unwrap().revoked)
+            .collect()
+    }
+
+    #[test]
+    fn test_revocation_lists() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("revoked.txt");
+        fs::write(&path, "000102030405060708090a0b0c0d0e0f\n").unwrap();
+
+        let signer: Box<dyn Signer + Send + Sync> =
+            Box::new(Secp256k1Signer::new(&[1u8; 32]).unwrap());
+        let key_ring = Arc::new(KeyRing::single(signer).unwrap());
+        let lists =
+            RevocationLists::new(Some(path.to_str().unwrap().to_string()), key_ring.clone())
+                .unwrap();
+        assert_eq!(revoked(&lists), vec![Uid(std::array::from_fn(|i| i as u8))]);
+
+        // The cached lists are served until they are signed again
+        fs::write(&path, "").unwrap();
+        assert_eq!(revoked(&lists).len(), 1);
+        lists.refresh().unwrap();
+        assert!(revoked(&lists).is_empty());
+        assert!(!lists.is_outdated());
+
+        // The current lists are kept if the file is invalid
+        fs::write(&path, "0001").unwrap();
+        assert!(lists.refresh().is_err());
+        assert!(revoked(&lists).is_empty());
+
+        // No file means no revoked attestations
+        let lists = RevocationLists::new(None, key_ring).unwrap();
+        assert!(revoked(&lists).is_empty());
+    }
+}
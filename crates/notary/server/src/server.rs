@@ -1,6 +1,6 @@
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
     middleware::from_extractor_with_state,
     response::{Html, IntoResponse},
     routing::{get, post},
@@ -18,8 +18,9 @@ use std::{
     net::{IpAddr, SocketAddr},
     pin::Pin,
     sync::{Arc, Mutex},
+    time::Duration,
 };
-use tlsn_core::CryptoProvider;
+use tlsn_core::signing::{RemoteSigner, RemoteSignerEndpoint, Signer};
 use tokio::{fs::File, io::AsyncReadExt, net::TcpListener};
 use tokio_rustls::{rustls, TlsAcceptor};
 use tower_http::cors::CorsLayer;
@@ -28,33 +29,50 @@ use tracing::{debug, error, info, warn};
 use zeroize::Zeroize;
 
 use crate::{
-    auth::{load_authorization_whitelist, watch_and_reload_authorization_whitelist},
+    auth::{
+        jwt::load_jwt_validator, load_authorization_whitelist,
+        watch_and_reload_authorization_whitelist,
+    },
     config::{NotarizationProperties, NotaryServerProperties},
     error::NotaryServerError,
+    keys::{watch_and_reload_keys, KeyRing},
+    metrics::Metrics,
     middleware::AuthorizationMiddleware,
+    rate_limit::{NotarizationQueue, RateLimiter},
+    revocation::{revocations, watch_and_refresh_revocation_lists, RevocationLists},
     service::{initialize, upgrade_protocol},
-    signing::AttestationKey,
-    types::{InfoResponse, NotaryGlobals},
+    signing::{parse_signature_algorithm, AttestationKey},
+    store::{build_session_store, spawn_reaper},
+    transparency::{consistency_proof, inclusion_proof, tree_heads, AttestationLog},
+    types::{InfoResponse, KeysResponse, NotaryGlobals},
 };
 
 #[cfg(feature = "tee_quote")]
 use crate::tee::quote;
 
-use tokio::sync::Semaphore;
-
 /// Start a TCP server (with or without TLS) to accept notarization request for
 /// both TCP and WebSocket clients
 #[tracing::instrument(skip(config))]
 pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotaryServerError> {
-    let attestation_key = get_attestation_key(&config.notarization).await?;
-    let verifying_key_pem = attestation_key
-        .verifying_key_pem()
-        .map_err(|err| eyre!("Failed to get verifying key in PEM format: {err}"))?;
-
-    #[cfg(feature = "tee_quote")]
-    let verifying_key_bytes = attestation_key.verifying_key_bytes();
-
-    let crypto_provider = build_crypto_provider(attestation_key);
+    // Load the notary signing keys, with hot reload if they are in a key directory
+    let (key_ring, key_watcher) = if let Some(key_directory) = &config.notarization.key_directory {
+        debug!("Loading notary server's signing keys from {key_directory}");
+        let key_ring = Arc::new(KeyRing::load(key_directory)?);
+        let watcher = watch_and_reload_keys(key_directory.clone(), key_ring.clone())?;
+        debug!("Successfully setup watcher for hot reload of notary signing keys!");
+        (key_ring, Some(watcher))
+    } else {
+        let signer = if let Some(endpoint) = &config.notarization.remote_signer {
+            connect_remote_signer(endpoint, &config.notarization.signature_algorithm).await?
+        } else {
+            get_attestation_key(&config.notarization)
+                .await?
+                .into_signer()
+        };
+        (Arc::new(KeyRing::single(signer)?), None)
+    };
+    // Need to keep the watcher, else it will be dropped and stop listening
+    let _key_watcher = key_watcher;
 
     // Build TLS acceptor if it is turned on
     let tls_acceptor = if !config.tls.enabled {
@@ -96,6 +114,8 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
     if watcher.is_some() {
         debug!("Successfully setup watcher for hot reload of authorization whitelist!");
     }
+    // Load the JWT public keys if JWT authorization is turned on
+    let jwt_validator = load_jwt_validator(config)?.map(Arc::new);
 
     let notary_address = SocketAddr::new(
         IpAddr::V4(config.host.parse().map_err(|err| {
@@ -109,31 +129,87 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
 
     info!("Listening for TCP traffic at {}", notary_address);
 
+    // Build the session store and periodically remove expired session ids from it
+    let store = build_session_store(&config.session)?;
+    let _reaper = spawn_reaper(
+        store.clone(),
+        Duration::from_secs(config.session.reap_interval),
+    )?;
+
+    let metrics = Arc::new(Metrics::new(config.concurrency));
+
+    // Open the transparency log of signed attestations if it is turned on
+    let transparency_log = if config.transparency.enabled {
+        let log = AttestationLog::open(config.transparency.log_path.as_deref(), key_ring.clone())?;
+        debug!("Successfully opened transparency log: {log:?}");
+        Some(Arc::new(log))
+    } else {
+        None
+    };
+
+    // Sign the revocation lists upfront, with hot reload if there is a revocation
+    // list file
+    let revocation_list_path = config.notarization.revocation_list_path.clone();
+    let revocation_lists = Arc::new(RevocationLists::new(
+        revocation_list_path.clone(),
+        key_ring.clone(),
+    )?);
+    let revocation_watcher = match revocation_list_path {
+        Some(path) => {
+            let watcher = watch_and_refresh_revocation_lists(path, revocation_lists.clone())?;
+            debug!("Successfully setup watcher for hot reload of revocation lists!");
+            Some(watcher)
+        }
+        None => None,
+    };
+    // Need to keep the watcher, else it will be dropped and stop listening
+    let _revocation_watcher = revocation_watcher;
+
     let protocol = Arc::new(http1::Builder::new());
     let notary_globals = NotaryGlobals::new(
-        Arc::new(crypto_provider),
+        key_ring,
         config.notarization.clone(),
+        config.session.clone(),
+        store,
         authorization_whitelist,
-        Arc::new(Semaphore::new(config.concurrency)),
+        jwt_validator,
+        Arc::new(NotarizationQueue::new(
+            config.concurrency,
+            &config.rate_limit,
+        )),
+        Arc::new(RateLimiter::new(config.rate_limit.clone())),
+        metrics.clone(),
+        transparency_log,
+        revocation_lists,
     );
 
     // Parameters needed for the info endpoint
     let version = env!("CARGO_PKG_VERSION").to_string();
     let git_commit_hash = env!("GIT_COMMIT_HASH").to_string();
 
-    // Parameters needed for the root / endpoint
-    let html_string = config.html_info.clone();
-    let html_info = Html(
-        html_string
-            .replace("{version}", &version)
-            .replace("{git_commit_hash}", &git_commit_hash)
-            .replace("{public_key}", &verifying_key_pem),
-    );
+    // Parameters needed for the root / endpoint, the public key is filled in per
+    // request as the active key may be rotated
+    let html_info = config
+        .html_info
+        .replace("{version}", &version)
+        .replace("{git_commit_hash}", &git_commit_hash);
 
     let router = Router::new()
         .route(
             "/",
-            get(|| async move { (StatusCode::OK, html_info).into_response() }),
+            get(|State(notary_globals): State<NotaryGlobals>| async move {
+                match notary_globals.key_ring.active_key() {
+                    Ok(key) => (
+                        StatusCode::OK,
+                        Html(html_info.replace("{public_key}", &key.public_key)),
+                    )
+                        .into_response(),
+                    Err(err) => {
+                        error!("{err}");
+                        NotaryServerError::Unexpected(err).into_response()
+                    }
+                }
+            }),
         )
         .route(
             "/healthcheck",
@@ -141,12 +217,27 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
         )
         .route(
             "/info",
-            get(|| async move {
+            get(|State(notary_globals): State<NotaryGlobals>| async move {
+                // Publish the key which is currently used for signing
+                let key_ring = &notary_globals.key_ring;
+                let public_key = match key_ring.active_key() {
+                    Ok(key) => key.public_key,
+                    Err(err) => {
+                        error!("{err}");
+                        return NotaryServerError::Unexpected(err).into_response();
+                    }
+                };
+                #[cfg(feature = "tee_quote")]
+                let verifying_key_bytes = match key_ring.active_verifying_key_bytes() {
+                    Ok(bytes) => bytes,
+                    Err(err) => return NotaryServerError::Unexpected(err).into_response(),
+                };
+
                 (
                     StatusCode::OK,
                     Json(InfoResponse {
                         version,
-                        public_key: verifying_key_pem,
+                        public_key,
                         git_commit_hash,
                         #[cfg(feature = "tee_quote")]
                         quote: quote(verifying_key_bytes).await,
@@ -155,6 +246,49 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
                     .into_response()
             }),
         )
+        .route(
+            "/keys",
+            get(|State(notary_globals): State<NotaryGlobals>| async move {
+                (
+                    StatusCode::OK,
+                    Json(KeysResponse {
+                        keys: notary_globals.key_ring.keys(),
+                    }),
+                )
+                    .into_response()
+            }),
+        )
+        .route("/revocations", get(revocations))
+        .route("/transparency/sth", get(tree_heads))
+        .route("/transparency/inclusion", get(inclusion_proof))
+        .route("/transparency/consistency", get(consistency_proof))
+        .route(
+            "/metrics",
+            get(|State(notary_globals): State<NotaryGlobals>| async move {
+                // Sample the semaphore saturation at scrape time
+                let metrics = &notary_globals.metrics;
+                metrics.active_sessions.set(
+                    metrics.concurrency_limit.get()
+                        - notary_globals.queue.available_permits() as i64,
+                );
+                metrics
+                    .queued_sessions
+                    .set(notary_globals.queue.waiting() as i64);
+
+                match metrics.encode() {
+                    Ok(metrics) => (
+                        StatusCode::OK,
+                        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+                        metrics,
+                    )
+                        .into_response(),
+                    Err(err) => {
+                        error!("Failed to encode metrics: {err}");
+                        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                    }
+                }
+            }),
+        )
         .route("/session", post(initialize))
         // Not applying auth middleware to /notarize endpoint for now as we can rely on our
         // short-lived session id generated from /session endpoint, as it is not possible
@@ -172,14 +306,22 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
     loop {
         // Poll and await for any incoming connection, ensure that all operations inside
         // are infallible to prevent bringing down the server
-        let stream = match poll_fn(|cx| Pin::new(&mut listener).poll_accept(cx)).await {
-            Ok((stream, _)) => stream,
+        let (stream, address) = match poll_fn(|cx| Pin::new(&mut listener).poll_accept(cx)).await {
+            Ok(connection) => connection,
             Err(err) => {
                 error!("{}", NotaryServerError::Connection(err.to_string()));
                 continue;
             }
         };
         debug!("Received a prover's TCP connection");
+        metrics
+            .connections
+            .with_label_values(&[if tls_acceptor.is_some() {
+                "true"
+            } else {
+                "false"
+            }])
+            .inc();
 
         let tower_service = router.clone();
         let tls_acceptor = tls_acceptor.clone();
@@ -195,7 +337,9 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
                         // Reference: https://github.com/tokio-rs/axum/blob/5201798d4e4d4759c208ef83e30ce85820c07baa/examples/low-level-rustls/src/main.rs#L67-L80
                         let io = TokioIo::new(stream);
                         let hyper_service =
-                            hyper::service::service_fn(move |request: Request<Incoming>| {
+                            hyper::service::service_fn(move |mut request: Request<Incoming>| {
+                                // Make the prover's address available for rate limiting
+                                request.extensions_mut().insert(ConnectInfo(address));
                                 tower_service.clone().call(request)
                             });
                         // Serve different requests using the same hyper protocol and axum router
@@ -227,7 +371,9 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
                 // Reference: https://github.com/tokio-rs/axum/blob/5201798d4e4d4759c208ef83e30ce85820c07baa/examples/low-level-rustls/src/main.rs#L67-L80
                 let io = TokioIo::new(stream);
                 let hyper_service =
-                    hyper::service::service_fn(move |request: Request<Incoming>| {
+                    hyper::service::service_fn(move |mut request: Request<Incoming>| {
+                        // Make the prover's address available for rate limiting
+                        request.extensions_mut().insert(ConnectInfo(address));
                         tower_service.clone().call(request)
                     });
                 // Serve different requests using the same hyper protocol and axum router
@@ -242,12 +388,6 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
     }
 }
 
-fn build_crypto_provider(attestation_key: AttestationKey) -> CryptoProvider {
-    let mut provider = CryptoProvider::default();
-    provider.signer.set_signer(attestation_key.into_signer());
-    provider
-}
-
 /// Get notary signing key for attestations.
 /// Generate a random key if user does not provide a static key.
 async fn get_attestation_key(config: &NotarizationProperties) -> Result<AttestationKey> {
@@ -276,6 +416,24 @@ async fn get_attestation_key(config: &NotarizationProperties) -> Result<Attestat
     Ok(key)
 }
 
+/// Connect to the remote signer which signs attestations with a key kept
+/// outside of the notary server
+async fn connect_remote_signer(
+    endpoint: &str,
+    signature_algorithm: &str,
+) -> Result<Box<dyn Signer + Send + Sync>> {
+    debug!("Connecting to notary server's remote signer at {endpoint}");
+
+    let endpoint: RemoteSignerEndpoint = endpoint.parse()?;
+    let alg = parse_signature_algorithm(signature_algorithm)?;
+    let signer = tokio::task::spawn_blocking(move || RemoteSigner::connect(endpoint, alg))
+        .await?
+        .map_err(|err| eyre!("Failed to connect to remote signer: {err}"))?;
+
+    debug!("Successfully connected to notary server's remote signer!");
+    Ok(Box::new(signer))
+}
+
 /// Read a PEM-formatted file and return its buffer reader
 pub async fn read_pem_file(file_path: &str) -> Result<BufReader<StdFile>> {
     let key_file = File::open(file_path).await?.into_std().await;
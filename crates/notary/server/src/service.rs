@@ -4,15 +4,23 @@ pub mod websocket;
 
 use axum::{
     body::Body,
-    extract::{rejection::JsonRejection, FromRequestParts, Query, State},
+    extract::{rejection::JsonRejection, ConnectInfo, FromRequestParts, Query, State},
     http::{header, request::Parts, StatusCode},
     response::{IntoResponse, Json, Response},
+    Extension,
 };
 use axum_macros::debug_handler;
 use eyre::eyre;
-use notary_common::{NotarizationSessionRequest, NotarizationSessionResponse};
-use std::time::Duration;
-use tlsn_common::config::ProtocolConfigValidator;
+use notary_common::{
+    NotarizationSessionRejection, NotarizationSessionRequest, NotarizationSessionResponse,
+};
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+use tlsn_common::config::{
+    ProtocolConfig, ProtocolConfigValidator, ProtocolConfigValidatorBuilderError,
+};
 use tlsn_core::attestation::AttestationConfig;
 use tlsn_verifier::{Verifier, VerifierConfig};
 use tokio::{
@@ -24,12 +32,16 @@ use tracing::{debug, error, info, trace};
 use uuid::Uuid;
 
 use crate::{
+    auth::jwt::JwtClaims,
+    config::NotarizationProperties,
     error::NotaryServerError,
+    rate_limit::ClientId,
     service::{
         axum_websocket::{header_eq, WebSocketUpgrade},
         tcp::{tcp_notarize, TcpUpgrade},
         websocket::websocket_notarize,
     },
+    store::run_blocking,
     types::{NotarizationRequestQuery, NotaryGlobals},
 };
 
@@ -77,41 +89,83 @@ pub async fn upgrade_protocol(
     State(notary_globals): State<NotaryGlobals>,
     Query(params): Query<NotarizationRequestQuery>,
 ) -> Response {
-    let permit = if let Ok(permit) = notary_globals.semaphore.clone().try_acquire_owned() {
-        permit
-    } else {
-        // TODO: estimate the time more precisely to avoid unnecessary retries.
-        return Response::builder()
-            .status(StatusCode::SERVICE_UNAVAILABLE)
-            .header("Retry-After", 5)
-            .body(Body::default())
-            .expect("Builder should not fail");
-    };
-
     info!("Received upgrade protocol request");
     let session_id = params.session_id;
     // Check if session_id exists in the store, this also removes session_id from
     // the store as each session_id can only be used once
-    if notary_globals
-        .store
-        .lock()
-        .unwrap()
-        .remove(&session_id)
-        .is_none()
-    {
-        let err_msg = format!("Session id {} does not exist", session_id);
-        error!(err_msg);
-        return NotaryServerError::BadProverRequest(err_msg).into_response();
+    let id = session_id.clone();
+    let (protocol_config, ttl) =
+        match run_blocking(&notary_globals.store, move |store| store.take(&id)).await {
+            Ok(Some(session)) => session,
+            Ok(None) => {
+                notary_globals
+                    .metrics
+                    .sessions_rejected
+                    .with_label_values(&["invalid_session"])
+                    .inc();
+                let err_msg = format!("Session id {} does not exist or has expired", session_id);
+                error!(err_msg);
+                return NotaryServerError::BadProverRequest(err_msg).into_response();
+            }
+            Err(err) => {
+                error!("Failed to look up session id {session_id}: {err}");
+                return NotaryServerError::Unexpected(err).into_response();
+            }
+        };
+
+    // Wait in the queue if the concurrency limit is reached, only once the session
+    // id is known to be valid
+    let permit = match notary_globals.queue.acquire().await {
+        Ok(permit) => permit,
+        Err(retry_after) => {
+            // Return the session id to the store so that the prover can retry with it
+            // until it was originally due to expire
+            let id = session_id.clone();
+            let config = protocol_config.clone();
+            if let Err(err) = run_blocking(&notary_globals.store, move |store| {
+                store.insert(&id, &config, ttl)
+            })
+            .await
+            {
+                error!("Failed to return session id {session_id} to the store: {err}");
+                return NotaryServerError::Unexpected(err).into_response();
+            }
+
+            notary_globals
+                .metrics
+                .sessions_rejected
+                .with_label_values(&["busy"])
+                .inc();
+            return Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .header(header::RETRY_AFTER, retry_after.as_secs())
+                .body(Body::default())
+                .expect("Builder should not fail");
+        }
     };
+
+    // Keep the bytes reserved from the quota of the client until the
+    // notarization is over, see `notary_service`
+    notary_globals.rate_limiter.start_session(
+        &session_id,
+        Duration::from_secs(notary_globals.notarization_config.timeout),
+    );
+
     // This completes the HTTP Upgrade request and returns a successful response to
     // the client, meanwhile initiating the websocket or tcp connection
     match protocol_upgrade {
         ProtocolUpgrade::Ws(ws) => ws.on_upgrade(move |socket| async move {
-            websocket_notarize(socket, notary_globals, session_id).await;
+            let queue = notary_globals.queue.clone();
+            let start = Instant::now();
+            websocket_notarize(socket, notary_globals, session_id, protocol_config).await;
+            queue.record(start.elapsed());
             drop(permit);
         }),
         ProtocolUpgrade::Tcp(tcp) => tcp.on_upgrade(move |stream| async move {
-            tcp_notarize(stream, notary_globals, session_id).await;
+            let queue = notary_globals.queue.clone();
+            let start = Instant::now();
+            tcp_notarize(stream, notary_globals, session_id, protocol_config).await;
+            queue.record(start.elapsed());
             drop(permit);
         }),
     }
@@ -122,6 +176,9 @@ pub async fn upgrade_protocol(
 #[debug_handler(state = NotaryGlobals)]
 pub async fn initialize(
     State(notary_globals): State<NotaryGlobals>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    client_id: Option<Extension<ClientId>>,
+    claims: Option<Extension<JwtClaims>>,
     payload: Result<Json<NotarizationSessionRequest>, JsonRejection>,
 ) -> impl IntoResponse {
     info!(
@@ -129,8 +186,24 @@ pub async fn initialize(
         "Received request for initializing a notarization session"
     );
 
+    // Identify the client by its credential, or by its IP address if it is not
+    // authorized with one
+    let client_id = match client_id {
+        Some(Extension(client_id)) => client_id,
+        None => ClientId(format!("ip:{}", address.ip())),
+    };
+    if let Err(exceeded) = notary_globals.rate_limiter.check_request(&client_id) {
+        error!(%client_id, "Request is rate limited: {exceeded}");
+        notary_globals
+            .metrics
+            .sessions_rejected
+            .with_label_values(&["rate_limited"])
+            .inc();
+        return NotaryServerError::RateLimited(exceeded).into_response();
+    }
+
     // Parse the body payload
-    let payload = match payload {
+    let Json(payload) = match payload {
         Ok(payload) => payload,
         Err(err) => {
             error!("Malformed payload submitted for initializing notarization: {err}");
@@ -138,67 +211,116 @@ pub async fn initialize(
         }
     };
 
-    // Ensure that the max_sent_data, max_recv_data submitted is not larger than the
-    // global max limits configured in notary server
-    if payload.max_sent_data.is_some() || payload.max_recv_data.is_some() {
-        if payload.max_sent_data.unwrap_or_default()
-            > notary_globals.notarization_config.max_sent_data
-        {
-            error!(
-                "Max sent data requested {:?} exceeds the global maximum threshold {:?}",
-                payload.max_sent_data.unwrap_or_default(),
-                notary_globals.notarization_config.max_sent_data
-            );
-            return NotaryServerError::BadProverRequest(
-                "Max sent data requested exceeds the global maximum threshold".to_string(),
-            )
-            .into_response();
+    // Apply the per-user limits from the JWT claims if JWT authorization is used
+    let notarization_config = match claims {
+        Some(Extension(claims)) => claims.limit(&notary_globals.notarization_config),
+        None => notary_globals.notarization_config.clone(),
+    };
+
+    let validator = match protocol_config_validator(&notarization_config) {
+        Ok(validator) => validator,
+        Err(err) => {
+            error!("Failed to build protocol config validator: {err}");
+            return NotaryServerError::from(err).into_response();
         }
-        if payload.max_recv_data.unwrap_or_default()
-            > notary_globals.notarization_config.max_recv_data
-        {
-            error!(
-                "Max recv data requested {:?} exceeds the global maximum threshold {:?}",
-                payload.max_recv_data.unwrap_or_default(),
-                notary_globals.notarization_config.max_recv_data
-            );
-            return NotaryServerError::BadProverRequest(
-                "Max recv data requested exceeds the global maximum threshold".to_string(),
+    };
+
+    // Use the protocol configuration submitted by the prover, or fall back to the
+    // global max limits configured in notary server for whichever of
+    // max_sent_data, max_recv_data is not submitted
+    let protocol_config = match payload.protocol_config {
+        Some(protocol_config) => protocol_config,
+        None => match ProtocolConfig::builder()
+            .max_sent_data(
+                payload
+                    .max_sent_data
+                    .unwrap_or(notarization_config.max_sent_data),
             )
-            .into_response();
-        }
+            .max_recv_data(
+                payload
+                    .max_recv_data
+                    .unwrap_or(notarization_config.max_recv_data),
+            )
+            .build()
+        {
+            Ok(protocol_config) => protocol_config,
+            Err(err) => {
+                error!("Invalid protocol configuration submitted: {err}");
+                return NotaryServerError::BadProverRequest(err.to_string()).into_response();
+            }
+        },
+    };
+
+    // Ensure that the protocol configuration is within the limits configured in
+    // notary server before any MPC setup happens
+    if let Err(err) = validator.validate(&protocol_config) {
+        error!("Protocol configuration requested is rejected: {err}");
+        return NotaryServerError::RejectedProtocolConfig(NotarizationSessionRejection {
+            message: err.to_string(),
+            limit: err.limit().copied(),
+        })
+        .into_response();
     }
 
     let prover_session_id = Uuid::new_v4().to_string();
+    let ttl = Duration::from_secs(notary_globals.session_config.ttl);
 
-    // Store the configuration data in a temporary store
-    notary_globals
-        .store
-        .lock()
-        .unwrap()
-        .insert(prover_session_id.clone(), ());
+    // Reserve the maximum bytes of the session from the daily quota of the client,
+    // the bytes which are not notarized are refunded once the session is over
+    let bytes = (protocol_config.max_sent_data() + protocol_config.max_recv_data()) as u64;
+    if let Err(exceeded) =
+        notary_globals
+            .rate_limiter
+            .reserve_bytes(&client_id, &prover_session_id, bytes, ttl)
+    {
+        error!(%client_id, "Request exceeds quota: {exceeded}");
+        notary_globals
+            .metrics
+            .sessions_rejected
+            .with_label_values(&["quota_exceeded"])
+            .inc();
+        return NotaryServerError::RateLimited(exceeded).into_response();
+    }
+
+    // Store the session id along with the accepted protocol configuration, which
+    // is enforced once the notarization starts
+    let id = prover_session_id.clone();
+    let config = protocol_config.clone();
+    if let Err(err) = run_blocking(&notary_globals.store, move |store| {
+        store.insert(&id, &config, ttl)
+    })
+    .await
+    {
+        error!("Failed to store session id: {err}");
+        return NotaryServerError::Unexpected(err).into_response();
+    }
 
     trace!("Latest store state: {:?}", notary_globals.store);
+    notary_globals.metrics.sessions_created.inc();
 
     // Return the session id in the response to the client
     (
         StatusCode::OK,
         Json(NotarizationSessionResponse {
             session_id: prover_session_id,
+            protocol_config: Some(protocol_config),
         }),
     )
         .into_response()
 }
 
-/// Run the notarization
+/// Run the notarization, the protocol configuration of the prover must not
+/// exceed the one accepted by the /session API
 pub async fn notary_service<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
     socket: T,
     notary_globals: NotaryGlobals,
     session_id: &str,
+    protocol_config: &ProtocolConfig,
 ) -> Result<(), NotaryServerError> {
     debug!(?session_id, "Starting notarization...");
 
-    let crypto_provider = notary_globals.crypto_provider.clone();
+    // Sign with the key which is active when the notarization starts
+    let crypto_provider = notary_globals.key_ring.crypto_provider()?;
 
     let mut att_config_builder = AttestationConfig::builder();
     att_config_builder
@@ -209,27 +331,136 @@ pub async fn notary_service<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
         att_config_builder.extension_validator(|_| Ok(()));
     }
 
+    if let Some(validity) = notary_globals.notarization_config.validity {
+        att_config_builder.validity(Duration::from_secs(validity));
+    }
+
     let att_config = att_config_builder
         .build()
         .map_err(|err| NotaryServerError::Notarization(Box::new(err)))?;
 
     let config = VerifierConfig::builder()
-        .protocol_config_validator(
-            ProtocolConfigValidator::builder()
-                .max_sent_data(notary_globals.notarization_config.max_sent_data)
-                .max_recv_data(notary_globals.notarization_config.max_recv_data)
-                .build()?,
-        )
-        .crypto_provider(crypto_provider)
+        .protocol_config_validator(session_config_validator(
+            protocol_config,
+            &notary_globals.notarization_config,
+        )?)
+        .crypto_provider(crypto_provider.clone())
         .build()?;
 
     #[allow(deprecated)]
-    timeout(
+    let notarize = async {
+        let mut verifier = Verifier::new(config)
+            .setup(socket.compat())
+            .await?
+            .run()
+            .await?;
+
+        let attestation = verifier.prepare_attestation(&att_config).await?;
+
+        // Sign in the blocking thread pool, as the signer may block, e.g. when
+        // signing with a remote signer
+        let provider = crypto_provider.clone();
+        let attestation = tokio::task::spawn_blocking(move || attestation.sign(&provider))
+            .await
+            .map_err(|err| eyre!("Signing task failed: {err}"))?
+            .map_err(|err| NotaryServerError::Notarization(Box::new(err)))?;
+
+        // Log the attestation before releasing it to the prover, so that every
+        // attestation the prover receives is in the transparency log
+        if let Some(log) = notary_globals.transparency_log.clone() {
+            let header = attestation.header.clone();
+            let index = tokio::task::spawn_blocking(move || log.append(&header))
+                .await
+                .map_err(|err| eyre!("Transparency log task failed: {err}"))??;
+            debug!(
+                ?session_id,
+                index, "Appended attestation to transparency log"
+            );
+        }
+
+        verifier.send_attestation(&attestation).await?;
+        verifier.close().await?;
+
+        let length = &attestation.body.connection_info().transcript_length;
+        Ok::<_, NotaryServerError>(length.sent as u64 + length.received as u64)
+    };
+
+    let result = match timeout(
         Duration::from_secs(notary_globals.notarization_config.timeout),
-        Verifier::new(config).notarize(socket.compat(), &att_config),
+        notarize,
     )
     .await
-    .map_err(|_| eyre!("Timeout reached before notarization completes"))??;
+    {
+        Ok(result) => result,
+        Err(_) => Err(eyre!("Timeout reached before notarization completes").into()),
+    };
+
+    // Only charge the notarized bytes against the quota of the client, or the
+    // entire reservation if the notarization failed
+    notary_globals
+        .rate_limiter
+        .settle(session_id, result.as_ref().ok().copied());
+
+    result.map(|_| ())
+}
+
+/// Build the validator of the protocol configuration requested by provers from
+/// the global limits configured in notary server
+fn protocol_config_validator(
+    config: &NotarizationProperties,
+) -> Result<ProtocolConfigValidator, ProtocolConfigValidatorBuilderError> {
+    let mut builder = ProtocolConfigValidator::builder();
+    builder
+        .max_sent_data(config.max_sent_data)
+        .max_recv_data(config.max_recv_data);
+
+    if let Some(max_recv_data_online) = config.max_recv_data_online {
+        builder.max_recv_data_online(max_recv_data_online);
+    }
+    if let Some(max_sent_records) = config.max_sent_records {
+        builder.max_sent_records(max_sent_records);
+    }
+    if let Some(max_recv_records) = config.max_recv_records {
+        builder.max_recv_records(max_recv_records);
+    }
+    if let Some(defer_decryption_from_start) = config.defer_decryption_from_start {
+        builder.defer_decryption_from_start(defer_decryption_from_start);
+    }
+    if let Some(network) = config.network {
+        builder.network(network);
+    }
+
+    builder.build()
+}
+
+/// Build the validator of the protocol configuration used by the prover in the
+/// MPC from the configuration accepted by the /session API, so that the prover
+/// can't exceed what it negotiated. Limits which were not negotiated fall back
+/// to the global limits configured in notary server
+fn session_config_validator(
+    protocol_config: &ProtocolConfig,
+    config: &NotarizationProperties,
+) -> Result<ProtocolConfigValidator, ProtocolConfigValidatorBuilderError> {
+    let mut builder = ProtocolConfigValidator::builder();
+    builder
+        .max_sent_data(protocol_config.max_sent_data())
+        .max_recv_data(protocol_config.max_recv_data())
+        .max_recv_data_online(protocol_config.max_recv_data_online())
+        .defer_decryption_from_start(protocol_config.defer_decryption_from_start())
+        .network(protocol_config.network());
+
+    if let Some(max_sent_records) = protocol_config
+        .max_sent_records()
+        .or(config.max_sent_records)
+    {
+        builder.max_sent_records(max_sent_records);
+    }
+    if let Some(max_recv_records) = protocol_config
+        .max_recv_records()
+        .or(config.max_recv_records)
+    {
+        builder.max_recv_records(max_recv_records);
+    }
 
-    Ok(())
+    builder.build()
 }
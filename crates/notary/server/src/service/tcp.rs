@@ -7,10 +7,13 @@ use axum_core::body::Body;
 use hyper::upgrade::{OnUpgrade, Upgraded};
 use hyper_util::rt::TokioIo;
 use std::future::Future;
+use tlsn_common::config::ProtocolConfig;
 use tokio::time::Instant;
 use tracing::{debug, error, info};
 
-use crate::{service::notary_service, types::NotaryGlobals, NotaryServerError};
+use crate::{
+    metrics::MeteredStream, service::notary_service, types::NotaryGlobals, NotaryServerError,
+};
 
 /// Custom extractor used to extract underlying TCP connection for TCP client —
 /// using the same upgrade primitives used by the WebSocket implementation where
@@ -84,10 +87,16 @@ pub async fn tcp_notarize(
     stream: TokioIo<Upgraded>,
     notary_globals: NotaryGlobals,
     session_id: String,
+    protocol_config: ProtocolConfig,
 ) {
     let start = Instant::now();
     debug!(?session_id, "Upgraded to tcp connection");
-    match notary_service(stream, notary_globals, &session_id).await {
+    let metrics = notary_globals.metrics.clone();
+    metrics.sessions_started.with_label_values(&["tcp"]).inc();
+    let stream = MeteredStream::new(stream, &metrics, "tcp");
+    let result = notary_service(stream, notary_globals, &session_id, &protocol_config).await;
+    metrics.observe_notarization("tcp", start.elapsed(), &result);
+    match result {
         Ok(_) => {
             info!(
                 ?session_id,
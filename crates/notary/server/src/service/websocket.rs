@@ -1,8 +1,10 @@
+use tlsn_common::config::ProtocolConfig;
 use tokio::time::Instant;
 use tracing::{debug, error, info};
 use ws_stream_tungstenite::WsStream;
 
 use crate::{
+    metrics::MeteredStream,
     service::{axum_websocket::WebSocket, notary_service},
     types::NotaryGlobals,
 };
@@ -12,13 +14,21 @@ pub async fn websocket_notarize(
     socket: WebSocket,
     notary_globals: NotaryGlobals,
     session_id: String,
+    protocol_config: ProtocolConfig,
 ) {
     let start = Instant::now();
     debug!(?session_id, "Upgraded to websocket connection");
     // Wrap the websocket in WsStream so that we have AsyncRead and AsyncWrite
     // implemented
-    let stream = WsStream::new(socket.into_inner());
-    match notary_service(stream, notary_globals, &session_id).await {
+    let metrics = notary_globals.metrics.clone();
+    metrics
+        .sessions_started
+        .with_label_values(&["websocket"])
+        .inc();
+    let stream = MeteredStream::new(WsStream::new(socket.into_inner()), &metrics, "websocket");
+    let result = notary_service(stream, notary_globals, &session_id, &protocol_config).await;
+    metrics.observe_notarization("websocket", start.elapsed(), &result);
+    match result {
         Ok(_) => {
             info!(
                 ?session_id,
@@ -0,0 +1,341 @@
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs,
+    io::{ErrorKind, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tlsn_common::config::ProtocolConfig;
+use tokio::task::JoinHandle;
+use tracing::{debug, error};
+
+use crate::config::SessionProperties;
+
+/// Storage of the session ids issued by the /session API, along with the
+/// protocol configuration accepted for each session, until they are used by
+/// the /notarize API or expire
+pub trait SessionStore: Debug + Send + Sync {
+    /// Stores a session id with its protocol configuration, which expires after
+    /// `ttl`
+    fn insert(
+        &self,
+        session_id: &str,
+        protocol_config: &ProtocolConfig,
+        ttl: Duration,
+    ) -> Result<()>;
+
+    /// Removes a session id, returns its protocol configuration and the
+    /// remaining time before it expires if it existed and had not expired.
+    /// Each session id can only be taken once, even when the store is shared
+    fn take(&self, session_id: &str) -> Result<Option<(ProtocolConfig, Duration)>>;
+
+    /// Removes all expired session ids, returns the number of removed ids
+    fn purge_expired(&self) -> Result<usize>;
+}
+
+/// Build the session store according to the config
+pub fn build_session_store(config: &SessionProperties) -> Result<Arc<dyn SessionStore>> {
+    let store: Arc<dyn SessionStore> = if let Some(path) = &config.store_path {
+        debug!("Storing session ids in {path}");
+        Arc::new(FileSessionStore::new(path)?)
+    } else {
+        debug!("Storing session ids in memory");
+        Arc::new(MemorySessionStore::default())
+    };
+    Ok(store)
+}
+
+/// Run an operation on the store in the blocking thread pool, as stores may
+/// perform blocking file system operations
+pub async fn run_blocking<T, F>(store: &Arc<dyn SessionStore>, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn SessionStore) -> Result<T> + Send + 'static,
+{
+    let store = store.clone();
+    tokio::task::spawn_blocking(move || f(store.as_ref()))
+        .await
+        .map_err(|err| eyre!("Session store task failed: {err}"))?
+}
+
+/// Spawn a background task which periodically removes expired session ids
+/// from the store
+pub fn spawn_reaper(store: Arc<dyn SessionStore>, interval: Duration) -> Result<JoinHandle<()>> {
+    if interval.is_zero() {
+        return Err(eyre!(
+            "Interval between purges of session ids must be non-zero"
+        ));
+    }
+
+    Ok(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            match run_blocking(&store, |store| store.purge_expired()).await {
+                Ok(0) => {}
+                Ok(count) => debug!("Removed {count} expired session ids"),
+                // Ensure that error from purging doesn't bring the server down
+                Err(err) => error!("Failed to remove expired session ids: {err}"),
+            }
+        }
+    }))
+}
+
+/// In-memory session store, which is neither persisted nor shared between
+/// notary instances
+#[derive(Debug, Default)]
+pub struct MemorySessionStore {
+    sessions: Mutex<HashMap<String, (Instant, ProtocolConfig)>>,
+}
+
+impl SessionStore for MemorySessionStore {
+    fn insert(
+        &self,
+        session_id: &str,
+        protocol_config: &ProtocolConfig,
+        ttl: Duration,
+    ) -> Result<()> {
+        self.sessions.lock().unwrap().insert(
+            session_id.to_string(),
+            (Instant::now() + ttl, protocol_config.clone()),
+        );
+        Ok(())
+    }
+
+    fn take(&self, session_id: &str) -> Result<Option<(ProtocolConfig, Duration)>> {
+        let session = self.sessions.lock().unwrap().remove(session_id);
+        let now = Instant::now();
+        Ok(session
+            .filter(|(expiry, _)| *expiry > now)
+            .map(|(expiry, protocol_config)| (protocol_config, expiry - now)))
+    }
+
+    fn purge_expired(&self) -> Result<usize> {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap();
+        let len = sessions.len();
+        sessions.retain(|_, (expiry, _)| *expiry > now);
+        Ok(len - sessions.len())
+    }
+}
+
+/// File-backed session store, which survives restarts and can be shared
+/// between notary instances by pointing them to the same directory, e.g. a
+/// network volume
+///
+/// Each session id is a file containing its [`SessionFile`] as JSON. Taking a
+/// session id deletes its file, which succeeds for at most one instance
+#[derive(Debug)]
+pub struct FileSessionStore {
+    dir: PathBuf,
+}
+
+/// Content of the file of a session id
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionFile {
+    /// Expiry as a unix timestamp in milliseconds
+    expiry: u64,
+    protocol_config: ProtocolConfig,
+}
+
+impl FileSessionStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .map_err(|err| eyre!("Failed to create session store directory: {err}"))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, session_id: &str) -> Result<PathBuf> {
+        // Session ids are provided by the prover, ensure they can't escape the directory
+        if session_id.is_empty()
+            || !session_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(eyre!("Invalid session id {session_id}"));
+        }
+        Ok(self.dir.join(session_id))
+    }
+
+    /// Read the session id stored at the path, returns None if it doesn't exist
+    fn read(path: &PathBuf) -> Result<Option<SessionFile>> {
+        match fs::read(path) {
+            Ok(content) => Ok(Some(serde_json::from_slice(&content).map_err(|err| {
+                eyre!("Malformed session file {}: {err}", path.display())
+            })?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Remove the file at the path, returns false if it was already removed
+    fn remove(path: &PathBuf) -> Result<bool> {
+        match fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn insert(
+        &self,
+        session_id: &str,
+        protocol_config: &ProtocolConfig,
+        ttl: Duration,
+    ) -> Result<()> {
+        let path = self.path(session_id)?;
+        let session = SessionFile {
+            expiry: unix_time_millis() + ttl.as_millis() as u64,
+            protocol_config: protocol_config.clone(),
+        };
+
+        // Write to a temporary file first so that other instances never read a
+        // partially written file
+        let tmp = self.dir.join(format!(".{session_id}.tmp"));
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&serde_json::to_vec(&session)?)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn take(&self, session_id: &str) -> Result<Option<(ProtocolConfig, Duration)>> {
+        let path = match self.path(session_id) {
+            Ok(path) => path,
+            // Such session id can never have been issued
+            Err(_) => return Ok(None),
+        };
+
+        let Some(session) = Self::read(&path)? else {
+            return Ok(None);
+        };
+
+        // Only the instance which removes the file may use the session id
+        let now = unix_time_millis();
+        if Self::remove(&path)? && session.expiry > now {
+            let ttl = Duration::from_millis(session.expiry - now);
+            Ok(Some((session.protocol_config, ttl)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn purge_expired(&self) -> Result<usize> {
+        let now = unix_time_millis();
+        let mut count = 0;
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            // Skip temporary files which are being written
+            if path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_none_or(|name| name.starts_with('.'))
+            {
+                continue;
+            }
+
+            match Self::read(&path) {
+                Ok(Some(session)) if session.expiry <= now => {
+                    if Self::remove(&path)? {
+                        count += 1;
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => error!("{err}"),
+            }
+        }
+        Ok(count)
+    }
+}
+
+fn unix_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time should be after unix epoch")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::{tempdir, TempDir};
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn file_store(dir: &TempDir) -> FileSessionStore {
+        FileSessionStore::new(dir.path()).unwrap()
+    }
+
+    fn protocol_config() -> ProtocolConfig {
+        ProtocolConfig::builder()
+            .max_sent_data(1 << 10)
+            .max_recv_data(1 << 12)
+            .build()
+            .unwrap()
+    }
+
+    fn check_store(store: &dyn SessionStore) {
+        let config = protocol_config();
+        store.insert("valid", &config, TTL).unwrap();
+        store.insert("expired", &config, Duration::ZERO).unwrap();
+
+        // Each session id can only be used once
+        let (taken, ttl) = store.take("valid").unwrap().unwrap();
+        assert_eq!(taken.max_sent_data(), config.max_sent_data());
+        assert_eq!(taken.max_recv_data(), config.max_recv_data());
+        assert!(ttl <= TTL);
+        assert!(store.take("valid").unwrap().is_none());
+        assert!(store.take("unknown").unwrap().is_none());
+        assert!(store.take("expired").unwrap().is_none());
+
+        store.insert("valid", &config, TTL).unwrap();
+        store.insert("expired", &config, Duration::ZERO).unwrap();
+        assert_eq!(store.purge_expired().unwrap(), 1);
+        assert!(store.take("valid").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_memory_session_store() {
+        check_store(&MemorySessionStore::default());
+    }
+
+    #[test]
+    fn test_file_session_store() {
+        let dir = tempdir().unwrap();
+        check_store(&file_store(&dir));
+    }
+
+    #[test]
+    fn test_file_session_store_is_shared() {
+        let dir = tempdir().unwrap();
+        let store = file_store(&dir);
+        let other = FileSessionStore::new(store.dir.clone()).unwrap();
+
+        store.insert("session", &protocol_config(), TTL).unwrap();
+        assert!(other.take("session").unwrap().is_some());
+        assert!(store.take("session").unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reaper_rejects_zero_interval() {
+        let store: Arc<dyn SessionStore> = Arc::new(MemorySessionStore::default());
+
+        assert!(spawn_reaper(store, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_file_session_store_rejects_path() {
+        let dir = tempdir().unwrap();
+        let store = file_store(&dir);
+
+        assert!(store.insert("../escape", &protocol_config(), TTL).is_err());
+        assert!(store.take("../escape").unwrap().is_none());
+    }
+}
@@ -0,0 +1,424 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use eyre::{eyre, Result};
+use notary_common::{ConsistencyProofResponse, InclusionProofResponse, TreeHeadsResponse};
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    sync::{Arc, Mutex, RwLock},
+};
+use tlsn_core::{
+    attestation::{Header, Uid},
+    hash::HashAlgId,
+    transparency::{ConsistencyProof, InclusionProof, SignedTreeHead, TransparencyLog},
+    CryptoProvider,
+};
+use tracing::{debug, error};
+
+use crate::{
+    error::NotaryServerError,
+    keys::KeyRing,
+    types::{ConsistencyProofQuery, InclusionProofQuery, NotaryGlobals},
+    util::parse_attestation_id,
+};
+
+/// Append-only transparency log of the headers of the attestations signed by
+/// the notary. The headers are persisted as JSON lines if a file path is set.
+/// The head of the log is signed by each published key once per append, and
+/// again when the keys are reloaded
+pub struct AttestationLog {
+    provider: CryptoProvider,
+    key_ring: Arc<KeyRing>,
+    inner: Mutex<Inner>,
+    /// Signed heads of the log, only replaced while holding the lock of the log
+    tree_heads: RwLock<Arc<TreeHeads>>,
+}
+
+/// Head of the log signed by each published key
+struct TreeHeads {
+    /// Generation of the key ring which signed the heads
+    generation: u64,
+    /// Size of the log
+    size: u64,
+    signed: Vec<SignedTreeHead>,
+}
+
+struct Inner {
+    log: TransparencyLog,
+    /// Index of each attestation in the log
+    index: BTreeMap<Uid, u64>,
+    file: Option<File>,
+}
+
+impl std::fmt::Debug for AttestationLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AttestationLog")
+            .field("size", &self.inner.lock().unwrap().log.len())
+            .finish()
+    }
+}
+
+impl AttestationLog {
+    /// Open the log persisted in the file, which is created if it doesn't
+    /// exist, or an in-memory log if no path is given. This blocks on the file
+    /// and the signers
+    pub fn open(path: Option<&str>, key_ring: Arc<KeyRing>) -> Result<Self> {
+        let provider = CryptoProvider::default();
+        let mut inner = Inner {
+            log: TransparencyLog::new(HashAlgId::SHA256),
+            index: BTreeMap::new(),
+            file: None,
+        };
+
+        if let Some(path) = path {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .read(true)
+                .open(path)
+                .map_err(|err| eyre!("Failed to open transparency log: {err}"))?;
+
+            for line in BufReader::new(&file).lines() {
+                let line = line.map_err(|err| eyre!("Failed to read transparency log: {err}"))?;
+                let header: Header = serde_json::from_str(&line)
+                    .map_err(|err| eyre!("Failed to parse transparency log entry: {err}"))?;
+                inner.append(&provider, &header)?;
+            }
+            debug!(
+                "Loaded {} attestations from transparency log",
+                inner.log.len()
+            );
+
+            inner.file = Some(file);
+        }
+
+        let tree_heads = sign_tree_heads(&provider, &key_ring, &inner.log)?;
+        Ok(Self {
+            provider,
+            key_ring,
+            inner: Mutex::new(inner),
+            tree_heads: RwLock::new(Arc::new(tree_heads)),
+        })
+    }
+
+    /// Append the header of a signed attestation, returns its index. The
+    /// header is added to the log, the new head is signed and the header is
+    /// then persisted to the file. The log is rolled back if signing or writing
+    /// fails. This blocks on the file and the signers, so it should be run in
+    /// the blocking thread pool
+    pub fn append(&self, header: &Header) -> Result<u64> {
+        let mut inner = self.inner.lock().unwrap();
+        let index = inner.append(&self.provider, header)?;
+        let tree_heads = match sign_tree_heads(&self.provider, &self.key_ring, &inner.log)
+            .and_then(|tree_heads| inner.persist(header).map(|_| tree_heads))
+        {
+            Ok(tree_heads) => tree_heads,
+            Err(err) => {
+                inner.rollback(header, index);
+                return Err(err);
+            }
+        };
+        *self.tree_heads.write().unwrap() = Arc::new(tree_heads);
+        Ok(index)
+    }
+
+    /// Returns the current head of the log signed by each published key. The
+    /// heads are signed again if the keys were reloaded since they were
+    /// signed, hence this may block on the signers
+    pub fn tree_heads(&self) -> Result<Vec<SignedTreeHead>> {
+        Ok(self.current_tree_heads()?.signed.clone())
+    }
+
+    /// Returns the proof that the attestation is included in the current log,
+    /// along with the head of the log signed by each published key, or `None`
+    /// if the attestation is not in the log. This may block on the signers,
+    /// and on the file while an attestation is appended
+    pub fn inclusion_proof(
+        &self,
+        id: &Uid,
+    ) -> Result<Option<(InclusionProof, Vec<SignedTreeHead>)>> {
+        let inner = self.inner.lock().unwrap();
+        let tree_heads = self.signed_tree_heads(&inner)?;
+        let Some(&leaf_index) = inner.index.get(id) else {
+            return Ok(None);
+        };
+        let proof = inner
+            .log
+            .inclusion_proof(&self.provider, leaf_index, tree_heads.size)?;
+        Ok(Some((proof, tree_heads.signed.clone())))
+    }
+
+    /// Returns the proof that the log of the second size extends the log of
+    /// the first size. This may block on the file while an attestation is
+    /// appended
+    pub fn consistency_proof(&self, first: u64, second: u64) -> Result<ConsistencyProof> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner.log.consistency_proof(&self.provider, first, second)?)
+    }
+
+    /// Returns the signed heads, which are signed again first if the keys were
+    /// reloaded since they were signed
+    fn current_tree_heads(&self) -> Result<Arc<TreeHeads>> {
+        let tree_heads = self.tree_heads.read().unwrap().clone();
+        if tree_heads.generation == self.key_ring.generation() {
+            return Ok(tree_heads);
+        }
+        self.signed_tree_heads(&self.inner.lock().unwrap())
+    }
+
+    /// Returns the signed heads of the log, whose lock must be held so that
+    /// the heads are of the current size and are signed once per change
+    fn signed_tree_heads(&self, inner: &Inner) -> Result<Arc<TreeHeads>> {
+        let tree_heads = self.tree_heads.read().unwrap().clone();
+        if tree_heads.generation == self.key_ring.generation() {
+            return Ok(tree_heads);
+        }
+        let tree_heads = Arc::new(sign_tree_heads(&self.provider, &self.key_ring, &inner.log)?);
+        *self.tree_heads.write().unwrap() = tree_heads.clone();
+        Ok(tree_heads)
+    }
+}
+
+fn sign_tree_heads(
+    provider: &CryptoProvider,
+    key_ring: &KeyRing,
+    log: &TransparencyLog,
+) -> Result<TreeHeads> {
+    // Read the generation first, so that keys reloaded while signing are
+    // detected by the next request
+    let generation = key_ring.generation();
+    let head = log.tree_head(provider, now())?;
+    Ok(TreeHeads {
+        generation,
+        size: head.size,
+        signed: key_ring.tree_heads(&head)?,
+    })
+}
+
+impl Inner {
+    fn append(&mut self, provider: &CryptoProvider, header: &Header) -> Result<u64> {
+        let index = self.log.append(provider, header)?;
+        // Keep the first entry if an id is logged twice
+        self.index.entry(header.id.clone()).or_insert(index);
+        Ok(index)
+    }
+
+    /// Write the header to the file, if any. A partially written line is
+    /// truncated so that the file can still be loaded
+    fn persist(&mut self, header: &Header) -> Result<()> {
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+
+        let mut line = serde_json::to_string(header)?;
+        line.push('\n');
+        let len = file
+            .metadata()
+            .map_err(|err| eyre!("Failed to read transparency log: {err}"))?
+            .len();
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|err| {
+                let _ = file.set_len(len);
+                eyre!("Failed to write transparency log: {err}")
+            })
+    }
+
+    /// Remove the header appended at the index
+    fn rollback(&mut self, header: &Header, index: u64) {
+        self.log.truncate(index);
+        if self.index.get(&header.id) == Some(&index) {
+            self.index.remove(&header.id);
+        }
+    }
+}
+
+/// Handler of the /transparency/sth API, returns the current head of the log
+/// signed by each published key
+pub async fn tree_heads(State(notary_globals): State<NotaryGlobals>) -> Response {
+    let Some(log) = notary_globals.transparency_log.clone() else {
+        return not_enabled();
+    };
+
+    match run_blocking(log, |log| log.tree_heads()).await {
+        Ok(tree_heads) => (StatusCode::OK, Json(TreeHeadsResponse { tree_heads })).into_response(),
+        Err(err) => {
+            error!("{err}");
+            NotaryServerError::Unexpected(err).into_response()
+        }
+    }
+}
+
+/// Handler of the /transparency/inclusion API, returns the proof that an
+/// attestation is included in the current log
+pub async fn inclusion_proof(
+    State(notary_globals): State<NotaryGlobals>,
+    Query(params): Query<InclusionProofQuery>,
+) -> Response {
+    let Some(log) = notary_globals.transparency_log.clone() else {
+        return not_enabled();
+    };
+
+    let id = match parse_attestation_id(&params.id) {
+        Ok(id) => id,
+        Err(err) => return NotaryServerError::BadProverRequest(err.to_string()).into_response(),
+    };
+
+    match run_blocking(log, move |log| log.inclusion_proof(&id)).await {
+        Ok(Some((proof, tree_heads))) => (
+            StatusCode::OK,
+            Json(InclusionProofResponse { proof, tree_heads }),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            format!("Attestation {id} is not in the transparency log"),
+        )
+            .into_response(),
+        Err(err) => {
+            error!("{err}");
+            NotaryServerError::Unexpected(err).into_response()
+        }
+    }
+}
+
+/// Handler of the /transparency/consistency API, returns the proof that the
+/// log of the second size extends the log of the first size
+pub async fn consistency_proof(
+    State(notary_globals): State<NotaryGlobals>,
+    Query(params): Query<ConsistencyProofQuery>,
+) -> Response {
+    let Some(log) = notary_globals.transparency_log.clone() else {
+        return not_enabled();
+    };
+
+    match run_blocking(log, move |log| {
+        log.consistency_proof(params.first, params.second)
+    })
+    .await
+    {
+        Ok(proof) => (StatusCode::OK, Json(ConsistencyProofResponse { proof })).into_response(),
+        Err(err) => NotaryServerError::BadProverRequest(err.to_string()).into_response(),
+    }
+}
+
+/// Run an operation on the log in the blocking thread pool, as it may wait for
+/// an attestation to be appended or for the signers
+async fn run_blocking<T, F>(log: Arc<AttestationLog>, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&AttestationLog) -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(log.as_ref()))
+        .await
+        .map_err(|err| eyre!("Transparency log task failed: {err}"))?
+}
+
+fn not_enabled() -> Response {
+    (StatusCode::NOT_FOUND, "Transparency log is not enabled").into_response()
+}
+
+fn now() -> u64 {
+    Utc::now().timestamp() as u64
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+    use tlsn_core::{
+        attestation::VERSION,
+        hash::{Hash, TypedHash},
+        signing::{Secp256k1Signer, Signer},
+        transparency::TreeHead,
+    };
+
+    use super::*;
+
+    fn header(i: u8) -> Header {
+        Header {
+            id: Uid([i; 16]),
+            version: VERSION,
+            root: TypedHash {
+                alg: HashAlgId::SHA256,
+                value: Hash::default(),
+            },
+        }
+    }
+
+    fn key_ring() -> Arc<KeyRing> {
+        let signer: Box<dyn Signer + Send + Sync> =
+            Box::new(Secp256k1Signer::new(&[1u8; 32]).unwrap());
+        Arc::new(KeyRing::single(signer).unwrap())
+    }
+
+    fn tree_head(log: &AttestationLog) -> TreeHead {
+        let tree_heads = log.tree_heads().unwrap();
+        assert_eq!(tree_heads.len(), 1);
+        tree_heads[0]
+            .verify(&CryptoProvider::default())
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn test_attestation_log() {
+        let provider = CryptoProvider::default();
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("transparency-log.jsonl");
+        let path = path.to_str().unwrap();
+
+        let log = AttestationLog::open(Some(path), key_ring()).unwrap();
+        assert_eq!(tree_head(&log).size, 0);
+        for i in 0..3 {
+            assert_eq!(log.append(&header(i)).unwrap(), i as u64);
+        }
+        let old_head = tree_head(&log);
+        assert_eq!(old_head.size, 3);
+        drop(log);
+
+        // The log is reloaded from the file
+        let log = AttestationLog::open(Some(path), key_ring()).unwrap();
+        assert_eq!(tree_head(&log).root, old_head.root);
+        log.append(&header(3)).unwrap();
+
+        let (proof, tree_heads) = log.inclusion_proof(&Uid([1; 16])).unwrap().unwrap();
+        let head = tree_heads[0].verify(&provider).unwrap();
+        assert_eq!(head, &tree_head(&log));
+        assert_eq!(head.size, 4);
+        proof.verify(&provider, head, &header(1)).unwrap();
+
+        log.consistency_proof(3, 4)
+            .unwrap()
+            .verify(&provider, &old_head, head)
+            .unwrap();
+
+        assert!(log.inclusion_proof(&Uid([4; 16])).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_attestation_log_rolls_back_failed_write() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("transparency-log.jsonl");
+
+        let log = AttestationLog::open(Some(path.to_str().unwrap()), key_ring()).unwrap();
+        log.append(&header(0)).unwrap();
+        let old_head = tree_head(&log);
+
+        // Writing to a read-only handle fails
+        log.inner.lock().unwrap().file = Some(File::open(&path).unwrap());
+        assert!(log.append(&header(1)).is_err());
+
+        assert_eq!(tree_head(&log), old_head);
+        assert!(log.inclusion_proof(&Uid([1; 16])).unwrap().is_none());
+
+        // The file still only holds the first attestation
+        let log = AttestationLog::open(Some(path.to_str().unwrap()), key_ring()).unwrap();
+        assert_eq!(tree_head(&log).root, old_head.root);
+    }
+}
@@ -3,12 +3,20 @@ use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
 };
-use tlsn_core::CryptoProvider;
-use tokio::sync::Semaphore;
+use tlsn_core::attestation::SignedRevocationList;
 
 #[cfg(feature = "tee_quote")]
 use crate::tee::Quote;
-use crate::{auth::AuthorizationWhitelistRecord, config::NotarizationProperties};
+use crate::{
+    auth::{jwt::JwtValidator, AuthorizationWhitelistRecord},
+    config::{NotarizationProperties, SessionProperties},
+    keys::{KeyInfo, KeyRing},
+    metrics::Metrics,
+    rate_limit::{NotarizationQueue, RateLimiter},
+    revocation::RevocationLists,
+    store::SessionStore,
+    transparency::AttestationLog,
+};
 
 /// Response object of the /info API
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +33,23 @@ pub struct InfoResponse {
     pub quote: Quote,
 }
 
+/// Response object of the /keys API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeysResponse {
+    /// All published keys of the notary, including the ones which are no longer
+    /// used for signing
+    pub keys: Vec<KeyInfo>,
+}
+
+/// Response object of the /revocations API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationsResponse {
+    /// Lists of the revoked attestations, signed by each published key
+    pub lists: Vec<SignedRevocationList>,
+}
+
 /// Request query of the /notarize API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -33,32 +58,76 @@ pub struct NotarizationRequestQuery {
     pub session_id: String,
 }
 
+/// Request query of the /transparency/inclusion API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InclusionProofQuery {
+    /// Id of the attestation (hex encoded)
+    pub id: String,
+}
+
+/// Request query of the /transparency/consistency API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyProofQuery {
+    /// Size of the older log
+    pub first: u64,
+    /// Size of the newer log
+    pub second: u64,
+}
+
 /// Global data that needs to be shared with the axum handlers
 #[derive(Clone, Debug)]
 pub struct NotaryGlobals {
-    pub crypto_provider: Arc<CryptoProvider>,
+    /// Keys used to sign the notarization
+    pub key_ring: Arc<KeyRing>,
     pub notarization_config: NotarizationProperties,
-    /// A temporary storage to store session_id
-    pub store: Arc<Mutex<HashMap<String, ()>>>,
+    pub session_config: SessionProperties,
+    /// Storage of session ids which have not been used yet
+    pub store: Arc<dyn SessionStore>,
     /// Whitelist of API keys for authorization purpose
     pub authorization_whitelist: Option<Arc<Mutex<HashMap<String, AuthorizationWhitelistRecord>>>>,
-    /// A semaphore to acquire a permit for notarization
-    pub semaphore: Arc<Semaphore>,
+    /// Validator of JWTs for authorization purpose
+    pub jwt_validator: Option<Arc<JwtValidator>>,
+    /// A queue to acquire a permit for notarization
+    pub queue: Arc<NotarizationQueue>,
+    /// Per-client rate limits and quotas
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Prometheus metrics
+    pub metrics: Arc<Metrics>,
+    /// Transparency log of the signed attestations, if enabled
+    pub transparency_log: Option<Arc<AttestationLog>>,
+    /// Signed lists of the revoked attestations
+    pub revocation_lists: Arc<RevocationLists>,
 }
 
 impl NotaryGlobals {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        crypto_provider: Arc<CryptoProvider>,
+        key_ring: Arc<KeyRing>,
         notarization_config: NotarizationProperties,
+        session_config: SessionProperties,
+        store: Arc<dyn SessionStore>,
         authorization_whitelist: Option<Arc<Mutex<HashMap<String, AuthorizationWhitelistRecord>>>>,
-        semaphore: Arc<Semaphore>,
+        jwt_validator: Option<Arc<JwtValidator>>,
+        queue: Arc<NotarizationQueue>,
+        rate_limiter: Arc<RateLimiter>,
+        metrics: Arc<Metrics>,
+        transparency_log: Option<Arc<AttestationLog>>,
+        revocation_lists: Arc<RevocationLists>,
     ) -> Self {
         Self {
-            crypto_provider,
+            key_ring,
             notarization_config,
-            store: Default::default(),
+            session_config,
+            store,
             authorization_whitelist,
-            semaphore,
+            jwt_validator,
+            queue,
+            rate_limiter,
+            metrics,
+            transparency_log,
+            revocation_lists,
         }
     }
 }
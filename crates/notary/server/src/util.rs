@@ -1,6 +1,7 @@
 use eyre::{eyre, Result};
 use serde::de::DeserializeOwned;
 use std::path::Path;
+use tlsn_core::attestation::Uid;
 
 /// Parse a yaml configuration file into a struct
 pub fn parse_config_file<T: DeserializeOwned>(location: &str) -> Result<T> {
@@ -21,6 +22,15 @@ pub fn parse_csv_file<T: DeserializeOwned>(location: &str) -> Result<Vec<T>> {
     Ok(table)
 }
 
+/// Parse a hex encoded attestation id
+pub fn parse_attestation_id(id: &str) -> Result<Uid> {
+    hex::decode(id)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(Uid)
+        .ok_or_else(|| eyre!("Invalid attestation id: {id}"))
+}
+
 /// Prepend a file path with a base directory if the path is not absolute.
 pub fn prepend_file_path<S: AsRef<str>>(file_path: S, base_dir: S) -> Result<String> {
     let path = Path::new(file_path.as_ref());
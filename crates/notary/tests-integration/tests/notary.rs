@@ -9,14 +9,18 @@ use hyper_util::{
     client::legacy::{connect::HttpConnector, Builder},
     rt::{TokioExecutor, TokioIo},
 };
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
 use notary_client::{Accepted, ClientError, NotarizationRequest, NotaryClient, NotaryConnection};
 use notary_common::{ClientType, NotarizationSessionRequest, NotarizationSessionResponse};
 use rstest::rstest;
 use rustls::{Certificate, RootCertStore};
-use std::{string::String, time::Duration};
+use std::{
+    string::String,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use tls_core::verify::WebPkiVerifier;
 use tls_server_fixture::{bind_test_server_hyper, CA_CERT_DER, SERVER_DOMAIN};
-use tlsn_common::config::ProtocolConfig;
+use tlsn_common::config::{ProtocolConfig, ProtocolConfigLimit};
 use tlsn_core::{request::RequestConfig, transcript::TranscriptCommitConfig, CryptoProvider};
 use tlsn_prover::{Prover, ProverConfig};
 use tokio::{
@@ -29,8 +33,8 @@ use tracing_subscriber::EnvFilter;
 use ws_stream_tungstenite::WsStream;
 
 use notary_server::{
-    read_pem_file, run_server, AuthorizationProperties, NotarizationProperties,
-    NotaryServerProperties, TLSProperties,
+    read_pem_file, run_server, AuthorizationMode, AuthorizationProperties,
+    JwtAuthorizationProperties, NotarizationProperties, NotaryServerProperties, TLSProperties,
 };
 
 const MAX_SENT_DATA: usize = 1 << 13;
@@ -65,6 +69,7 @@ fn get_server_config(
         auth: AuthorizationProperties {
             enabled: auth_enabled,
             whitelist_path: Some("./fixture/auth/whitelist.csv".to_string()),
+            ..Default::default()
         },
         concurrency,
         ..Default::default()
@@ -309,6 +314,7 @@ async fn test_websocket_prover() {
         client_type: ClientType::Websocket,
         max_sent_data: Some(MAX_SENT_DATA),
         max_recv_data: Some(MAX_RECV_DATA),
+        protocol_config: None,
     })
     .unwrap();
 
@@ -535,3 +541,145 @@ async fn test_notarization_request_retry() {
         _ = sleep(Duration::from_secs(2)) => panic!("Expected client future to complete first")
     }
 }
+
+#[tokio::test]
+async fn test_rejected_protocol_config() {
+    let config = setup_config_and_server(100, 7053, false, false, 100).await;
+    let client = tcp_prover_client(config.clone());
+
+    let notarization_request = NotarizationRequest::builder()
+        .max_sent_data(MAX_SENT_DATA)
+        .max_recv_data(MAX_RECV_DATA)
+        .max_sent_records(1 << 16)
+        .build()
+        .unwrap();
+
+    let err = client
+        .request_notarization(notarization_request)
+        .await
+        .err()
+        .unwrap();
+
+    let rejection = err.rejection().expect("protocol config should be rejected");
+    let limit = rejection
+        .limit
+        .expect("rejection should be caused by a limit");
+    assert_eq!(limit.limit, ProtocolConfigLimit::MaxSentRecords);
+    assert_eq!(limit.requested, 1 << 16);
+}
+
+// Runs the MPC setup of a prover with the given protocol configuration.
+async fn setup_prover(socket: NotaryConnection, protocol_config: ProtocolConfig) -> bool {
+    let prover_config = ProverConfig::builder()
+        .server_name(SERVER_DOMAIN)
+        .protocol_config(protocol_config)
+        .build()
+        .unwrap();
+
+    Prover::new(prover_config)
+        .setup(socket.compat())
+        .await
+        .is_ok()
+}
+
+#[tokio::test]
+async fn test_session_protocol_config_is_enforced() {
+    let config = setup_config_and_server(100, 7054, false, false, 100).await;
+    let client = tcp_prover_client(config.clone());
+
+    // The session is accepted with limits lower than the global ones.
+    let notarization_request = NotarizationRequest::builder()
+        .max_sent_data(1 << 10)
+        .max_recv_data(1 << 10)
+        .build()
+        .unwrap();
+    let accepted = client
+        .request_notarization(notarization_request)
+        .await
+        .unwrap();
+
+    // The prover then attempts to use the global limits in the MPC.
+    let protocol_config = ProtocolConfig::builder()
+        .max_sent_data(MAX_SENT_DATA)
+        .max_recv_data(MAX_RECV_DATA)
+        .build()
+        .unwrap();
+
+    assert!(!setup_prover(accepted.io, protocol_config).await);
+}
+
+#[tokio::test]
+async fn test_jwt_limits_are_enforced() {
+    const JWT_MAX_DATA: usize = 1 << 10;
+
+    let mut config = get_server_config(7055, false, true, 100);
+    config.auth.mode = AuthorizationMode::Jwt;
+    config.auth.jwt = Some(JwtAuthorizationProperties {
+        algorithm: Algorithm::ES256,
+        public_key_path: Some("./fixture/auth/jwt.pub".to_string()),
+        jwks_path: None,
+        issuer: None,
+        audience: None,
+        claims: vec![],
+    });
+
+    let server_config = config.clone();
+    tokio::spawn(async move {
+        run_server(&server_config).await.unwrap();
+    });
+    sleep(Duration::from_millis(100)).await;
+
+    let exp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 600;
+    let jwt = jsonwebtoken::encode(
+        &Header::new(Algorithm::ES256),
+        &serde_json::json!({
+            "sub": "prover",
+            "exp": exp,
+            "max_sent_data": JWT_MAX_DATA,
+            "max_recv_data": JWT_MAX_DATA,
+        }),
+        &EncodingKey::from_ec_pem(include_bytes!("../fixture/auth/jwt.key")).unwrap(),
+    )
+    .unwrap();
+
+    let client = NotaryClient::builder()
+        .host(&config.host)
+        .port(config.port)
+        .enable_tls(false)
+        .jwt(jwt)
+        .build()
+        .unwrap();
+
+    // The session can't be requested with more than the limits of the claims.
+    let err = accepted_client(client.clone()).await.err().unwrap();
+    let limit = err
+        .rejection()
+        .expect("protocol config should be rejected")
+        .limit
+        .expect("rejection should be caused by a limit");
+    assert_eq!(limit.limit, ProtocolConfigLimit::MaxSentData);
+    assert_eq!(limit.maximum, JWT_MAX_DATA);
+
+    // Once a session is accepted within the limits, the MPC can't exceed them.
+    let notarization_request = NotarizationRequest::builder()
+        .max_sent_data(JWT_MAX_DATA)
+        .max_recv_data(JWT_MAX_DATA)
+        .build()
+        .unwrap();
+    let accepted = client
+        .request_notarization(notarization_request)
+        .await
+        .unwrap();
+
+    let protocol_config = ProtocolConfig::builder()
+        .max_sent_data(MAX_SENT_DATA)
+        .max_recv_data(MAX_RECV_DATA)
+        .build()
+        .unwrap();
+
+    assert!(!setup_prover(accepted.io, protocol_config).await);
+}
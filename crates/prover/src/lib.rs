@@ -28,7 +28,11 @@ use tls_client::{ClientConnection, ServerName as TlsServerName};
 use tls_client_async::{bind_client, TlsConnection};
 use tls_core::msgs::enums::ContentType;
 use tlsn_common::{
-    commit::{commit_records, hash::prove_hash},
+    commit::{
+        commit_records,
+        hash::{prove_hash, prove_masked_hash},
+        predicate::eval_predicates,
+    },
     context::build_mt_context,
     encoding,
     mux::attach_mux,
@@ -377,6 +381,8 @@ impl Prover<state::Committed> {
         }
 
         let mut hash_commitments = None;
+        let mut masked_hash_commitments = None;
+        let mut predicates = None;
         if let Some(commit_config) = config.transcript_commit() {
             if commit_config.has_encoding() {
                 let hasher = self
@@ -419,6 +425,30 @@ impl Prover<state::Committed> {
                     .map_err(ProverError::commit)?,
                 );
             }
+
+            if commit_config.has_masked_hash() {
+                masked_hash_commitments = Some(
+                    prove_masked_hash(
+                        vm,
+                        transcript_refs,
+                        commit_config
+                            .iter_masked_hash()
+                            .map(|((dir, idx), alg)| (*dir, idx.clone(), *alg)),
+                    )
+                    .map_err(ProverError::commit)?,
+                );
+            }
+
+            if commit_config.has_predicates() {
+                predicates = Some(
+                    eval_predicates(
+                        vm,
+                        transcript_refs,
+                        commit_config.iter_predicates().cloned(),
+                    )
+                    .map_err(ProverError::commit)?,
+                );
+            }
         }
 
         mux_fut
@@ -437,6 +467,26 @@ impl Prover<state::Committed> {
             }
         }
 
+        if let Some((hash_fut, hash_secrets)) = masked_hash_commitments {
+            let hash_commitments = hash_fut.try_recv().map_err(ProverError::commit)?;
+            for (commitment, secret) in hash_commitments.into_iter().zip(hash_secrets) {
+                output
+                    .transcript_commitments
+                    .push(TranscriptCommitment::MaskedHash(commitment));
+                output
+                    .transcript_secrets
+                    .push(TranscriptSecret::MaskedHash(secret));
+            }
+        }
+
+        if let Some(predicates) = predicates {
+            for predicate in predicates.try_recv().map_err(ProverError::commit)? {
+                output
+                    .transcript_commitments
+                    .push(TranscriptCommitment::Predicate(predicate));
+            }
+        }
+
         Ok(output)
     }
 
@@ -459,8 +509,18 @@ impl Prover<state::Committed> {
             // Temporarily, we reject attestation requests which contain hash commitments to
             // subsets of the transcript. We do this because we want to preserve the
             // obliviousness of the reference notary, and hash commitments currently leak
-            // the ranges which are being committed.
+            // the ranges which are being committed. Use
+            // `TranscriptCommitmentKind::MaskedHash` to commit to subsets without
+            // disclosing the ranges. Ranges which are the subject of a predicate are
+            // disclosed by the predicate itself, so they are allowed.
             for ((direction, idx), _) in config.iter_hash() {
+                if config
+                    .iter_predicates()
+                    .any(|predicate| predicate.direction == *direction && predicate.idx == *idx)
+                {
+                    continue;
+                }
+
                 let len = match direction {
                     Direction::Sent => self.transcript().sent().len(),
                     Direction::Received => self.transcript().received().len(),
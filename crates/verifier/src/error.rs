@@ -1,6 +1,7 @@
 use mpc_tls::MpcTlsError;
 use std::{error::Error, fmt};
 use tlsn_common::{encoding::EncodingError, zk_aes::ZkAesCtrError};
+use tlsn_core::PolicyError;
 
 /// Error for [`Verifier`](crate::Verifier).
 #[derive(Debug, thiserror::Error)]
@@ -47,6 +48,20 @@ impl VerifierError {
     {
         Self::new(ErrorKind::Verify, source)
     }
+
+    pub(crate) fn policy(source: PolicyError) -> Self {
+        Self::new(ErrorKind::Policy, source)
+    }
+
+    /// Returns the requirement of the
+    /// [`VerifyConfig`](tlsn_core::VerifyConfig) which was not met, if this
+    /// error was caused by one.
+    pub fn policy_error(&self) -> Option<&PolicyError> {
+        match self.kind {
+            ErrorKind::Policy => self.source.as_ref()?.downcast_ref(),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -58,6 +73,7 @@ enum ErrorKind {
     Commit,
     Attestation,
     Verify,
+    Policy,
 }
 
 impl fmt::Display for VerifierError {
@@ -72,6 +88,7 @@ impl fmt::Display for VerifierError {
             ErrorKind::Commit => f.write_str("commit error")?,
             ErrorKind::Attestation => f.write_str("attestation error")?,
             ErrorKind::Verify => f.write_str("verification error")?,
+            ErrorKind::Policy => f.write_str("policy error")?,
         }
 
         if let Some(source) = &self.source {
@@ -12,7 +12,9 @@ use std::sync::Arc;
 
 pub use config::{VerifierConfig, VerifierConfigBuilder, VerifierConfigBuilderError};
 pub use error::VerifierError;
-pub use tlsn_core::{VerifierOutput, VerifyConfig, VerifyConfigBuilder, VerifyConfigBuilderError};
+pub use tlsn_core::{
+    PolicyError, VerifierOutput, VerifyConfig, VerifyConfigBuilder, VerifyConfigBuilderError,
+};
 
 use futures::{AsyncRead, AsyncWrite, TryFutureExt};
 use mpc_tls::{FollowerData, MpcTlsFollower, SessionKeys};
@@ -23,7 +25,11 @@ use mpz_vm_core::prelude::*;
 use serio::{stream::IoStreamExt, SinkExt};
 use tls_core::msgs::enums::ContentType;
 use tlsn_common::{
-    commit::{commit_records, hash::verify_hash},
+    commit::{
+        commit_records,
+        hash::{verify_hash, verify_masked_hash},
+        predicate::eval_predicates,
+    },
     config::ProtocolConfig,
     context::build_mt_context,
     encoding,
@@ -33,7 +39,7 @@ use tlsn_common::{
     Role,
 };
 use tlsn_core::{
-    attestation::{Attestation, AttestationConfig},
+    attestation::{Attestation, AttestationConfig, UnsignedAttestation},
     connection::{ConnectionInfo, ServerName, TlsVersion, TranscriptLength},
     request::Request,
     transcript::TranscriptCommitment,
@@ -323,10 +329,7 @@ impl Verifier<state::Committed> {
     ///
     /// * `config` - Verification configuration.
     #[instrument(parent = &self.span, level = "info", skip_all, err)]
-    pub async fn verify(
-        &mut self,
-        #[allow(unused_variables)] config: &VerifyConfig,
-    ) -> Result<VerifierOutput, VerifierError> {
+    pub async fn verify(&mut self, config: &VerifyConfig) -> Result<VerifierOutput, VerifierError> {
         let state::Committed {
             mux_fut,
             ctx,
@@ -346,6 +349,10 @@ impl Verifier<state::Committed> {
             .poll_with(ctx.io_mut().expect_next().map_err(VerifierError::from))
             .await?;
 
+        config
+            .check_transcript_length(&connection_info.transcript_length)
+            .map_err(VerifierError::policy)?;
+
         let server_name = if let Some((name, cert_data)) = server_identity {
             cert_data
                 .verify_with_provider(
@@ -361,6 +368,13 @@ impl Verifier<state::Committed> {
             None
         };
 
+        config
+            .check_server_name(server_name.as_ref())
+            .map_err(VerifierError::policy)?;
+        config
+            .check_transcript(transcript.as_ref())
+            .map_err(VerifierError::policy)?;
+
         if let Some(partial_transcript) = &transcript {
             // Check ranges.
             if partial_transcript.len_sent() != connection_info.transcript_length.sent as usize
@@ -383,6 +397,8 @@ impl Verifier<state::Committed> {
 
         let mut transcript_commitments = Vec::new();
         let mut hash_commitments = None;
+        let mut masked_hash_commitments = None;
+        let mut predicates = None;
         if let Some(commit_config) = transcript_commit {
             if commit_config.encoding() {
                 let commitment = mux_fut
@@ -403,6 +419,39 @@ impl Verifier<state::Committed> {
                         .map_err(VerifierError::verify)?,
                 );
             }
+
+            if commit_config.has_masked_hash() {
+                masked_hash_commitments = Some(
+                    verify_masked_hash(
+                        vm,
+                        transcript_refs,
+                        commit_config.iter_masked_hash().cloned(),
+                    )
+                    .map_err(VerifierError::verify)?,
+                );
+            }
+
+            if commit_config.has_predicates() {
+                // Predicates must be over plaintext which is hash committed.
+                for predicate in commit_config.iter_predicates() {
+                    if !commit_config.iter_hash().any(|(direction, idx, _)| {
+                        *direction == predicate.direction && *idx == predicate.idx
+                    }) {
+                        return Err(VerifierError::verify(
+                            "prover sent predicate over plaintext which is not hash committed",
+                        ));
+                    }
+                }
+
+                predicates = Some(
+                    eval_predicates(
+                        vm,
+                        transcript_refs,
+                        commit_config.iter_predicates().cloned(),
+                    )
+                    .map_err(VerifierError::verify)?,
+                );
+            }
         }
 
         mux_fut
@@ -421,6 +470,22 @@ impl Verifier<state::Committed> {
             }
         }
 
+        if let Some(hash_commitments) = masked_hash_commitments {
+            for commitment in hash_commitments.try_recv().map_err(VerifierError::verify)? {
+                transcript_commitments.push(TranscriptCommitment::MaskedHash(commitment));
+            }
+        }
+
+        if let Some(predicates) = predicates {
+            for predicate in predicates.try_recv().map_err(VerifierError::verify)? {
+                transcript_commitments.push(TranscriptCommitment::Predicate(predicate));
+            }
+        }
+
+        config
+            .check_predicates(&transcript_commitments)
+            .map_err(VerifierError::policy)?;
+
         Ok(VerifierOutput {
             server_name,
             transcript,
@@ -441,6 +506,38 @@ impl Verifier<state::Committed> {
         &mut self,
         config: &AttestationConfig,
     ) -> Result<Attestation, VerifierError> {
+        #[allow(deprecated)]
+        let attestation = self
+            .prepare_attestation(config)
+            .await?
+            .sign(self.config.crypto_provider())
+            .map_err(VerifierError::attestation)?;
+
+        #[allow(deprecated)]
+        self.send_attestation(&attestation).await?;
+
+        Ok(attestation)
+    }
+
+    /// Receives the attestation request from the prover and builds the
+    /// attestation, without signing it.
+    ///
+    /// The attestation must be signed with the crypto provider of the verifier
+    /// and sent with [`Verifier::send_attestation`]. This is the first half of
+    /// [`Verifier::notarize`], which allows the caller to sign, e.g. with a
+    /// blocking signer, and to act on the attestation before it is released.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Attestation configuration.
+    #[instrument(parent = &self.span, level = "info", skip_all, err)]
+    #[deprecated(
+        note = "attestation functionality will be removed from this API in future releases."
+    )]
+    pub async fn prepare_attestation(
+        &mut self,
+        config: &AttestationConfig,
+    ) -> Result<UnsignedAttestation, VerifierError> {
         let VerifierOutput {
             server_name,
             transcript,
@@ -478,9 +575,25 @@ impl Verifier<state::Committed> {
             .server_ephemeral_key(server_ephemeral_key.clone())
             .transcript_commitments(transcript_commitments);
 
-        let attestation = builder
-            .build(self.config.crypto_provider())
-            .map_err(VerifierError::attestation)?;
+        builder
+            .build_unsigned(self.config.crypto_provider())
+            .map_err(VerifierError::attestation)
+    }
+
+    /// Sends the attestation to the prover.
+    ///
+    /// # Arguments
+    ///
+    /// * `attestation` - The attestation, see [`Verifier::prepare_attestation`].
+    #[instrument(parent = &self.span, level = "info", skip_all, err)]
+    #[deprecated(
+        note = "attestation functionality will be removed from this API in future releases."
+    )]
+    pub async fn send_attestation(
+        &mut self,
+        attestation: &Attestation,
+    ) -> Result<(), VerifierError> {
+        let state::Committed { mux_fut, ctx, .. } = &mut self.state;
 
         mux_fut
             .poll_with(
@@ -492,7 +605,7 @@ impl Verifier<state::Committed> {
 
         info!("Sent attestation");
 
-        Ok(attestation)
+        Ok(())
     }
 
     /// Closes the connection with the prover.